@@ -0,0 +1,592 @@
+//! dudect-style constant-time check of the tag verification paths.
+//!
+//! Each target is measured with two classes of inputs, one fixed and one random, interleaved
+//! at random. The fixed input is a valid message sealed under the fixed key by the target's own
+//! sealing function, so the fixed class takes the success path and the random class, with a
+//! random tag or key, the failure path. Welch's t-test is then applied to the execution times,
+//! both on all samples and on samples cropped at increasing percentiles, as described in "Dude,
+//! is my code constant time?" (Reparaz, Balasch, Verbauwhede). A |t| above 4.5 suggests a timing
+//! leak, above 10 it is almost certainly one.
+//!
+//! Run in release mode on an otherwise idle Linux host:
+//!
+//! ```sh
+//! cargo run --release --example dudect -- [measurements] [target filter]
+//! ```
+
+use gimli_crypto::aead::generic_array::GenericArray;
+use gimli_crypto::envelope;
+use gimli_crypto::hydrogen::secretbox;
+use gimli_crypto::key_wrap;
+use gimli_crypto::session::{self, Role};
+use gimli_crypto::{
+    AeadInPlace, Duplex, GimliAead, KEY_SIZE, KeyInit, NONCE_SIZE, Strobe, TAG_SIZE, Tag,
+    decrypt_in_place, encrypt_in_place,
+};
+use std::hint::black_box;
+use std::process::ExitCode;
+
+/// Length of the ciphertext passed to the decryption functions.
+const MESSAGE_SIZE: usize = 64;
+
+/// Size of the nonce, session id or synthetic IV material of an input.
+const IV_SIZE: usize = 32;
+
+/// Number of measurements per batch, results are reported after each batch.
+const BATCH_SIZE: usize = 100_000;
+
+/// Number of percentile thresholds used for cropping, as in the reference dudect.
+const PERCENTILES: usize = 100;
+
+/// |t| above which a target is reported as leaking.
+const T_THRESHOLD: f64 = 4.5;
+
+/// Small xorshift generator, good enough to pick classes and random inputs.
+struct Rng(u64);
+
+impl Rng {
+    fn from_time() -> Self {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as u64;
+        Self(nanos | 1)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn fill(&mut self, buffer: &mut [u8]) {
+        for chunk in buffer.chunks_mut(8) {
+            chunk.copy_from_slice(&self.next_u64().to_le_bytes()[..chunk.len()]);
+        }
+    }
+
+    fn array<const N: usize>(&mut self) -> [u8; N] {
+        let mut array = [0u8; N];
+        self.fill(&mut array);
+        array
+    }
+}
+
+/// Inputs of one measurement.
+#[derive(Clone)]
+struct Input {
+    key: [u8; KEY_SIZE],
+    iv: [u8; IV_SIZE],
+    ciphertext: [u8; MESSAGE_SIZE],
+    tag: Tag,
+}
+
+impl Input {
+    fn nonce(&self) -> [u8; NONCE_SIZE] {
+        self.iv[..NONCE_SIZE].try_into().unwrap()
+    }
+}
+
+/// A function under test and how its two input classes are generated.
+struct Target {
+    name: &'static str,
+    /// Encrypt `input.ciphertext` under `input.key` and set the tag, making the input valid.
+    seal: fn(input: &mut Input),
+    /// Create the input for class `0` (fixed) or `1` (random) from the `fixed` input.
+    prepare: fn(class: usize, fixed: &Input, rng: &mut Rng) -> Input,
+    /// Run the function under test, returning whether the input was accepted.
+    run: fn(input: &mut Input) -> bool,
+}
+
+/// Class 0 uses the fixed tag, class 1 a random tag, everything else is the same.
+fn vary_tag(class: usize, fixed: &Input, rng: &mut Rng) -> Input {
+    let mut input = fixed.clone();
+    if class == 1 {
+        input.tag = rng.array();
+    }
+    input
+}
+
+/// Class 0 uses the fixed key, class 1 a random key, everything else is the same.
+fn vary_key(class: usize, fixed: &Input, rng: &mut Rng) -> Input {
+    let mut input = fixed.clone();
+    if class == 1 {
+        input.key = rng.array();
+    }
+    input
+}
+
+fn seal_aead(input: &mut Input) {
+    input.tag = encrypt_in_place(
+        &input.key,
+        &input.nonce(),
+        b"associated data",
+        &mut input.ciphertext,
+    );
+}
+
+fn run_decrypt_in_place(input: &mut Input) -> bool {
+    let result = decrypt_in_place(
+        black_box(&input.key),
+        black_box(&input.nonce()),
+        black_box(b"associated data"),
+        black_box(&mut input.ciphertext),
+        black_box(&input.tag),
+    );
+    black_box(result).is_ok()
+}
+
+fn run_gimli_aead(input: &mut Input) -> bool {
+    let cipher = GimliAead::new(GenericArray::from_slice(black_box(&input.key)));
+    let result = cipher.decrypt_in_place_detached(
+        GenericArray::from_slice(black_box(&input.nonce())),
+        black_box(b"associated data"),
+        black_box(&mut input.ciphertext),
+        GenericArray::from_slice(black_box(&input.tag)),
+    );
+    black_box(result).is_ok()
+}
+
+/// The secretbox header is `iv[..SIV_SIZE] || tag`.
+fn seal_secretbox(input: &mut Input) {
+    let header = secretbox::encrypt_in_place_with_iv(
+        &input.key,
+        b"dudect  ",
+        0,
+        input.iv[..secretbox::IV_SIZE].try_into().unwrap(),
+        &mut input.ciphertext,
+    );
+    input.iv[..secretbox::SIV_SIZE].copy_from_slice(&header[..secretbox::SIV_SIZE]);
+    input.tag.copy_from_slice(&header[secretbox::SIV_SIZE..]);
+}
+
+fn run_secretbox(input: &mut Input) -> bool {
+    let mut header = [0u8; secretbox::HEADER_SIZE];
+    header[..secretbox::SIV_SIZE].copy_from_slice(&input.iv[..secretbox::SIV_SIZE]);
+    header[secretbox::SIV_SIZE..].copy_from_slice(&input.tag);
+    let result = secretbox::decrypt_in_place(
+        black_box(&input.key),
+        black_box(b"dudect  "),
+        black_box(0),
+        black_box(&header),
+        black_box(&mut input.ciphertext),
+    );
+    black_box(result).is_ok()
+}
+
+fn seal_duplex(input: &mut Input) {
+    let mut duplex = Duplex::new(b"dudect");
+    duplex.absorb_key(&input.key);
+    duplex.absorb(&input.iv);
+    duplex.encrypt(&mut input.ciphertext);
+    duplex.squeeze(&mut input.tag);
+}
+
+fn run_duplex(input: &mut Input) -> bool {
+    let mut duplex = Duplex::new(b"dudect");
+    duplex.absorb_key(black_box(&input.key));
+    duplex.absorb(black_box(&input.iv));
+    duplex.decrypt(black_box(&mut input.ciphertext));
+    black_box(duplex.verify(black_box(&input.tag))).is_ok()
+}
+
+fn seal_strobe(input: &mut Input) {
+    let mut strobe = Strobe::new(b"dudect");
+    strobe.key(&input.key, false);
+    strobe.ad(&input.iv, false);
+    strobe.send_enc(&mut input.ciphertext, false);
+    strobe.send_mac(&mut input.tag, false);
+}
+
+fn run_strobe(input: &mut Input) -> bool {
+    let mut strobe = Strobe::new(b"dudect");
+    strobe.key(black_box(&input.key), false);
+    strobe.ad(black_box(&input.iv), false);
+    strobe.recv_enc(black_box(&mut input.ciphertext), false);
+    black_box(strobe.recv_mac(black_box(&input.tag))).is_ok()
+}
+
+/// The first packet of a session with `iv` as the session id.
+fn seal_session(input: &mut Input) {
+    let mut sender = session::Sender::new(&input.key, &input.iv, Role::Initiator);
+    let mut packet = [0u8; session::OVERHEAD + MESSAGE_SIZE];
+    sender
+        .encrypt(b"associated data", &input.ciphertext, &mut packet)
+        .unwrap();
+    let (ciphertext, tag) = packet[session::SEQ_SIZE..].split_at(MESSAGE_SIZE);
+    input.ciphertext.copy_from_slice(ciphertext);
+    input.tag.copy_from_slice(tag);
+}
+
+fn run_session_decrypt(input: &mut Input) -> bool {
+    let mut packet = [0u8; session::OVERHEAD + MESSAGE_SIZE];
+    packet[session::SEQ_SIZE..session::SEQ_SIZE + MESSAGE_SIZE].copy_from_slice(&input.ciphertext);
+    packet[session::SEQ_SIZE + MESSAGE_SIZE..].copy_from_slice(&input.tag);
+
+    let mut receiver: session::Receiver =
+        session::Receiver::new(&input.key, &input.iv, Role::Responder);
+    let result = receiver.decrypt(
+        black_box(b"associated data"),
+        black_box(&packet),
+        black_box(&mut input.ciphertext),
+    );
+    black_box(result).is_ok()
+}
+
+/// Length of the envelopes, with `associated data` as AAD.
+const ENVELOPE_SIZE: usize = envelope::sealed_len(15, MESSAGE_SIZE);
+
+/// Offset of the ciphertext in the envelopes.
+const ENVELOPE_CIPHERTEXT: usize = envelope::HEADER_SIZE + 15;
+
+fn seal_envelope(input: &mut Input) {
+    let mut sealed = [0u8; ENVELOPE_SIZE];
+    envelope::seal_with_nonce(
+        1,
+        &input.key,
+        &input.nonce(),
+        b"associated data",
+        &input.ciphertext,
+        &mut sealed,
+    )
+    .unwrap();
+    input
+        .ciphertext
+        .copy_from_slice(&sealed[ENVELOPE_CIPHERTEXT..ENVELOPE_SIZE - TAG_SIZE]);
+    input
+        .tag
+        .copy_from_slice(&sealed[ENVELOPE_SIZE - TAG_SIZE..]);
+}
+
+fn run_envelope_open(input: &mut Input) -> bool {
+    // The header does not depend on the key, the ciphertext and tag are replaced below.
+    let mut sealed = [0u8; ENVELOPE_SIZE];
+    envelope::seal_with_nonce(
+        1,
+        &[0; KEY_SIZE],
+        &input.nonce(),
+        b"associated data",
+        &input.ciphertext,
+        &mut sealed,
+    )
+    .unwrap();
+    sealed[ENVELOPE_CIPHERTEXT..ENVELOPE_SIZE - TAG_SIZE].copy_from_slice(&input.ciphertext);
+    sealed[ENVELOPE_SIZE - TAG_SIZE..].copy_from_slice(&input.tag);
+
+    let keys = [(1, input.key)];
+    let result = envelope::open(
+        black_box(&keys),
+        black_box(&sealed),
+        black_box(&mut input.ciphertext),
+    );
+    black_box(result).is_ok()
+}
+
+/// A wrapped 32-byte key is `tag || iv[..16] || ciphertext[..32]`, the tag being the start of the
+/// SIV.
+fn seal_key_wrap(input: &mut Input) {
+    let key: [u8; 32] = input.ciphertext[..32].try_into().unwrap();
+    let wrapped = key_wrap::wrap(&input.key, &key);
+    input.tag.copy_from_slice(&wrapped[..TAG_SIZE]);
+    input.iv[..key_wrap::OVERHEAD - TAG_SIZE]
+        .copy_from_slice(&wrapped[TAG_SIZE..key_wrap::OVERHEAD]);
+    input.ciphertext[..32].copy_from_slice(&wrapped[key_wrap::OVERHEAD..]);
+}
+
+fn run_key_unwrap(input: &mut Input) -> bool {
+    let mut wrapped = [0u8; 32 + key_wrap::OVERHEAD];
+    wrapped[..TAG_SIZE].copy_from_slice(&input.tag);
+    wrapped[TAG_SIZE..key_wrap::OVERHEAD]
+        .copy_from_slice(&input.iv[..key_wrap::OVERHEAD - TAG_SIZE]);
+    wrapped[key_wrap::OVERHEAD..].copy_from_slice(&input.ciphertext[..32]);
+
+    let result = key_wrap::unwrap::<[u8; 32]>(black_box(&input.key), black_box(&wrapped));
+    black_box(result).is_ok()
+}
+
+/// All targets, add new tag or MAC verification functions here.
+const TARGETS: &[Target] = &[
+    Target {
+        name: "decrypt_in_place/tag",
+        seal: seal_aead,
+        prepare: vary_tag,
+        run: run_decrypt_in_place,
+    },
+    Target {
+        name: "decrypt_in_place/key",
+        seal: seal_aead,
+        prepare: vary_key,
+        run: run_decrypt_in_place,
+    },
+    Target {
+        name: "GimliAead/tag",
+        seal: seal_aead,
+        prepare: vary_tag,
+        run: run_gimli_aead,
+    },
+    Target {
+        name: "GimliAead/key",
+        seal: seal_aead,
+        prepare: vary_key,
+        run: run_gimli_aead,
+    },
+    Target {
+        name: "secretbox::decrypt_in_place/tag",
+        seal: seal_secretbox,
+        prepare: vary_tag,
+        run: run_secretbox,
+    },
+    Target {
+        name: "secretbox::decrypt_in_place/key",
+        seal: seal_secretbox,
+        prepare: vary_key,
+        run: run_secretbox,
+    },
+    Target {
+        name: "Duplex::verify/tag",
+        seal: seal_duplex,
+        prepare: vary_tag,
+        run: run_duplex,
+    },
+    Target {
+        name: "Duplex::verify/key",
+        seal: seal_duplex,
+        prepare: vary_key,
+        run: run_duplex,
+    },
+    Target {
+        name: "Strobe::recv_mac/tag",
+        seal: seal_strobe,
+        prepare: vary_tag,
+        run: run_strobe,
+    },
+    Target {
+        name: "Strobe::recv_mac/key",
+        seal: seal_strobe,
+        prepare: vary_key,
+        run: run_strobe,
+    },
+    Target {
+        name: "session::Receiver::decrypt/tag",
+        seal: seal_session,
+        prepare: vary_tag,
+        run: run_session_decrypt,
+    },
+    Target {
+        name: "session::Receiver::decrypt/key",
+        seal: seal_session,
+        prepare: vary_key,
+        run: run_session_decrypt,
+    },
+    Target {
+        name: "envelope::open/tag",
+        seal: seal_envelope,
+        prepare: vary_tag,
+        run: run_envelope_open,
+    },
+    Target {
+        name: "envelope::open/key",
+        seal: seal_envelope,
+        prepare: vary_key,
+        run: run_envelope_open,
+    },
+    Target {
+        name: "key_wrap::unwrap/tag",
+        seal: seal_key_wrap,
+        prepare: vary_tag,
+        run: run_key_unwrap,
+    },
+    Target {
+        name: "key_wrap::unwrap/key",
+        seal: seal_key_wrap,
+        prepare: vary_key,
+        run: run_key_unwrap,
+    },
+];
+
+/// Read a timestamp, in cycles where a cycle counter is available.
+#[inline(always)]
+fn timestamp() -> u64 {
+    #[cfg(target_arch = "x86_64")]
+    {
+        // SAFETY: `rdtsc` is available on all x86_64 CPUs.
+        unsafe { core::arch::x86_64::_rdtsc() }
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        use std::sync::OnceLock;
+        use std::time::Instant;
+
+        static START: OnceLock<Instant> = OnceLock::new();
+        START.get_or_init(Instant::now).elapsed().as_nanos() as u64
+    }
+}
+
+/// Online mean and variance for both classes, and Welch's t-statistic between them.
+#[derive(Clone, Copy, Default)]
+struct TTest {
+    count: [f64; 2],
+    mean: [f64; 2],
+    m2: [f64; 2],
+}
+
+impl TTest {
+    fn push(&mut self, class: usize, x: f64) {
+        self.count[class] += 1.0;
+        let delta = x - self.mean[class];
+        self.mean[class] += delta / self.count[class];
+        self.m2[class] += delta * (x - self.mean[class]);
+    }
+
+    fn t(&self) -> f64 {
+        if self.count[0] < 2.0 || self.count[1] < 2.0 {
+            return 0.0;
+        }
+        let var0 = self.m2[0] / (self.count[0] - 1.0);
+        let var1 = self.m2[1] / (self.count[1] - 1.0);
+        let den = (var0 / self.count[0] + var1 / self.count[1]).sqrt();
+        if den == 0.0 {
+            0.0
+        } else {
+            (self.mean[0] - self.mean[1]) / den
+        }
+    }
+
+    fn samples(&self) -> f64 {
+        self.count[0] + self.count[1]
+    }
+}
+
+/// State of the statistical tests of one target.
+struct Analysis {
+    /// Test on all samples.
+    full: TTest,
+    /// Tests on samples below each percentile threshold.
+    cropped: [TTest; PERCENTILES],
+    /// Cropping thresholds, set from the first batch.
+    thresholds: Option<[u64; PERCENTILES]>,
+}
+
+impl Analysis {
+    fn new() -> Self {
+        Self {
+            full: TTest::default(),
+            cropped: [TTest::default(); PERCENTILES],
+            thresholds: None,
+        }
+    }
+
+    fn update(&mut self, classes: &[usize], times: &[u64]) {
+        let thresholds = *self.thresholds.get_or_insert_with(|| {
+            let mut sorted = times.to_vec();
+            sorted.sort_unstable();
+            core::array::from_fn(|i| {
+                let p = 1.0 - 0.5f64.powf(10.0 * (i + 1) as f64 / PERCENTILES as f64);
+                sorted[(p * sorted.len() as f64) as usize]
+            })
+        });
+
+        for (&class, &time) in classes.iter().zip(times) {
+            self.full.push(class, time as f64);
+            for (test, &threshold) in self.cropped.iter_mut().zip(&thresholds) {
+                if time < threshold {
+                    test.push(class, time as f64);
+                }
+            }
+        }
+    }
+
+    /// The test with the largest |t|, ignoring tests with too few samples.
+    fn worst(&self) -> TTest {
+        core::iter::once(&self.full)
+            .chain(&self.cropped)
+            .filter(|test| test.samples() > 1000.0)
+            .max_by(|a, b| a.t().abs().total_cmp(&b.t().abs()))
+            .copied()
+            .unwrap_or(self.full)
+    }
+}
+
+/// Measure `target` `measurements` times, returning the largest |t| found.
+fn measure(target: &Target, measurements: usize, rng: &mut Rng) -> f64 {
+    let mut fixed = Input {
+        key: rng.array(),
+        iv: rng.array(),
+        ciphertext: rng.array(),
+        tag: [0u8; TAG_SIZE],
+    };
+    // Seal the fixed input so the fixed class measures the success path.
+    (target.seal)(&mut fixed);
+    assert!(
+        (target.run)(&mut fixed.clone()),
+        "{}: the fixed input must be valid",
+        target.name
+    );
+
+    let mut analysis = Analysis::new();
+    let mut done = 0;
+    while done < measurements {
+        let batch = BATCH_SIZE.min(measurements - done);
+        let classes: Vec<usize> = (0..batch).map(|_| (rng.next_u64() & 1) as usize).collect();
+        let mut inputs: Vec<Input> = classes
+            .iter()
+            .map(|&class| (target.prepare)(class, &fixed, rng))
+            .collect();
+
+        let mut times = Vec::with_capacity(batch);
+        for input in &mut inputs {
+            let start = timestamp();
+            (target.run)(input);
+            let end = timestamp();
+            times.push(end.wrapping_sub(start));
+        }
+
+        analysis.update(&classes, &times);
+        done += batch;
+
+        let worst = analysis.worst();
+        println!(
+            "{:<24} {:>10} samples, max |t| = {:>7.2}, mean {:.1} vs {:.1}",
+            target.name,
+            done,
+            worst.t().abs(),
+            worst.mean[0],
+            worst.mean[1],
+        );
+    }
+
+    analysis.worst().t().abs()
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let measurements = match args.next().map(|arg| arg.parse()) {
+        None => 1_000_000,
+        Some(Ok(n)) => n,
+        Some(Err(_)) => {
+            eprintln!("usage: dudect [measurements] [target filter]");
+            return ExitCode::FAILURE;
+        }
+    };
+    let filter = args.next().unwrap_or_default();
+
+    let mut rng = Rng::from_time();
+    let mut leaks = 0;
+    for target in TARGETS.iter().filter(|t| t.name.contains(&filter)) {
+        let t = measure(target, measurements, &mut rng);
+        if t > T_THRESHOLD {
+            println!("{}: possible timing leak (|t| = {t:.2})", target.name);
+            leaks += 1;
+        } else {
+            println!("{}: no leak detected (|t| = {t:.2})", target.name);
+        }
+    }
+
+    if leaks == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
@@ -0,0 +1,118 @@
+//! Generate NIST LWC known-answer test files from this implementation.
+//!
+//! The output follows the format of the `genkat_aead.c` and `genkat_hash.c` programs from the
+//! NIST lightweight cryptography competition, so new modes can ship KATs in the same format.
+//!
+//! ```sh
+//! cargo run --example genkat -- aead > LWC_AEAD_KAT_256_128.txt
+//! cargo run --example genkat -- hash > LWC_HASH_KAT_256.txt
+//! ```
+
+use gimli_crypto::{KEY_SIZE, NONCE_SIZE, encrypt_in_place, hash};
+use std::io::{self, BufWriter, Write};
+use std::process::ExitCode;
+
+/// Longest plaintext in the AEAD KAT.
+const MAX_MESSAGE_LENGTH_AEAD: usize = 32;
+
+/// Longest associated data in the AEAD KAT.
+const MAX_ASSOCIATED_DATA_LENGTH: usize = 32;
+
+/// Longest message in the hash KAT.
+const MAX_MESSAGE_LENGTH_HASH: usize = 1024;
+
+/// Buffer filled with `0, 1, 2, ...` as done by `init_buffer` in the NIST programs.
+fn init_buffer(len: usize) -> Vec<u8> {
+    (0..len).map(|i| i as u8).collect()
+}
+
+/// Write `label` followed by `data` as upper case hex.
+fn write_bstr(out: &mut impl Write, label: &str, data: &[u8]) -> io::Result<()> {
+    write!(out, "{label}")?;
+    for byte in data {
+        write!(out, "{byte:02X}")?;
+    }
+    writeln!(out)
+}
+
+/// Write an AEAD KAT for a mode with `key_len` byte keys and `nonce_len` byte nonces.
+///
+/// `encrypt` returns `ciphertext || tag` for `(key, nonce, associated data, plaintext)`.
+fn write_aead_kat(
+    out: &mut impl Write,
+    key_len: usize,
+    nonce_len: usize,
+    encrypt: impl Fn(&[u8], &[u8], &[u8], &[u8]) -> Vec<u8>,
+) -> io::Result<()> {
+    let key = init_buffer(key_len);
+    let nonce = init_buffer(nonce_len);
+    let msg = init_buffer(MAX_MESSAGE_LENGTH_AEAD);
+    let ad = init_buffer(MAX_ASSOCIATED_DATA_LENGTH);
+
+    let mut count = 1;
+    for mlen in 0..=MAX_MESSAGE_LENGTH_AEAD {
+        for adlen in 0..=MAX_ASSOCIATED_DATA_LENGTH {
+            writeln!(out, "Count = {count}")?;
+            count += 1;
+
+            write_bstr(out, "Key = ", &key)?;
+            write_bstr(out, "Nonce = ", &nonce)?;
+            write_bstr(out, "PT = ", &msg[..mlen])?;
+            write_bstr(out, "AD = ", &ad[..adlen])?;
+            write_bstr(
+                out,
+                "CT = ",
+                &encrypt(&key, &nonce, &ad[..adlen], &msg[..mlen]),
+            )?;
+            writeln!(out)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write a hash KAT, `hash` returns the digest of its input.
+fn write_hash_kat(out: &mut impl Write, hash: impl Fn(&[u8]) -> Vec<u8>) -> io::Result<()> {
+    let msg = init_buffer(MAX_MESSAGE_LENGTH_HASH);
+
+    for mlen in 0..=MAX_MESSAGE_LENGTH_HASH {
+        writeln!(out, "Count = {}", mlen + 1)?;
+        write_bstr(out, "Msg = ", &msg[..mlen])?;
+        write_bstr(out, "MD = ", &hash(&msg[..mlen]))?;
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let mode = std::env::args().nth(1);
+    let mut out = BufWriter::new(io::stdout().lock());
+
+    let result = match mode.as_deref() {
+        Some("aead") => write_aead_kat(&mut out, KEY_SIZE, NONCE_SIZE, |key, nonce, ad, pt| {
+            let mut buffer = pt.to_vec();
+            let tag = encrypt_in_place(
+                key.try_into().unwrap(),
+                nonce.try_into().unwrap(),
+                ad,
+                &mut buffer,
+            );
+            buffer.extend_from_slice(&tag);
+            buffer
+        }),
+        Some("hash") => write_hash_kat(&mut out, |msg| hash(msg).to_vec()),
+        _ => {
+            eprintln!("usage: genkat <aead|hash>");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match result.and_then(|()| out.flush()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("genkat: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
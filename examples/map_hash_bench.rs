@@ -0,0 +1,71 @@
+//! Throughput of `GimliBuildHasher` against `std`'s SipHash-1-3 `RandomState`.
+//!
+//! Hashes keys of several lengths, as hash maps do, and inserts integer keys into a `HashMap`
+//! with each build hasher. Run in release mode on an otherwise idle host:
+//!
+//! ```sh
+//! cargo run --release --example map_hash_bench
+//! ```
+
+use gimli_crypto::GimliBuildHasher;
+use std::collections::HashMap;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Key lengths in bytes.
+const KEY_LENGTHS: [usize; 6] = [4, 8, 16, 32, 64, 1024];
+
+/// Bytes hashed per key length and build hasher.
+const BYTES_PER_RUN: usize = 64 << 20;
+
+/// Number of keys inserted into each map.
+const MAP_SIZE: u64 = 1_000_000;
+
+/// Time hashing `BYTES_PER_RUN` bytes as keys of `len` bytes.
+fn hash_keys(build_hasher: &impl BuildHasher, len: usize) -> Duration {
+    let key = vec![0xa5u8; len];
+    let start = Instant::now();
+    for _ in 0..BYTES_PER_RUN / len {
+        black_box(build_hasher.hash_one(black_box(&key[..])));
+    }
+    start.elapsed()
+}
+
+/// Time inserting `MAP_SIZE` integer keys.
+fn fill_map<S: BuildHasher>(build_hasher: S) -> Duration {
+    let mut map = HashMap::with_capacity_and_hasher(MAP_SIZE as usize, build_hasher);
+    let start = Instant::now();
+    for key in 0..MAP_SIZE {
+        map.insert(black_box(key), key);
+    }
+    black_box(&map);
+    start.elapsed()
+}
+
+fn mib_per_s(elapsed: Duration) -> f64 {
+    (BYTES_PER_RUN as f64 / (1 << 20) as f64) / elapsed.as_secs_f64()
+}
+
+fn main() {
+    let gimli = GimliBuildHasher::new(&[7u8; 32]);
+    let siphash = RandomState::new();
+
+    println!(
+        "{:>10} {:>14} {:>14}",
+        "key bytes", "Gimli MiB/s", "SipHash MiB/s"
+    );
+    for len in KEY_LENGTHS {
+        println!(
+            "{len:>10} {:>14.1} {:>14.1}",
+            mib_per_s(hash_keys(&gimli, len)),
+            mib_per_s(hash_keys(&siphash, len)),
+        );
+    }
+
+    println!();
+    println!("{MAP_SIZE} u64 inserts:");
+    println!("{:>10} {:>12.1?}", "Gimli", fill_map(gimli));
+    println!("{:>10} {:>12.1?}", "SipHash", fill_map(siphash));
+}
@@ -0,0 +1,53 @@
+//! Roundtrip random key/nonce/AAD/plaintext through the in-place and RustCrypto AEAD APIs.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use gimli_crypto::aead::generic_array::GenericArray;
+use gimli_crypto::{
+    AeadInPlace, GimliAead, KEY_SIZE, KeyInit, NONCE_SIZE, decrypt_in_place, encrypt_in_place,
+};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    key: [u8; KEY_SIZE],
+    nonce: [u8; NONCE_SIZE],
+    associated_data: Vec<u8>,
+    plaintext: Vec<u8>,
+}
+
+fuzz_target!(|input: Input| {
+    let mut buffer = input.plaintext.clone();
+    let tag = encrypt_in_place(
+        &input.key,
+        &input.nonce,
+        &input.associated_data,
+        &mut buffer,
+    );
+
+    // The RustCrypto wrapper must produce the same ciphertext and tag.
+    let cipher = GimliAead::new(&GenericArray::from(input.key));
+    let nonce = GenericArray::from(input.nonce);
+    let mut rc_buffer = input.plaintext.clone();
+    let rc_tag = cipher
+        .encrypt_in_place_detached(&nonce, &input.associated_data, &mut rc_buffer)
+        .expect("encryption cannot fail");
+    assert_eq!(buffer, rc_buffer);
+    assert_eq!(&tag[..], rc_tag.as_slice());
+
+    decrypt_in_place(
+        &input.key,
+        &input.nonce,
+        &input.associated_data,
+        &mut buffer,
+        &tag,
+    )
+    .expect("roundtrip must authenticate");
+    assert_eq!(buffer, input.plaintext);
+
+    cipher
+        .decrypt_in_place_detached(&nonce, &input.associated_data, &mut rc_buffer, &rc_tag)
+        .expect("roundtrip must authenticate");
+    assert_eq!(rc_buffer, input.plaintext);
+});
@@ -0,0 +1,65 @@
+//! Flip bits in the ciphertext or tag and require that authentication fails.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use gimli_crypto::aead::generic_array::GenericArray;
+use gimli_crypto::{
+    AeadInPlace, AuthenticationFailed, GimliAead, KEY_SIZE, KeyInit, NONCE_SIZE, TAG_SIZE,
+    decrypt_in_place, encrypt_in_place,
+};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    key: [u8; KEY_SIZE],
+    nonce: [u8; NONCE_SIZE],
+    associated_data: Vec<u8>,
+    plaintext: Vec<u8>,
+    /// Position of the flipped byte in `ciphertext || tag`, reduced modulo its length.
+    position: usize,
+    /// Bits to flip, a zero mask is replaced by `1`.
+    mask: u8,
+}
+
+fuzz_target!(|input: Input| {
+    let mut buffer = input.plaintext.clone();
+    let mut tag = encrypt_in_place(
+        &input.key,
+        &input.nonce,
+        &input.associated_data,
+        &mut buffer,
+    );
+
+    let mask = if input.mask == 0 { 1 } else { input.mask };
+    let position = input.position % (buffer.len() + TAG_SIZE);
+    if position < buffer.len() {
+        buffer[position] ^= mask;
+    } else {
+        tag[position - buffer.len()] ^= mask;
+    }
+
+    let mut rc_buffer = buffer.clone();
+    assert_eq!(
+        decrypt_in_place(
+            &input.key,
+            &input.nonce,
+            &input.associated_data,
+            &mut buffer,
+            &tag
+        ),
+        Err(AuthenticationFailed)
+    );
+
+    let cipher = GimliAead::new(&GenericArray::from(input.key));
+    assert!(
+        cipher
+            .decrypt_in_place_detached(
+                &GenericArray::from(input.nonce),
+                &input.associated_data,
+                &mut rc_buffer,
+                &GenericArray::from(tag),
+            )
+            .is_err()
+    );
+});
@@ -0,0 +1,34 @@
+//! Compare the one-shot hash against the incremental `Hasher` and `GimliHash` at random splits.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use gimli_crypto::{Digest, GimliHash, Hasher, hash};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    data: Vec<u8>,
+    /// Lengths of the chunks fed to the incremental hashers, the rest is fed at the end.
+    splits: Vec<u8>,
+}
+
+fuzz_target!(|input: Input| {
+    let expected = hash(&input.data);
+
+    let mut hasher = Hasher::new();
+    let mut digest = GimliHash::new();
+
+    let mut rest = &input.data[..];
+    for &split in &input.splits {
+        let (chunk, tail) = rest.split_at((split as usize).min(rest.len()));
+        hasher.update(chunk);
+        Digest::update(&mut digest, chunk);
+        rest = tail;
+    }
+    hasher.update(rest);
+    Digest::update(&mut digest, rest);
+
+    assert_eq!(hasher.finalize(), expected);
+    assert_eq!(digest.finalize().as_slice(), &expected[..]);
+});
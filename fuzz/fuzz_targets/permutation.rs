@@ -0,0 +1,16 @@
+//! Compare the SIMD permutation backend selected for the target against the portable one.
+
+#![no_main]
+
+use gimli_crypto::fuzzing::{permute, permute_portable};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|state: [u32; 12]| {
+    let mut simd = state;
+    let mut portable = state;
+
+    permute(&mut simd);
+    permute_portable(&mut portable);
+
+    assert_eq!(simd, portable);
+});
@@ -0,0 +1,11 @@
+fn main() {
+    println!("cargo::rerun-if-changed=c");
+
+    cc::Build::new()
+        .file("c/gimli.c")
+        .file("c/aead.c")
+        .file("c/hash.c")
+        .include("c")
+        .warnings(true)
+        .compile("gimli_reference");
+}
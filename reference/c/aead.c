@@ -0,0 +1,120 @@
+/* Gimli AEAD (`aead/gimli24v1`), reference implementation (public domain). */
+
+#include "gimli.h"
+
+int gimli_ref_aead_encrypt(
+  unsigned char *c,unsigned long long *clen,
+  const unsigned char *m,unsigned long long mlen,
+  const unsigned char *ad,unsigned long long adlen,
+  const unsigned char *nsec,
+  const unsigned char *npub,
+  const unsigned char *k
+)
+{
+  uint32_t state[12];
+  uint8_t *state_8 = (uint8_t *) state;
+  unsigned long long i;
+
+  (void) nsec;
+
+  *clen = mlen + 16;
+
+  /* Init state with key and nonce plus first permute */
+  for (i = 0;i < 16;++i) state_8[i] = npub[i];
+  for (i = 0;i < 32;++i) state_8[16 + i] = k[i];
+  gimli_ref_permute(state);
+
+  while (adlen >= 16) {
+    for (i = 0;i < 16;++i) state_8[i] ^= ad[i];
+    gimli_ref_permute(state);
+    ad += 16;
+    adlen -= 16;
+  }
+  for (i = 0;i < adlen;++i) state_8[i] ^= ad[i];
+  state_8[adlen] ^= 1;
+  state_8[47] ^= 1;
+  gimli_ref_permute(state);
+
+  while (mlen >= 16) {
+    for (i = 0;i < 16;++i) state_8[i] ^= m[i];
+    for (i = 0;i < 16;++i) c[i] = state_8[i];
+    gimli_ref_permute(state);
+    c += 16;
+    m += 16;
+    mlen -= 16;
+  }
+  for (i = 0;i < mlen;++i) state_8[i] ^= m[i];
+  for (i = 0;i < mlen;++i) c[i] = state_8[i];
+  c += mlen;
+  state_8[mlen] ^= 1;
+  state_8[47] ^= 1;
+  gimli_ref_permute(state);
+
+  for (i = 0;i < 16;++i) c[i] = state_8[i];
+
+  return 0;
+}
+
+int gimli_ref_aead_decrypt(
+  unsigned char *m,unsigned long long *mlen,
+  unsigned char *nsec,
+  const unsigned char *c,unsigned long long clen,
+  const unsigned char *ad,unsigned long long adlen,
+  const unsigned char *npub,
+  const unsigned char *k
+)
+{
+  uint32_t state[12];
+  uint8_t *state_8 = (uint8_t *) state;
+  unsigned long long i;
+  unsigned long long tlen;
+  uint32_t result;
+
+  (void) nsec;
+
+  if (clen < 16) return -1;
+  *mlen = tlen = clen - 16;
+
+  /* Init state with key and nonce plus first permute */
+  for (i = 0;i < 16;++i) state_8[i] = npub[i];
+  for (i = 0;i < 32;++i) state_8[16 + i] = k[i];
+  gimli_ref_permute(state);
+
+  while (adlen >= 16) {
+    for (i = 0;i < 16;++i) state_8[i] ^= ad[i];
+    gimli_ref_permute(state);
+    ad += 16;
+    adlen -= 16;
+  }
+  for (i = 0;i < adlen;++i) state_8[i] ^= ad[i];
+  state_8[adlen] ^= 1;
+  state_8[47] ^= 1;
+  gimli_ref_permute(state);
+
+  while (tlen >= 16) {
+    for (i = 0;i < 16;++i) m[i] = state_8[i] ^ c[i];
+    for (i = 0;i < 16;++i) state_8[i] = c[i];
+    gimli_ref_permute(state);
+    c += 16;
+    m += 16;
+    tlen -= 16;
+  }
+  for (i = 0;i < tlen;++i) m[i] = state_8[i] ^ c[i];
+  for (i = 0;i < tlen;++i) state_8[i] = c[i];
+  c += tlen;
+  m += tlen;
+  state_8[tlen] ^= 1;
+  state_8[47] ^= 1;
+  gimli_ref_permute(state);
+
+  result = 0;
+  for (i = 0;i < 16;++i) result |= c[i] ^ state_8[i];
+  result -= 1;
+  result = ((int32_t) result) >> 16;
+
+  tlen = *mlen;
+  m -= tlen;
+  for (i = 0;i < tlen;++i) m[i] &= result;
+
+  return ~result;
+}
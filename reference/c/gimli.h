@@ -0,0 +1,42 @@
+/*
+ * Gimli reference implementation, vendored from the `gimli24v1` NIST LWC submission
+ * (public domain). Function names are prefixed with `gimli_ref_` to avoid symbol clashes.
+ */
+
+#ifndef GIMLI_REFERENCE_H
+#define GIMLI_REFERENCE_H
+
+#include <stdint.h>
+
+#define GIMLI_REF_KEYBYTES 32
+#define GIMLI_REF_NPUBBYTES 16
+#define GIMLI_REF_ABYTES 16
+#define GIMLI_REF_HASHBYTES 32
+
+void gimli_ref_permute(uint32_t *state);
+
+int gimli_ref_aead_encrypt(
+  unsigned char *c,unsigned long long *clen,
+  const unsigned char *m,unsigned long long mlen,
+  const unsigned char *ad,unsigned long long adlen,
+  const unsigned char *nsec,
+  const unsigned char *npub,
+  const unsigned char *k
+);
+
+int gimli_ref_aead_decrypt(
+  unsigned char *m,unsigned long long *mlen,
+  unsigned char *nsec,
+  const unsigned char *c,unsigned long long clen,
+  const unsigned char *ad,unsigned long long adlen,
+  const unsigned char *npub,
+  const unsigned char *k
+);
+
+int gimli_ref_hash(
+  unsigned char *out,
+  const unsigned char *in,
+  unsigned long long inlen
+);
+
+#endif
@@ -0,0 +1,46 @@
+/* Gimli hash (`hash/gimli24v1`), reference implementation (public domain). */
+
+#include "gimli.h"
+
+#define rateInBytes 16
+
+int gimli_ref_hash(
+  unsigned char *out,
+  const unsigned char *in,
+  unsigned long long inlen
+)
+{
+  uint32_t state[12];
+  uint8_t *state_8 = (uint8_t *) state;
+  unsigned long long blockSize = 0;
+  unsigned long long i;
+
+  for (i = 0;i < 12;++i) state[i] = 0;
+
+  /* === Absorb all the input blocks === */
+  while (inlen > 0) {
+    blockSize = inlen < rateInBytes ? inlen : rateInBytes;
+    for (i = 0;i < blockSize;++i) state_8[i] ^= in[i];
+    in += blockSize;
+    inlen -= blockSize;
+
+    if (blockSize == rateInBytes) {
+      gimli_ref_permute(state);
+      blockSize = 0;
+    }
+  }
+
+  /* === Do the padding and switch to the squeezing phase === */
+  state_8[blockSize] ^= 0x1F;
+  /* Add the second bit of padding */
+  state_8[rateInBytes - 1] ^= 0x80;
+  /* Switch to the squeezing phase */
+  gimli_ref_permute(state);
+
+  /* === Squeeze out all the output blocks === */
+  for (i = 0;i < 16;++i) out[i] = state_8[i];
+  gimli_ref_permute(state);
+  for (i = 0;i < 16;++i) out[16 + i] = state_8[i];
+
+  return 0;
+}
@@ -0,0 +1,187 @@
+//! # Gimli reference implementation
+//!
+//! Safe wrappers around the reference C implementation of the Gimli permutation,
+//! `aead/gimli24v1` and `hash/gimli24v1` vendored in `c/`. This crate is only used as a
+//! dev-dependency of `gimli-crypto` for differential testing and is never published.
+
+use core::ffi::{c_int, c_uchar, c_ulonglong};
+
+/// Reference key size in bytes.
+pub const KEY_SIZE: usize = 32;
+
+/// Reference nonce size in bytes.
+pub const NONCE_SIZE: usize = 16;
+
+/// Reference tag size in bytes.
+pub const TAG_SIZE: usize = 16;
+
+/// Reference hash output size in bytes.
+pub const HASH_SIZE: usize = 32;
+
+unsafe extern "C" {
+    fn gimli_ref_permute(state: *mut u32);
+
+    fn gimli_ref_aead_encrypt(
+        c: *mut c_uchar,
+        clen: *mut c_ulonglong,
+        m: *const c_uchar,
+        mlen: c_ulonglong,
+        ad: *const c_uchar,
+        adlen: c_ulonglong,
+        nsec: *const c_uchar,
+        npub: *const c_uchar,
+        k: *const c_uchar,
+    ) -> c_int;
+
+    fn gimli_ref_aead_decrypt(
+        m: *mut c_uchar,
+        mlen: *mut c_ulonglong,
+        nsec: *mut c_uchar,
+        c: *const c_uchar,
+        clen: c_ulonglong,
+        ad: *const c_uchar,
+        adlen: c_ulonglong,
+        npub: *const c_uchar,
+        k: *const c_uchar,
+    ) -> c_int;
+
+    fn gimli_ref_hash(out: *mut c_uchar, input: *const c_uchar, inlen: c_ulonglong) -> c_int;
+}
+
+/// Apply the reference Gimli permutation to `state`.
+pub fn permute(state: &mut [u32; 12]) {
+    // SAFETY: The C function reads and writes exactly 12 words.
+    unsafe { gimli_ref_permute(state.as_mut_ptr()) }
+}
+
+/// Encrypt `plaintext` with the reference AEAD, returning `ciphertext || tag`.
+pub fn aead_encrypt(
+    key: &[u8; KEY_SIZE],
+    nonce: &[u8; NONCE_SIZE],
+    associated_data: &[u8],
+    plaintext: &[u8],
+) -> Vec<u8> {
+    let mut output = vec![0u8; plaintext.len() + TAG_SIZE];
+    let mut output_len = 0;
+
+    // SAFETY: All pointers are valid for the lengths passed, and `output` has room for
+    // `plaintext.len() + TAG_SIZE` bytes as required by the C function.
+    let ret = unsafe {
+        gimli_ref_aead_encrypt(
+            output.as_mut_ptr(),
+            &mut output_len,
+            plaintext.as_ptr(),
+            plaintext.len() as c_ulonglong,
+            associated_data.as_ptr(),
+            associated_data.len() as c_ulonglong,
+            core::ptr::null(),
+            nonce.as_ptr(),
+            key.as_ptr(),
+        )
+    };
+
+    assert_eq!(ret, 0);
+    assert_eq!(output_len as usize, output.len());
+    output
+}
+
+/// Decrypt `ciphertext || tag` with the reference AEAD, returning `None` on authentication
+/// failure.
+pub fn aead_decrypt(
+    key: &[u8; KEY_SIZE],
+    nonce: &[u8; NONCE_SIZE],
+    associated_data: &[u8],
+    ciphertext_and_tag: &[u8],
+) -> Option<Vec<u8>> {
+    let mut output = vec![0u8; ciphertext_and_tag.len().saturating_sub(TAG_SIZE)];
+    let mut output_len = 0;
+
+    // SAFETY: All pointers are valid for the lengths passed, and `output` has room for
+    // `ciphertext_and_tag.len() - TAG_SIZE` bytes as required by the C function, which
+    // rejects inputs shorter than a tag before writing anything.
+    let ret = unsafe {
+        gimli_ref_aead_decrypt(
+            output.as_mut_ptr(),
+            &mut output_len,
+            core::ptr::null_mut(),
+            ciphertext_and_tag.as_ptr(),
+            ciphertext_and_tag.len() as c_ulonglong,
+            associated_data.as_ptr(),
+            associated_data.len() as c_ulonglong,
+            nonce.as_ptr(),
+            key.as_ptr(),
+        )
+    };
+
+    (ret == 0).then_some(output)
+}
+
+/// Hash `input` with the reference `hash/gimli24v1`.
+pub fn hash(input: &[u8]) -> [u8; HASH_SIZE] {
+    let mut output = [0u8; HASH_SIZE];
+
+    // SAFETY: `input` is valid for `input.len()` bytes and `output` holds `HASH_SIZE` bytes.
+    let ret = unsafe {
+        gimli_ref_hash(
+            output.as_mut_ptr(),
+            input.as_ptr(),
+            input.len() as c_ulonglong,
+        )
+    };
+
+    assert_eq!(ret, 0);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permutation_test_vector() {
+        // Test vector from Gimli specification
+        let mut state = [
+            0x00000000, 0x9e3779ba, 0x3c6ef37a, 0xdaa66d46, 0x78dde724, 0x1715611a, 0xb54cdb2e,
+            0x53845566, 0xf1bbcfc8, 0x8ff34a5a, 0x2e2ac522, 0xcc624026,
+        ];
+
+        permute(&mut state);
+
+        assert_eq!(
+            state,
+            [
+                0xba11c85a, 0x91bad119, 0x380ce880, 0xd24c2c68, 0x3eceffea, 0x277a921c, 0x4f73a0bd,
+                0xda5a9cd8, 0x84b673f0, 0x34e52ff7, 0x9e2bef49, 0xf41bb8d6,
+            ]
+        );
+    }
+
+    #[test]
+    fn aead_roundtrip() {
+        let key = [1u8; KEY_SIZE];
+        let nonce = [2u8; NONCE_SIZE];
+
+        let sealed = aead_encrypt(&key, &nonce, b"ad", b"Hello, reference!");
+        assert_eq!(
+            aead_decrypt(&key, &nonce, b"ad", &sealed).as_deref(),
+            Some(&b"Hello, reference!"[..])
+        );
+        assert_eq!(aead_decrypt(&key, &nonce, b"AD", &sealed), None);
+        assert_eq!(
+            aead_decrypt(&key, &nonce, b"ad", &sealed[..TAG_SIZE - 1]),
+            None
+        );
+    }
+
+    #[test]
+    fn hash_empty() {
+        assert_eq!(
+            hash(b""),
+            [
+                0xb0, 0x63, 0x4b, 0x2c, 0x0b, 0x08, 0x2a, 0xed, 0xc5, 0xc0, 0xa2, 0xfe, 0x4e, 0xe3,
+                0xad, 0xcf, 0xc9, 0x89, 0xec, 0x05, 0xde, 0x6f, 0x00, 0xad, 0xdb, 0x04, 0xb3, 0xaa,
+                0xac, 0x27, 0x1f, 0x67,
+            ]
+        );
+    }
+}
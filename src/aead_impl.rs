@@ -39,7 +39,7 @@ pub type Tag = [u8; TAG_SIZE];
 pub struct AuthenticationFailed;
 
 /// Initialize the Gimli AEAD state with key and nonce.
-fn initialize(key: &[u8; KEY_SIZE], nonce: &[u8; NONCE_SIZE]) -> State {
+pub(crate) fn initialize(key: &[u8; KEY_SIZE], nonce: &[u8; NONCE_SIZE]) -> State {
     let mut state = State::new();
     let state_bytes = state.as_bytes_mut();
 
@@ -0,0 +1,211 @@
+//! # Balloon password hashing
+//!
+//! Memory-hard password hashing and key derivation with
+//! [Balloon hashing](https://eprint.iacr.org/2016/027) over `hash/gimli24v1`. The cost is tuned
+//! with the space cost `s_cost`, the number of 32-byte blocks of memory, and the time cost
+//! `t_cost`, the number of mixing rounds over that memory. Memory accesses depend only on the
+//! salt, not on the password.
+//!
+//! The algorithm is the one of the paper with `delta = 3`. Every hash call is prefixed with a
+//! little-endian 64-bit counter, the password and salt of the first call are prefixed with their
+//! little-endian 64-bit lengths and the index block is `t || m || i` as little-endian 64-bit
+//! integers. A block index is derived from the first 8 bytes of a hash in little-endian modulo
+//! `s_cost`.
+//!
+//! Hashes are stored as PHC strings, `$balloon-gimli$v=1$s=<s_cost>,t=<t_cost>$<salt>$<hash>`,
+//! with [`PhcHash`]. With the `alloc` feature `Balloon` implements the `password-hash` traits.
+//!
+//! The costs of a stored hash come from the hash itself, so verification takes a `max_params`
+//! limit that keeps a forged hash from exhausting memory or time.
+//!
+//! # Usage
+//!
+//! ```
+//! use gimli_crypto::balloon::{self, Params, PhcHash};
+//!
+//! let params = Params::new(16, 1).unwrap(); // Tiny for the example, see `Params::default()`
+//! let mut memory = [[0u8; balloon::BLOCK_SIZE]; 16];
+//! let salt = [7u8; 16]; // Use a random salt per password
+//!
+//! // Derive a key, e.g. for `encrypt_in_place`.
+//! let key = balloon::hash_password_into(b"hunter2", &salt, &params, &mut memory).unwrap();
+//!
+//! // Store the password hash as a PHC string.
+//! let mut buffer = [0u8; balloon::MAX_PHC_LEN];
+//! let phc = PhcHash::new(params, &salt, key).unwrap();
+//! let phc = phc.encode(&mut buffer).unwrap();
+//! assert!(phc.starts_with("$balloon-gimli$v=1$s=16,t=1$"));
+//!
+//! let stored = PhcHash::parse(phc).unwrap();
+//! assert!(stored.verify(b"hunter2", &params, &mut memory).is_ok());
+//! assert!(stored.verify(b"hunter3", &params, &mut memory).is_err());
+//! ```
+
+use crate::Hasher;
+use zeroize::Zeroize;
+
+#[cfg(feature = "alloc")]
+mod password_hash_impl;
+mod phc;
+
+#[cfg(feature = "alloc")]
+pub use password_hash_impl::Balloon;
+pub use phc::{ALGORITHM_ID, MAX_PHC_LEN, MAX_SALT_SIZE, MIN_SALT_SIZE, PhcHash, VERSION};
+
+#[cfg(feature = "alloc")]
+pub use password_hash; // For `Balloon` users
+
+/// Output size in bytes.
+pub const OUTPUT_SIZE: usize = 32;
+
+/// Size of a memory block in bytes.
+pub const BLOCK_SIZE: usize = 32;
+
+/// A memory block.
+pub type Block = [u8; BLOCK_SIZE];
+
+/// Number of dependencies per block and round, `delta` of the paper.
+const DELTA: u64 = 3;
+
+/// Errors of the password hashing functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// A cost is zero, the memory does not fit in `usize` or a cost is above `max_params`.
+    InvalidParams,
+    /// The memory has fewer than `s_cost` blocks.
+    MemoryTooSmall,
+    /// The salt is shorter than [`MIN_SALT_SIZE`] or longer than [`MAX_SALT_SIZE`].
+    InvalidSalt,
+    /// The PHC string is malformed or for another algorithm.
+    InvalidPhc,
+    /// The output buffer is too small for the result.
+    BufferTooSmall,
+    /// The password does not match the hash.
+    VerificationFailed,
+}
+
+/// Cost parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    s_cost: u32,
+    t_cost: u32,
+}
+
+impl Params {
+    /// Default space cost, 622592 blocks or 19 MiB.
+    ///
+    /// The memory of the OWASP minimum for Argon2id, `m=19 MiB, t=2, p=1`. A release build
+    /// takes a few seconds with the default time cost, fine for deriving a backup key but slow
+    /// for interactive logins.
+    pub const DEFAULT_S_COST: u32 = 19 * 1024 * 1024 / BLOCK_SIZE as u32;
+
+    /// Default time cost, the passes of the OWASP Argon2id minimum.
+    ///
+    /// Every round mixes three pseudorandom blocks into each block, so a round does more work
+    /// than an Argon2 pass.
+    pub const DEFAULT_T_COST: u32 = 2;
+
+    /// Create parameters with `s_cost` blocks of memory and `t_cost` rounds.
+    ///
+    /// Fails if either cost is zero or the memory size does not fit in `usize`.
+    pub const fn new(s_cost: u32, t_cost: u32) -> Result<Self, Error> {
+        if s_cost == 0 || t_cost == 0 || (s_cost as usize).checked_mul(BLOCK_SIZE).is_none() {
+            return Err(Error::InvalidParams);
+        }
+        Ok(Self { s_cost, t_cost })
+    }
+
+    /// Space cost in blocks.
+    pub const fn s_cost(&self) -> u32 {
+        self.s_cost
+    }
+
+    /// Time cost in rounds.
+    pub const fn t_cost(&self) -> u32 {
+        self.t_cost
+    }
+
+    /// Memory needed in bytes.
+    pub const fn memory_size(&self) -> usize {
+        self.s_cost as usize * BLOCK_SIZE
+    }
+
+    /// Whether neither cost is above the cost of `max_params`.
+    pub const fn fits_within(&self, max_params: &Params) -> bool {
+        self.s_cost <= max_params.s_cost && self.t_cost <= max_params.t_cost
+    }
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            s_cost: Self::DEFAULT_S_COST,
+            t_cost: Self::DEFAULT_T_COST,
+        }
+    }
+}
+
+/// Hash `password` with `salt`, using the first `s_cost` blocks of `memory`.
+///
+/// The output can be used directly as a key. `memory` is zeroed before returning.
+pub fn hash_password_into(
+    password: &[u8],
+    salt: &[u8],
+    params: &Params,
+    memory: &mut [Block],
+) -> Result<[u8; OUTPUT_SIZE], Error> {
+    let s_cost = params.s_cost as usize;
+    let buffer = memory.get_mut(..s_cost).ok_or(Error::MemoryTooSmall)?;
+    let mut counter = 0u64;
+
+    // Expand the input into the buffer.
+    buffer[0] = hash(
+        &mut counter,
+        &[
+            &(password.len() as u64).to_le_bytes(),
+            password,
+            &(salt.len() as u64).to_le_bytes(),
+            salt,
+        ],
+    );
+    for m in 1..s_cost {
+        buffer[m] = hash(&mut counter, &[&buffer[m - 1]]);
+    }
+
+    // Mix the buffer.
+    for t in 0..params.t_cost as u64 {
+        for m in 0..s_cost {
+            let previous = buffer[(m + s_cost - 1) % s_cost];
+            buffer[m] = hash(&mut counter, &[&previous, &buffer[m]]);
+
+            for i in 0..DELTA {
+                let mut index = [0u8; 24];
+                index[..8].copy_from_slice(&t.to_le_bytes());
+                index[8..16].copy_from_slice(&(m as u64).to_le_bytes());
+                index[16..].copy_from_slice(&i.to_le_bytes());
+
+                let other = hash(&mut counter, &[salt, &index]);
+                let other = u64::from_le_bytes(other[..8].try_into().unwrap()) % s_cost as u64;
+                buffer[m] = hash(&mut counter, &[&buffer[m], &buffer[other as usize]]);
+            }
+        }
+    }
+
+    let output = buffer[s_cost - 1];
+    buffer.as_flattened_mut().zeroize();
+    Ok(output)
+}
+
+/// `hash(counter++, parts...)`.
+fn hash(counter: &mut u64, parts: &[&[u8]]) -> Block {
+    let mut hasher = Hasher::new();
+    hasher.update(&counter.to_le_bytes());
+    for part in parts {
+        hasher.update(part);
+    }
+    *counter += 1;
+    hasher.finalize()
+}
+
+#[cfg(test)]
+mod tests;
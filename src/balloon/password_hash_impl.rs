@@ -0,0 +1,163 @@
+use super::{ALGORITHM_ID, BLOCK_SIZE, MAX_SALT_SIZE, MIN_SALT_SIZE, Params, VERSION};
+use alloc::vec;
+use password_hash::errors::InvalidValue;
+use password_hash::{
+    Decimal, Error, Ident, Output, ParamsString, PasswordHash, PasswordHasher, Result, Salt,
+};
+
+const IDENT: Ident<'static> = Ident::new_unwrap(ALGORITHM_ID);
+
+/// Balloon hashing with the `password-hash` traits.
+///
+/// Verification is provided by the blanket `PasswordVerifier` implementation. It rejects hashes
+/// with a cost above [`max_params`](Self::max_params) before allocating memory.
+///
+/// ```
+/// use gimli_crypto::balloon::password_hash::{
+///     PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
+/// };
+/// use gimli_crypto::balloon::{Balloon, Params};
+///
+/// let balloon = Balloon::new(Params::new(16, 1).unwrap()); // Use `Balloon::default()` in production
+/// let salt = SaltString::from_b64("c2FsdHNhbHRzYWx0").unwrap(); // Use a random salt per password
+/// let hash = balloon
+///     .hash_password(b"hunter2", &salt)
+///     .unwrap()
+///     .to_string();
+///
+/// let hash = PasswordHash::new(&hash).unwrap();
+/// assert!(balloon.verify_password(b"hunter2", &hash).is_ok());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Balloon {
+    params: Params,
+    max_params: Params,
+}
+
+impl Balloon {
+    /// Default verification limit, 2^22 blocks or 128 MiB and 16 rounds.
+    pub const DEFAULT_MAX_PARAMS: Params = Params {
+        s_cost: 1 << 22,
+        t_cost: 16,
+    };
+
+    /// Create a hasher with `params` for new hashes.
+    ///
+    /// The verification limit is the larger of `params` and
+    /// [`DEFAULT_MAX_PARAMS`](Self::DEFAULT_MAX_PARAMS) in each cost.
+    pub fn new(params: Params) -> Self {
+        let max_params = Params {
+            s_cost: params.s_cost.max(Self::DEFAULT_MAX_PARAMS.s_cost),
+            t_cost: params.t_cost.max(Self::DEFAULT_MAX_PARAMS.t_cost),
+        };
+        Self { params, max_params }
+    }
+
+    /// Parameters for new hashes.
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+
+    /// Largest costs accepted from a hash.
+    pub fn max_params(&self) -> &Params {
+        &self.max_params
+    }
+
+    /// Set the largest costs accepted from a hash, e.g. to allow hashes made with more memory.
+    pub fn set_max_params(&mut self, max_params: Params) {
+        self.max_params = max_params;
+    }
+}
+
+impl Default for Balloon {
+    fn default() -> Self {
+        Self::new(Params::default())
+    }
+}
+
+impl PasswordHasher for Balloon {
+    type Params = Params;
+
+    fn hash_password_customized<'a>(
+        &self,
+        password: &[u8],
+        algorithm: Option<Ident<'a>>,
+        version: Option<Decimal>,
+        params: Params,
+        salt: impl Into<Salt<'a>>,
+    ) -> Result<PasswordHash<'a>> {
+        if algorithm.is_some_and(|algorithm| algorithm != IDENT) {
+            return Err(Error::Algorithm);
+        }
+        if version.is_some_and(|version| version != VERSION) {
+            return Err(Error::Version);
+        }
+
+        let salt = salt.into();
+        let mut salt_buffer = [0u8; MAX_SALT_SIZE];
+        let salt_bytes = salt.decode_b64(&mut salt_buffer)?;
+        if salt_bytes.len() < MIN_SALT_SIZE {
+            return Err(Error::SaltInvalid(InvalidValue::TooShort));
+        }
+
+        if !params.fits_within(&self.max_params) {
+            return Err(Error::ParamValueInvalid(InvalidValue::TooLong));
+        }
+        let mut memory = vec![[0u8; BLOCK_SIZE]; params.s_cost() as usize];
+        let hash = super::hash_password_into(password, salt_bytes, &params, &mut memory)
+            .map_err(|_| Error::Crypto)?;
+
+        Ok(PasswordHash {
+            algorithm: IDENT,
+            version: Some(VERSION),
+            params: params.try_into()?,
+            salt: Some(salt),
+            hash: Some(Output::new(&hash)?),
+        })
+    }
+
+    fn hash_password<'a>(
+        &self,
+        password: &[u8],
+        salt: impl Into<Salt<'a>>,
+    ) -> Result<PasswordHash<'a>> {
+        self.hash_password_customized(password, None, None, self.params, salt)
+    }
+}
+
+impl TryFrom<&PasswordHash<'_>> for Params {
+    type Error = Error;
+
+    fn try_from(hash: &PasswordHash<'_>) -> Result<Self> {
+        let mut s_cost = None;
+        let mut t_cost = None;
+        for (name, value) in hash.params.iter() {
+            match name.as_str() {
+                "s" => s_cost = Some(value.decimal()?),
+                "t" => t_cost = Some(value.decimal()?),
+                _ => return Err(Error::ParamNameInvalid),
+            }
+        }
+
+        let s_cost = s_cost.ok_or(Error::ParamValueInvalid(InvalidValue::Malformed))?;
+        let t_cost = t_cost.ok_or(Error::ParamValueInvalid(InvalidValue::Malformed))?;
+        Params::new(s_cost, t_cost).map_err(|_| {
+            if s_cost == 0 || t_cost == 0 {
+                Error::ParamValueInvalid(InvalidValue::TooShort)
+            } else {
+                Error::ParamValueInvalid(InvalidValue::TooLong)
+            }
+        })
+    }
+}
+
+impl TryFrom<Params> for ParamsString {
+    type Error = Error;
+
+    fn try_from(params: Params) -> Result<Self> {
+        let mut string = ParamsString::new();
+        string.add_decimal("s", params.s_cost())?;
+        string.add_decimal("t", params.t_cost())?;
+        Ok(string)
+    }
+}
@@ -0,0 +1,242 @@
+use super::{Error, OUTPUT_SIZE, Params, hash_password_into};
+use subtle::ConstantTimeEq;
+
+/// Algorithm identifier in PHC strings.
+pub const ALGORITHM_ID: &str = "balloon-gimli";
+
+/// Version in PHC strings.
+pub const VERSION: u32 = 1;
+
+/// Smallest supported salt size in bytes, 16 or more is recommended.
+pub const MIN_SALT_SIZE: usize = 8;
+
+/// Largest supported salt size in bytes, the PHC string limit of 64 characters.
+pub const MAX_SALT_SIZE: usize = 48;
+
+/// Longest possible PHC string in bytes.
+pub const MAX_PHC_LEN: usize = "$balloon-gimli$v=1$s=4294967295,t=4294967295$".len()
+    + b64_len(MAX_SALT_SIZE)
+    + 1
+    + b64_len(OUTPUT_SIZE);
+
+/// A password hash with its parameters and salt, stored as a PHC string.
+#[derive(Debug, Clone)]
+pub struct PhcHash {
+    params: Params,
+    salt: [u8; MAX_SALT_SIZE],
+    salt_len: usize,
+    hash: [u8; OUTPUT_SIZE],
+}
+
+impl PhcHash {
+    /// Create from the output of [`hash_password_into`](super::hash_password_into) and its
+    /// inputs.
+    pub fn new(params: Params, salt: &[u8], hash: [u8; OUTPUT_SIZE]) -> Result<Self, Error> {
+        if !(MIN_SALT_SIZE..=MAX_SALT_SIZE).contains(&salt.len()) {
+            return Err(Error::InvalidSalt);
+        }
+        let mut salt_buffer = [0u8; MAX_SALT_SIZE];
+        salt_buffer[..salt.len()].copy_from_slice(salt);
+
+        Ok(Self {
+            params,
+            salt: salt_buffer,
+            salt_len: salt.len(),
+            hash,
+        })
+    }
+
+    /// Parse `$balloon-gimli$v=1$s=<s_cost>,t=<t_cost>$<salt>$<hash>`.
+    pub fn parse(phc: &str) -> Result<Self, Error> {
+        let mut fields = phc.split('$');
+        if fields.next() != Some("") || fields.next() != Some(ALGORITHM_ID) {
+            return Err(Error::InvalidPhc);
+        }
+        let version = fields.next().ok_or(Error::InvalidPhc)?;
+        if parse_param(version, "v")? != VERSION {
+            return Err(Error::InvalidPhc);
+        }
+
+        let (s_cost, t_cost) = fields
+            .next()
+            .and_then(|params| params.split_once(','))
+            .ok_or(Error::InvalidPhc)?;
+        let params = Params::new(parse_param(s_cost, "s")?, parse_param(t_cost, "t")?)
+            .map_err(|_| Error::InvalidPhc)?;
+
+        let mut salt = [0u8; MAX_SALT_SIZE];
+        let salt_len = b64_decode(fields.next().ok_or(Error::InvalidPhc)?, &mut salt)?;
+        if salt_len < MIN_SALT_SIZE {
+            return Err(Error::InvalidPhc);
+        }
+
+        let mut hash = [0u8; OUTPUT_SIZE];
+        let hash_len = b64_decode(fields.next().ok_or(Error::InvalidPhc)?, &mut hash)?;
+        if hash_len != OUTPUT_SIZE || fields.next().is_some() {
+            return Err(Error::InvalidPhc);
+        }
+
+        Ok(Self {
+            params,
+            salt,
+            salt_len,
+            hash,
+        })
+    }
+
+    /// Encode as a PHC string into `out`, which needs at most [`MAX_PHC_LEN`] bytes.
+    pub fn encode<'a>(&self, out: &'a mut [u8]) -> Result<&'a str, Error> {
+        let mut writer = Writer { out, len: 0 };
+        writer.push(b"$")?;
+        writer.push(ALGORITHM_ID.as_bytes())?;
+        writer.push(b"$v=")?;
+        writer.push_decimal(VERSION)?;
+        writer.push(b"$s=")?;
+        writer.push_decimal(self.params.s_cost())?;
+        writer.push(b",t=")?;
+        writer.push_decimal(self.params.t_cost())?;
+        writer.push(b"$")?;
+        writer.push_b64(self.salt())?;
+        writer.push(b"$")?;
+        writer.push_b64(&self.hash)?;
+
+        let Writer { out, len } = writer;
+        Ok(core::str::from_utf8(&out[..len]).unwrap())
+    }
+
+    /// Cost parameters.
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+
+    /// Salt.
+    pub fn salt(&self) -> &[u8] {
+        &self.salt[..self.salt_len]
+    }
+
+    /// Hash output.
+    pub fn hash(&self) -> &[u8; OUTPUT_SIZE] {
+        &self.hash
+    }
+
+    /// Check `password` against the hash in constant time, using `memory` as in
+    /// [`hash_password_into`](super::hash_password_into).
+    ///
+    /// Fails with [`Error::InvalidParams`] before hashing if a cost of the hash is above
+    /// `max_params`, e.g. the parameters new hashes are created with.
+    pub fn verify(
+        &self,
+        password: &[u8],
+        max_params: &Params,
+        memory: &mut [super::Block],
+    ) -> Result<(), Error> {
+        if !self.params.fits_within(max_params) {
+            return Err(Error::InvalidParams);
+        }
+        let hash = hash_password_into(password, self.salt(), &self.params, memory)?;
+        if bool::from(hash.ct_eq(&self.hash)) {
+            Ok(())
+        } else {
+            Err(Error::VerificationFailed)
+        }
+    }
+}
+
+/// Parse `<name>=<decimal>` without leading zeros.
+fn parse_param(field: &str, name: &str) -> Result<u32, Error> {
+    let value = field
+        .strip_prefix(name)
+        .and_then(|field| field.strip_prefix('='))
+        .ok_or(Error::InvalidPhc)?;
+    if value.is_empty()
+        || !value.bytes().all(|b| b.is_ascii_digit())
+        || (value.len() > 1 && value.starts_with('0'))
+    {
+        return Err(Error::InvalidPhc);
+    }
+    value.parse().map_err(|_| Error::InvalidPhc)
+}
+
+struct Writer<'a> {
+    out: &'a mut [u8],
+    len: usize,
+}
+
+impl Writer<'_> {
+    fn push(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        let end = self.len + bytes.len();
+        self.out
+            .get_mut(self.len..end)
+            .ok_or(Error::BufferTooSmall)?
+            .copy_from_slice(bytes);
+        self.len = end;
+        Ok(())
+    }
+
+    fn push_decimal(&mut self, value: u32) -> Result<(), Error> {
+        let mut digits = [0u8; 10];
+        let mut start = digits.len();
+        let mut value = value;
+        loop {
+            start -= 1;
+            digits[start] = b'0' + (value % 10) as u8;
+            value /= 10;
+            if value == 0 {
+                break;
+            }
+        }
+        self.push(&digits[start..])
+    }
+
+    fn push_b64(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        for chunk in bytes.chunks(3) {
+            let mut group = [0u8; 3];
+            group[..chunk.len()].copy_from_slice(chunk);
+            let bits = u32::from_be_bytes([0, group[0], group[1], group[2]]);
+
+            let mut chars = [0u8; 4];
+            for (i, c) in chars.iter_mut().enumerate() {
+                *c = B64_ALPHABET[(bits >> (18 - 6 * i) & 0x3f) as usize];
+            }
+            self.push(&chars[..chunk.len() + 1])?;
+        }
+        Ok(())
+    }
+}
+
+/// The B64 alphabet of the PHC string format, standard Base64 without padding.
+const B64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Length of the B64 encoding of `len` bytes.
+const fn b64_len(len: usize) -> usize {
+    (len * 4).div_ceil(3)
+}
+
+/// Decode canonical B64 `input` into `out`, returning the decoded length.
+fn b64_decode(input: &str, out: &mut [u8]) -> Result<usize, Error> {
+    let input = input.as_bytes();
+    if input.len() % 4 == 1 {
+        return Err(Error::InvalidPhc);
+    }
+    let len = input.len() * 3 / 4;
+    let out = out.get_mut(..len).ok_or(Error::InvalidPhc)?;
+
+    for (chunk, out) in input.chunks(4).zip(out.chunks_mut(3)) {
+        let mut bits = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            let value = B64_ALPHABET
+                .iter()
+                .position(|&a| a == c)
+                .ok_or(Error::InvalidPhc)?;
+            bits |= (value as u32) << (18 - 6 * i);
+        }
+        let bytes = bits.to_be_bytes();
+        out.copy_from_slice(&bytes[1..1 + out.len()]);
+
+        // Unused trailing bits must be zero for a canonical encoding.
+        if bytes[1 + out.len()..].iter().any(|&b| b != 0) {
+            return Err(Error::InvalidPhc);
+        }
+    }
+    Ok(len)
+}
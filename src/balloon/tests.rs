@@ -0,0 +1,333 @@
+extern crate std;
+use super::*;
+use std::vec;
+use std::vec::Vec;
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+const SALT: &[u8] = b"saltsaltsaltsalt";
+
+fn balloon(password: &[u8], salt: &[u8], s_cost: u32, t_cost: u32) -> [u8; OUTPUT_SIZE] {
+    let params = Params::new(s_cost, t_cost).unwrap();
+    let mut memory = vec![[0u8; BLOCK_SIZE]; s_cost as usize];
+    hash_password_into(password, salt, &params, &mut memory).unwrap()
+}
+
+#[test]
+fn test_known_outputs() {
+    let cases: [(&[u8], u32, u32, &str); 4] = [
+        (
+            b"",
+            1,
+            1,
+            "dd25847d401b72c77c16ae7ed361d9d36912b60c8ae4f2d2e6bdf4469388e1cb",
+        ),
+        (
+            b"hunter2",
+            1,
+            1,
+            "24144dcd9c424ca9bacce118bb7f828b90989688b6009ca9f445540f488cc27a",
+        ),
+        (
+            b"hunter2",
+            4,
+            1,
+            "8f4d328b4f7f1cd2a6fb6d027af143c9a5f0bcba4ca1b38c92c46bc0f03994b0",
+        ),
+        (
+            b"hunter2",
+            8,
+            3,
+            "6f983bcf350e1633ffc3d49bf5c1710b32b2b860e650381f8ef122fea3b63e1e",
+        ),
+    ];
+    for (password, s_cost, t_cost, expected) in cases {
+        assert_eq!(
+            balloon(password, SALT, s_cost, t_cost).to_vec(),
+            hex_to_bytes(expected),
+            "s_cost {s_cost} t_cost {t_cost}"
+        );
+    }
+}
+
+#[test]
+fn test_inputs_change_output() {
+    let reference = balloon(b"hunter2", SALT, 4, 2);
+    assert_ne!(balloon(b"hunter3", SALT, 4, 2), reference);
+    assert_ne!(balloon(b"hunter2", b"saltsaltsaltsalu", 4, 2), reference);
+    assert_ne!(balloon(b"hunter2", SALT, 5, 2), reference);
+    assert_ne!(balloon(b"hunter2", SALT, 4, 3), reference);
+
+    // Length prefixes separate the password from the salt.
+    assert_ne!(
+        balloon(b"hunter2s", b"altsaltsaltsalt", 4, 2),
+        balloon(b"hunter2", b"saltsaltsaltsalt", 4, 2)
+    );
+}
+
+#[test]
+fn test_memory() {
+    let params = Params::new(4, 1).unwrap();
+    assert_eq!(params.memory_size(), 4 * BLOCK_SIZE);
+
+    let mut memory = [[0u8; BLOCK_SIZE]; 3];
+    assert_eq!(
+        hash_password_into(b"hunter2", SALT, &params, &mut memory),
+        Err(Error::MemoryTooSmall)
+    );
+
+    // Extra memory is unused and the used part is zeroed afterwards.
+    let mut memory = [[0xffu8; BLOCK_SIZE]; 6];
+    let hash = hash_password_into(b"hunter2", SALT, &params, &mut memory).unwrap();
+    assert_eq!(hash, balloon(b"hunter2", SALT, 4, 1));
+    assert!(memory[..4].iter().flatten().all(|&b| b == 0));
+    assert!(memory[4..].iter().flatten().all(|&b| b == 0xff));
+}
+
+#[test]
+fn test_params() {
+    assert_eq!(Params::new(0, 1), Err(Error::InvalidParams));
+    assert_eq!(Params::new(1, 0), Err(Error::InvalidParams));
+    // Any memory size that fits in `usize` is allowed, limits are a verification policy.
+    let fits = (u32::MAX as usize).checked_mul(BLOCK_SIZE).is_some();
+    assert_eq!(Params::new(u32::MAX, u32::MAX).is_ok(), fits);
+
+    let params = Params::default();
+    assert_eq!(params.s_cost(), Params::DEFAULT_S_COST);
+    assert_eq!(params.t_cost(), Params::DEFAULT_T_COST);
+    assert_eq!(params.memory_size(), 19 * 1024 * 1024);
+
+    let max_params = Params::new(16, 2).unwrap();
+    assert!(Params::new(16, 2).unwrap().fits_within(&max_params));
+    assert!(Params::new(1, 1).unwrap().fits_within(&max_params));
+    assert!(!Params::new(17, 1).unwrap().fits_within(&max_params));
+    assert!(!Params::new(1, 3).unwrap().fits_within(&max_params));
+}
+
+#[test]
+fn test_phc_roundtrip() {
+    let params = Params::new(4, 1).unwrap();
+    let mut buffer = [0u8; MAX_PHC_LEN];
+
+    for salt_len in MIN_SALT_SIZE..=MAX_SALT_SIZE {
+        let salt: Vec<u8> = (0..salt_len).map(|i| (i * 37) as u8).collect();
+        let hash = [salt_len as u8; OUTPUT_SIZE];
+
+        let phc = PhcHash::new(params, &salt, hash).unwrap();
+        let decoded = PhcHash::parse(phc.encode(&mut buffer).unwrap()).unwrap();
+        assert_eq!(decoded.params(), &params);
+        assert_eq!(decoded.salt(), salt);
+        assert_eq!(decoded.hash(), &hash);
+    }
+}
+
+#[test]
+fn test_phc_verify() {
+    let params = Params::new(4, 1).unwrap();
+    let mut memory = [[0u8; BLOCK_SIZE]; 4];
+    let hash = hash_password_into(b"hunter2", SALT, &params, &mut memory).unwrap();
+    let phc = PhcHash::new(params, SALT, hash).unwrap();
+
+    phc.verify(b"hunter2", &params, &mut memory).unwrap();
+    assert_eq!(
+        phc.verify(b"hunter3", &params, &mut memory),
+        Err(Error::VerificationFailed)
+    );
+    assert_eq!(
+        phc.verify(b"hunter2", &params, &mut memory[..3]),
+        Err(Error::MemoryTooSmall)
+    );
+
+    // Costs above the limit are rejected before hashing.
+    assert_eq!(
+        phc.verify(b"hunter2", &Params::new(3, 1).unwrap(), &mut memory),
+        Err(Error::InvalidParams)
+    );
+    let phc = PhcHash::new(Params::new(4, 2).unwrap(), SALT, hash).unwrap();
+    assert_eq!(
+        phc.verify(b"hunter2", &params, &mut memory),
+        Err(Error::InvalidParams)
+    );
+}
+
+const VALID_PHC: &str = "$balloon-gimli$v=1$s=16,t=2$c2FsdHNhbHRzYWx0c2FsdA$\
+                         AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA";
+
+#[test]
+fn test_phc_encoding() {
+    let phc = PhcHash::new(Params::new(16, 2).unwrap(), SALT, [0; OUTPUT_SIZE]).unwrap();
+    let mut buffer = [0u8; MAX_PHC_LEN];
+    assert_eq!(phc.encode(&mut buffer).unwrap(), VALID_PHC);
+
+    let mut buffer = [0u8; VALID_PHC.len() - 1];
+    assert_eq!(phc.encode(&mut buffer), Err(Error::BufferTooSmall));
+
+    // The longest string fits exactly.
+    let phc = PhcHash::new(
+        Params {
+            s_cost: u32::MAX,
+            t_cost: u32::MAX,
+        },
+        &[0; MAX_SALT_SIZE],
+        [0; OUTPUT_SIZE],
+    )
+    .unwrap();
+    let mut buffer = [0u8; MAX_PHC_LEN];
+    assert_eq!(phc.encode(&mut buffer).unwrap().len(), MAX_PHC_LEN);
+}
+
+#[test]
+fn test_phc_invalid_salt() {
+    let params = Params::new(1, 1).unwrap();
+    let hash = [0; OUTPUT_SIZE];
+    assert_eq!(
+        PhcHash::new(params, &[0; MIN_SALT_SIZE - 1], hash).err(),
+        Some(Error::InvalidSalt)
+    );
+    assert_eq!(
+        PhcHash::new(params, &[0; MAX_SALT_SIZE + 1], hash).err(),
+        Some(Error::InvalidSalt)
+    );
+}
+
+#[test]
+fn test_phc_parse_rejects() {
+    assert!(PhcHash::parse(VALID_PHC).is_ok());
+
+    let replacements = [
+        ("$balloon-gimli$", "balloon-gimli$"),
+        ("balloon-gimli", "argon2id"),
+        ("v=1", "v=2"),
+        ("v=1$", ""),
+        ("s=16,t=2", "t=2,s=16"),
+        ("s=16", "s=016"),
+        ("s=16", "s=0"),
+        ("s=16", "s=+16"),
+        ("s=16", "s=4294967296"),
+        (",t=2", ""),
+        ("t=2", "t=2,p=1"),
+        // Salt too short, not canonical, invalid character or padded.
+        ("c2FsdHNhbHRzYWx0c2FsdA", "c2FsdA"),
+        ("c2FsdHNhbHRzYWx0c2FsdA", "c2FsdHNhbHRzYWx0c2FsdB"),
+        ("c2FsdHNhbHRzYWx0c2FsdA", "c2FsdHNhbHRz-Wx0c2FsdA"),
+        ("c2FsdHNhbHRzYWx0c2FsdA", "c2FsdHNhbHRzYWx0c2FsdA=="),
+        ("c2FsdHNhbHRzYWx0c2FsdA", "c2FsdHNhbHRzYWx0c2Fsd"),
+        // Hash too short, too long or missing.
+        ("AAA", "AA"),
+        ("AAA", "AAAA"),
+        ("$AAA", "AAA"),
+    ];
+    for (from, to) in replacements {
+        let phc = VALID_PHC.replacen(from, to, 1);
+        assert_eq!(PhcHash::parse(&phc).err(), Some(Error::InvalidPhc), "{phc}");
+    }
+    let non_canonical = std::format!("{}B", &VALID_PHC[..VALID_PHC.len() - 1]);
+    assert_eq!(
+        PhcHash::parse(&non_canonical).err(),
+        Some(Error::InvalidPhc)
+    );
+    // Large costs parse, `verify` checks them against its limit.
+    let large = VALID_PHC.replacen("t=2", "t=4294967295", 1);
+    assert_eq!(PhcHash::parse(&large).unwrap().params().t_cost(), u32::MAX);
+
+    let hashless = &VALID_PHC[..VALID_PHC.rfind('$').unwrap()];
+    assert_eq!(PhcHash::parse(hashless).err(), Some(Error::InvalidPhc));
+    let trailing = std::format!("{VALID_PHC}$");
+    assert_eq!(PhcHash::parse(&trailing).err(), Some(Error::InvalidPhc));
+}
+
+#[cfg(feature = "alloc")]
+mod traits {
+    use super::*;
+    use ::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+    use std::string::ToString;
+
+    #[test]
+    fn test_hash_and_verify() {
+        let hasher = Balloon::new(Params::new(16, 2).unwrap());
+        let salt = SaltString::encode_b64(SALT).unwrap();
+        let hash = hasher.hash_password(b"hunter2", &salt).unwrap();
+
+        assert_eq!(hash.algorithm.as_str(), ALGORITHM_ID);
+        assert_eq!(hash.version, Some(VERSION));
+        assert_eq!(
+            hash.hash.unwrap().as_bytes(),
+            balloon(b"hunter2", SALT, 16, 2)
+        );
+
+        hasher.verify_password(b"hunter2", &hash).unwrap();
+        assert!(hasher.verify_password(b"hunter3", &hash).is_err());
+
+        // Verification uses the parameters of the hash, not of the hasher.
+        let other = Balloon::new(Params::new(8, 1).unwrap());
+        other.verify_password(b"hunter2", &hash).unwrap();
+    }
+
+    #[test]
+    fn test_max_params() {
+        let hasher = Balloon::new(Params::new(16, 2).unwrap());
+        assert_eq!(hasher.max_params(), &Balloon::DEFAULT_MAX_PARAMS);
+        let large = Params::new(1 << 23, 20).unwrap();
+        assert_eq!(Balloon::new(large).max_params(), &large);
+
+        let salt = SaltString::encode_b64(SALT).unwrap();
+        let hash = hasher.hash_password(b"hunter2", &salt).unwrap();
+
+        let mut verifier = Balloon::new(Params::new(8, 1).unwrap());
+        verifier.set_max_params(Params::new(16, 1).unwrap());
+        assert_eq!(
+            verifier.verify_password(b"hunter2", &hash),
+            Err(::password_hash::Error::ParamValueInvalid(
+                ::password_hash::errors::InvalidValue::TooLong
+            ))
+        );
+        verifier.set_max_params(Params::new(16, 2).unwrap());
+        verifier.verify_password(b"hunter2", &hash).unwrap();
+    }
+
+    #[test]
+    fn test_phc_interop() {
+        let hasher = Balloon::new(Params::new(16, 2).unwrap());
+        let salt = SaltString::encode_b64(SALT).unwrap();
+        let serialized = hasher.hash_password(b"hunter2", &salt).unwrap().to_string();
+
+        let phc = PhcHash::parse(&serialized).unwrap();
+        let mut buffer = [0u8; MAX_PHC_LEN];
+        assert_eq!(phc.encode(&mut buffer).unwrap(), serialized);
+
+        let mut memory = [[0u8; BLOCK_SIZE]; 16];
+        phc.verify(b"hunter2", hasher.params(), &mut memory)
+            .unwrap();
+
+        let hash = PasswordHash::new(&serialized).unwrap();
+        hasher.verify_password(b"hunter2", &hash).unwrap();
+    }
+
+    #[test]
+    fn test_rejects() {
+        let hasher = Balloon::new(Params::new(16, 2).unwrap());
+
+        let short_salt = SaltString::encode_b64(&SALT[..MIN_SALT_SIZE - 1]).unwrap();
+        assert!(hasher.hash_password(b"hunter2", &short_salt).is_err());
+
+        for (from, to) in [
+            ("balloon-gimli", "argon2id"),
+            ("v=1", "v=2"),
+            (",t=2", ""),
+            ("t=2", "t=0"),
+            ("t=2", "t=2,p=1"),
+            // Oversized costs are rejected before allocating memory.
+            ("s=16", "s=4294967295"),
+            ("t=2", "t=4294967295"),
+        ] {
+            let phc = VALID_PHC.replacen(from, to, 1);
+            let hash = PasswordHash::new(&phc).unwrap();
+            assert!(hasher.verify_password(b"hunter2", &hash).is_err(), "{phc}");
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! # Keyed hashing for hash maps
+//!
+//! [`GimliBuildHasher`] is a [`BuildHasher`] for `HashMap`s and `hashbrown` maps whose keys are
+//! attacker-controlled. The hash is a keyed sponge over the Gimli permutation with the 32-byte
+//! key in the capacity, so without the key an attacker cannot predict which inputs collide and
+//! flood a bucket (HashDoS). It plays the role of SipHash in `std`'s `RandomState`, use a random
+//! key per process or map.
+//!
+//! The 64-bit output is for bucket selection only, it is not a MAC or a cryptographic digest.
+//! Each 16 bytes of input cost a full Gimli permutation, which makes it an order of magnitude
+//! slower than SipHash on desktop CPUs, see `examples/map_hash_bench.rs`. It avoids a second
+//! primitive in firmware that already carries Gimli.
+//!
+//! # Usage
+//!
+//! ```
+//! use core::hash::BuildHasher;
+//! use gimli_crypto::GimliBuildHasher;
+//!
+//! let build_hasher = GimliBuildHasher::new(&[7u8; 32]);
+//! assert_eq!(build_hasher.hash_one("key"), build_hasher.hash_one("key"));
+//! assert_ne!(
+//!     build_hasher.hash_one("key"),
+//!     GimliBuildHasher::new(&[8u8; 32]).hash_one("key")
+//! );
+//! ```
+
+use crate::RATE;
+use crate::gimli::{State, gimli};
+use core::hash::{BuildHasher, Hasher};
+
+/// Key size of [`GimliBuildHasher`] in bytes.
+pub const MAP_KEY_SIZE: usize = 32;
+
+/// Domain separation byte of the padding.
+const DOMAIN_MAP_HASH: u8 = 0x01;
+
+/// Padding marker byte.
+const PADDING_MARKER: u8 = 0x80;
+
+/// Keyed [`BuildHasher`] creating [`GimliMapHasher`]s.
+///
+/// With the `getrandom` feature [`Default`] draws a random key like `std`'s `RandomState`.
+#[derive(Clone)]
+pub struct GimliBuildHasher {
+    /// Initial state with the key in the capacity.
+    state: State,
+}
+
+impl GimliBuildHasher {
+    /// Create a build hasher with the key `key`.
+    pub fn new(key: &[u8; MAP_KEY_SIZE]) -> Self {
+        let mut state = State::new();
+        state.as_bytes_mut()[RATE..].copy_from_slice(key);
+        Self { state }
+    }
+
+    /// Create a build hasher with a random key.
+    #[cfg(feature = "getrandom")]
+    pub fn random() -> Self {
+        use aead::rand_core::RngCore;
+        use zeroize::Zeroize;
+
+        let mut key = [0u8; MAP_KEY_SIZE];
+        aead::OsRng.fill_bytes(&mut key);
+        let build_hasher = Self::new(&key);
+        key.zeroize();
+        build_hasher
+    }
+}
+
+#[cfg(feature = "getrandom")]
+impl Default for GimliBuildHasher {
+    fn default() -> Self {
+        Self::random()
+    }
+}
+
+impl BuildHasher for GimliBuildHasher {
+    type Hasher = GimliMapHasher;
+
+    fn build_hasher(&self) -> GimliMapHasher {
+        GimliMapHasher {
+            state: self.state.clone(),
+            buffer: [0; RATE],
+            buffer_len: 0,
+        }
+    }
+}
+
+/// Keyed [`Hasher`] producing 64-bit hashes, created by [`GimliBuildHasher`].
+#[derive(Clone)]
+pub struct GimliMapHasher {
+    state: State,
+    buffer: [u8; RATE],
+    buffer_len: usize,
+}
+
+impl Hasher for GimliMapHasher {
+    fn write(&mut self, mut bytes: &[u8]) {
+        while !bytes.is_empty() {
+            let (head, rest) = bytes.split_at(bytes.len().min(RATE - self.buffer_len));
+            self.buffer[self.buffer_len..self.buffer_len + head.len()].copy_from_slice(head);
+            self.buffer_len += head.len();
+            bytes = rest;
+
+            if self.buffer_len == RATE {
+                for (state, byte) in self.state.as_bytes_mut().iter_mut().zip(&self.buffer) {
+                    *state ^= byte;
+                }
+                gimli(&mut self.state);
+                self.buffer_len = 0;
+            }
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        let mut state = self.state.clone();
+        let state_bytes = state.as_bytes_mut();
+        for (state, byte) in state_bytes.iter_mut().zip(&self.buffer[..self.buffer_len]) {
+            *state ^= byte;
+        }
+        state_bytes[self.buffer_len] ^= DOMAIN_MAP_HASH;
+        state_bytes[RATE - 1] ^= PADDING_MARKER;
+        gimli(&mut state);
+
+        u64::from_le_bytes(state.as_bytes()[..8].try_into().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests;
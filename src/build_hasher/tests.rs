@@ -0,0 +1,98 @@
+extern crate std;
+use super::*;
+use std::collections::HashMap;
+use std::vec::Vec;
+
+// Under miri, only test inputs up to one block past the rate to keep test time reasonable.
+#[cfg(miri)]
+const MAX_LEN: u8 = 17;
+#[cfg(not(miri))]
+const MAX_LEN: u8 = 40;
+
+fn hash_bytes(build_hasher: &GimliBuildHasher, parts: &[&[u8]]) -> u64 {
+    let mut hasher = build_hasher.build_hasher();
+    for part in parts {
+        hasher.write(part);
+    }
+    hasher.finish()
+}
+
+#[test]
+fn test_known_answer() {
+    let build_hasher = GimliBuildHasher::new(&[7; MAP_KEY_SIZE]);
+    assert_eq!(hash_bytes(&build_hasher, &[]), 0x859e_f3e5_8fdb_b971);
+    assert_eq!(hash_bytes(&build_hasher, &[b"key"]), 0xc57c_6563_df72_9a6c);
+}
+
+#[test]
+fn test_split_writes() {
+    let build_hasher = GimliBuildHasher::new(&[7; MAP_KEY_SIZE]);
+    let data: Vec<u8> = (0..MAX_LEN).collect();
+
+    for len in 0..=data.len() {
+        let expected = hash_bytes(&build_hasher, &[&data[..len]]);
+        for split in 0..=len {
+            assert_eq!(
+                hash_bytes(&build_hasher, &[&data[..split], &data[split..len]]),
+                expected,
+                "len {len} split {split}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_distinct_inputs() {
+    let build_hasher = GimliBuildHasher::new(&[7; MAP_KEY_SIZE]);
+    let data = [0u8; 33];
+
+    // Inputs differing only in their length, including zero padding of a full block.
+    let hashes: Vec<u64> = (0..=data.len())
+        .map(|len| hash_bytes(&build_hasher, &[&data[..len]]))
+        .collect();
+    for (i, a) in hashes.iter().enumerate() {
+        for b in &hashes[i + 1..] {
+            assert_ne!(a, b);
+        }
+    }
+
+    let other_key = GimliBuildHasher::new(&[8; MAP_KEY_SIZE]);
+    assert_ne!(
+        hash_bytes(&build_hasher, &[b"key"]),
+        hash_bytes(&other_key, &[b"key"])
+    );
+}
+
+#[test]
+fn test_finish_does_not_consume() {
+    let build_hasher = GimliBuildHasher::new(&[7; MAP_KEY_SIZE]);
+    let mut hasher = build_hasher.build_hasher();
+    hasher.write(b"prefix");
+    let first = hasher.finish();
+    assert_eq!(hasher.finish(), first);
+
+    hasher.write(b"suffix");
+    assert_eq!(
+        hasher.finish(),
+        hash_bytes(&build_hasher, &[b"prefixsuffix"])
+    );
+}
+
+#[test]
+fn test_hash_map() {
+    let mut map = HashMap::with_hasher(GimliBuildHasher::new(&[7; MAP_KEY_SIZE]));
+    for i in 0..100u32 {
+        map.insert(i, i * 2);
+    }
+    assert_eq!(map.len(), 100);
+    assert!((0..100u32).all(|i| map[&i] == i * 2));
+    assert_eq!(map.get(&100), None);
+}
+
+#[test]
+#[cfg(feature = "getrandom")]
+fn test_random_key() {
+    let a = GimliBuildHasher::default();
+    let b = GimliBuildHasher::random();
+    assert_ne!(a.hash_one("key"), b.hash_one("key"));
+}
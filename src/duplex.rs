@@ -0,0 +1,232 @@
+//! # Stateful duplex object
+//!
+//! [`Duplex`] is a single transcript object over the Gimli permutation, in the spirit of Strobe
+//! and Xoodyak's Cyclist. Every operation absorbs or produces its data, pads it and permutes with
+//! a domain separation tag of its own XORed into the capacity, so the output depends on the full
+//! sequence of operations, their kinds and the boundaries between them.
+//!
+//! # Usage
+//!
+//! ```
+//! use gimli_crypto::Duplex;
+//!
+//! let key = [7u8; 32];
+//! let mut data = *b"Secret message";
+//!
+//! // Sender.
+//! let mut sender = Duplex::new(b"example protocol v1");
+//! sender.absorb_key(&key);
+//! sender.absorb(b"public header");
+//! sender.encrypt(&mut data);
+//! let mut tag = [0u8; 16];
+//! sender.squeeze(&mut tag);
+//!
+//! // Receiver, performing the same sequence of operations.
+//! let mut receiver = Duplex::new(b"example protocol v1");
+//! receiver.absorb_key(&key);
+//! receiver.absorb(b"public header");
+//! receiver.decrypt(&mut data);
+//! receiver.verify(&tag).expect("authentication failed");
+//!
+//! assert_eq!(&data, b"Secret message");
+//! ```
+//!
+//! Decrypted data must not be used before [`Duplex::verify`] succeeds.
+
+use crate::gimli::{State, gimli};
+use crate::{AuthenticationFailed, RATE, STATE_LAST_BYTE, TAG_SIZE};
+use subtle::{Choice, ConstantTimeEq};
+
+/// Operation tag for the protocol label absorbed by [`Duplex::new`].
+const TAG_INIT: u8 = 0x01;
+
+/// Operation tag for [`Duplex::absorb`].
+const TAG_ABSORB: u8 = 0x02;
+
+/// Operation tag for [`Duplex::absorb_key`].
+const TAG_ABSORB_KEY: u8 = 0x03;
+
+/// Operation tag for [`Duplex::encrypt`] and [`Duplex::decrypt`].
+const TAG_CRYPT: u8 = 0x04;
+
+/// Operation tag for [`Duplex::squeeze`] and [`Duplex::verify`].
+const TAG_SQUEEZE: u8 = 0x05;
+
+/// Operation tag for [`Duplex::squeeze_key`].
+const TAG_SQUEEZE_KEY: u8 = 0x06;
+
+/// Operation tag for [`Duplex::ratchet`].
+const TAG_RATCHET: u8 = 0x07;
+
+/// Transcript object over the Gimli permutation.
+///
+/// Two `Duplex` objects created with the same protocol label produce the same output as long as
+/// they perform the same operations on the same public data and keys. Cloning forks the
+/// transcript. The state is zeroed on drop.
+#[derive(Clone)]
+pub struct Duplex {
+    state: State,
+}
+
+impl Duplex {
+    /// Create a transcript for the protocol identified by `protocol`.
+    pub fn new(protocol: &[u8]) -> Self {
+        let mut duplex = Self {
+            state: State::new(),
+        };
+        duplex.absorb_with_tag(protocol, TAG_INIT);
+        duplex
+    }
+
+    /// Absorb public data such as headers, nonces or public keys.
+    pub fn absorb(&mut self, data: &[u8]) {
+        self.absorb_with_tag(data, TAG_ABSORB);
+    }
+
+    /// Absorb secret key material.
+    pub fn absorb_key(&mut self, key: &[u8]) {
+        self.absorb_with_tag(key, TAG_ABSORB_KEY);
+    }
+
+    /// Encrypt `buffer` in-place.
+    ///
+    /// The ciphertext is absorbed, so a later [`squeeze`](Self::squeeze) authenticates it.
+    pub fn encrypt(&mut self, buffer: &mut [u8]) {
+        let mut iter = buffer.chunks_exact_mut(RATE);
+        for chunk in &mut iter {
+            self.xor_in(chunk);
+            chunk.copy_from_slice(&self.state.as_bytes()[..RATE]);
+            self.permute(TAG_CRYPT);
+        }
+        let remainder = iter.into_remainder();
+        self.xor_in(remainder);
+        remainder.copy_from_slice(&self.state.as_bytes()[..remainder.len()]);
+        self.pad_and_permute(remainder.len(), TAG_CRYPT);
+    }
+
+    /// Decrypt `buffer` in-place, the inverse of [`encrypt`](Self::encrypt).
+    ///
+    /// The plaintext is not authenticated until [`verify`](Self::verify) succeeds.
+    pub fn decrypt(&mut self, buffer: &mut [u8]) {
+        let mut iter = buffer.chunks_exact_mut(RATE);
+        for chunk in &mut iter {
+            self.decrypt_block(chunk);
+            self.permute(TAG_CRYPT);
+        }
+        let remainder = iter.into_remainder();
+        self.decrypt_block(remainder);
+        self.pad_and_permute(remainder.len(), TAG_CRYPT);
+    }
+
+    /// Fill `out` with output depending on the whole transcript, e.g. an authentication tag.
+    ///
+    /// The length of `out` is absorbed first, so outputs of different lengths are unrelated.
+    pub fn squeeze(&mut self, out: &mut [u8]) {
+        self.squeeze_with_tag(out, TAG_SQUEEZE);
+    }
+
+    /// Fill `out` with key material, domain separated from [`squeeze`](Self::squeeze).
+    pub fn squeeze_key(&mut self, out: &mut [u8]) {
+        self.squeeze_with_tag(out, TAG_SQUEEZE_KEY);
+    }
+
+    /// Squeeze `tag.len()` bytes and compare them with `tag` in constant time.
+    ///
+    /// Tags shorter than [`TAG_SIZE`](crate::TAG_SIZE) bytes are rejected without squeezing, so
+    /// a truncated tag of attacker-controlled length cannot pass.
+    pub fn verify(&mut self, tag: &[u8]) -> Result<(), AuthenticationFailed> {
+        if tag.len() < TAG_SIZE {
+            return Err(AuthenticationFailed);
+        }
+
+        let mut equal = Choice::from(1);
+        self.squeeze_with_tag_into(tag.len(), TAG_SQUEEZE, |offset, block| {
+            let len = block.len().min(tag.len() - offset);
+            equal &= block[..len].ct_eq(&tag[offset..offset + len]);
+        });
+
+        if equal.into() {
+            Ok(())
+        } else {
+            Err(AuthenticationFailed)
+        }
+    }
+
+    /// Make the transcript irreversible.
+    ///
+    /// Zeroes the rate between two permutations so that a later compromise of the state does not
+    /// reveal earlier keys or outputs.
+    pub fn ratchet(&mut self) {
+        for _ in 0..2 {
+            self.state.as_bytes_mut()[..RATE].fill(0);
+            self.permute(TAG_RATCHET);
+        }
+    }
+
+    /// Absorb `data` and finish the operation with padding.
+    fn absorb_with_tag(&mut self, data: &[u8], tag: u8) {
+        let mut iter = data.chunks_exact(RATE);
+        for chunk in &mut iter {
+            self.xor_in(chunk);
+            self.permute(tag);
+        }
+        let remainder = iter.remainder();
+        self.xor_in(remainder);
+        self.pad_and_permute(remainder.len(), tag);
+    }
+
+    fn squeeze_with_tag(&mut self, out: &mut [u8], tag: u8) {
+        let len = out.len();
+        self.squeeze_with_tag_into(len, tag, |offset, block| {
+            let end = (offset + RATE).min(len);
+            out[offset..end].copy_from_slice(&block[..end - offset]);
+        });
+    }
+
+    /// Absorb `len` and pass each output block with its offset to `f`.
+    fn squeeze_with_tag_into(&mut self, len: usize, tag: u8, mut f: impl FnMut(usize, &[u8])) {
+        self.absorb_with_tag(&(len as u64).to_le_bytes(), tag);
+        for offset in (0..len).step_by(RATE) {
+            f(offset, &self.state.as_bytes()[..RATE]);
+            self.permute(tag);
+        }
+    }
+
+    /// Decrypt a block of at most `RATE` bytes and replace the rate with the ciphertext.
+    fn decrypt_block(&mut self, block: &mut [u8]) {
+        let state_bytes = self.state.as_bytes_mut();
+        for (byte, state_byte) in block.iter_mut().zip(state_bytes.iter_mut()) {
+            let ciphertext_byte = *byte;
+            *byte ^= *state_byte;
+            *state_byte = ciphertext_byte;
+        }
+    }
+
+    /// XOR `data` into the rate.
+    #[inline(always)]
+    fn xor_in(&mut self, data: &[u8]) {
+        let state_bytes = self.state.as_bytes_mut();
+        for (i, byte) in data.iter().enumerate() {
+            state_bytes[i] ^= byte;
+        }
+    }
+
+    /// Apply pad10*1 at `position` and permute.
+    #[inline(always)]
+    fn pad_and_permute(&mut self, position: usize, tag: u8) {
+        let state_bytes = self.state.as_bytes_mut();
+        state_bytes[position] ^= 0x01;
+        state_bytes[RATE - 1] ^= 0x80;
+        self.permute(tag);
+    }
+
+    /// XOR the operation tag into the capacity and permute.
+    #[inline(always)]
+    fn permute(&mut self, tag: u8) {
+        self.state.as_bytes_mut()[STATE_LAST_BYTE] ^= tag;
+        gimli(&mut self.state);
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,177 @@
+extern crate std;
+use super::*;
+use std::vec::Vec;
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+const PROTOCOL: &[u8] = b"gimli-crypto duplex tests";
+
+fn test_message(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 7) as u8).collect()
+}
+
+fn keyed() -> Duplex {
+    let mut duplex = Duplex::new(PROTOCOL);
+    duplex.absorb_key(&[0x42; 32]);
+    duplex.absorb(b"header");
+    duplex
+}
+
+fn squeeze16(duplex: &mut Duplex) -> [u8; 16] {
+    let mut out = [0u8; 16];
+    duplex.squeeze(&mut out);
+    out
+}
+
+#[test]
+fn test_roundtrip_all_lengths() {
+    for len in 0..=100 {
+        let message = test_message(len);
+
+        let mut sender = keyed();
+        let mut buffer = message.clone();
+        sender.encrypt(&mut buffer);
+        let tag = squeeze16(&mut sender);
+
+        if len > 0 {
+            assert_ne!(buffer, message, "length {len}");
+        }
+
+        let mut receiver = keyed();
+        receiver.decrypt(&mut buffer);
+        assert_eq!(receiver.verify(&tag), Ok(()), "length {len}");
+        assert_eq!(buffer, message, "length {len}");
+
+        // Both sides stay in sync after the exchange.
+        assert_eq!(squeeze16(&mut sender), squeeze16(&mut receiver));
+    }
+}
+
+#[test]
+fn test_tampered_ciphertext() {
+    let mut buffer = test_message(40);
+    let mut sender = keyed();
+    sender.encrypt(&mut buffer);
+    let tag = squeeze16(&mut sender);
+
+    for i in 0..buffer.len() {
+        let mut tampered = buffer.clone();
+        tampered[i] ^= 1;
+        let mut receiver = keyed();
+        receiver.decrypt(&mut tampered);
+        assert_eq!(receiver.verify(&tag), Err(AuthenticationFailed), "byte {i}");
+    }
+
+    let mut bad_tag = tag;
+    bad_tag[15] ^= 0x80;
+    let mut receiver = keyed();
+    receiver.decrypt(&mut buffer);
+    assert_eq!(receiver.verify(&bad_tag), Err(AuthenticationFailed));
+}
+
+#[test]
+fn test_verify_short_tag() {
+    let mut sender = keyed();
+    let tag = squeeze16(&mut sender);
+
+    assert_eq!(keyed().verify(&[]), Err(AuthenticationFailed));
+    for len in 1..TAG_SIZE {
+        assert_eq!(
+            keyed().verify(&tag[..len]),
+            Err(AuthenticationFailed),
+            "length {len}"
+        );
+    }
+    assert_eq!(keyed().verify(&tag), Ok(()));
+}
+
+#[test]
+fn test_verify_long_tag() {
+    let mut sender = keyed();
+    let mut tag = [0u8; 40];
+    sender.squeeze(&mut tag);
+
+    assert_eq!(keyed().verify(&tag), Ok(()));
+    assert_eq!(keyed().verify(&tag[..39]), Err(AuthenticationFailed));
+
+    tag[39] ^= 1;
+    assert_eq!(keyed().verify(&tag), Err(AuthenticationFailed));
+}
+
+#[test]
+fn test_domain_separation() {
+    let data = test_message(20);
+    let mut outputs: Vec<[u8; 16]> = Vec::new();
+
+    let mut push = |f: &dyn Fn(&mut Duplex)| {
+        let mut duplex = Duplex::new(PROTOCOL);
+        f(&mut duplex);
+        let out = squeeze16(&mut duplex);
+        assert!(!outputs.contains(&out));
+        outputs.push(out);
+    };
+
+    push(&|_| {});
+    push(&|d| d.absorb(&data));
+    push(&|d| d.absorb_key(&data));
+    push(&|d| {
+        d.absorb(&data[..10]);
+        d.absorb(&data[10..]);
+    });
+    push(&|d| d.absorb(&[]));
+    push(&|d| d.encrypt(&mut data.clone()));
+    push(&|d| d.decrypt(&mut data.clone()));
+    push(&|d| d.squeeze(&mut [0u8; 16]));
+    push(&|d| d.squeeze_key(&mut [0u8; 16]));
+    push(&|d| d.ratchet());
+
+    let mut other = Duplex::new(b"another protocol");
+    assert!(!outputs.contains(&squeeze16(&mut other)));
+}
+
+#[test]
+fn test_squeeze_lengths_unrelated() {
+    let mut short = [0u8; 16];
+    keyed().squeeze(&mut short);
+    let mut long = [0u8; 32];
+    keyed().squeeze(&mut long);
+    assert_ne!(short[..], long[..16]);
+
+    let mut key = [0u8; 16];
+    keyed().squeeze_key(&mut key);
+    assert_ne!(short, key);
+}
+
+#[test]
+fn test_clone_forks_transcript() {
+    let mut a = keyed();
+    let mut b = a.clone();
+    assert_eq!(squeeze16(&mut a), squeeze16(&mut b));
+
+    a.absorb(b"a");
+    b.absorb(b"b");
+    assert_ne!(squeeze16(&mut a), squeeze16(&mut b));
+}
+
+#[test]
+fn test_transcript_output_is_stable() {
+    let mut duplex = keyed();
+    let mut buffer = *b"Secret message";
+    duplex.encrypt(&mut buffer);
+    duplex.ratchet();
+    let tag = squeeze16(&mut duplex);
+
+    assert_eq!(
+        &buffer[..],
+        &hex_to_bytes("5dfd0b590cb67d6ee4d925418ddb")[..]
+    );
+    assert_eq!(
+        &tag[..],
+        &hex_to_bytes("1cb0b7ca29e25e7f7f48b2171c08283e")[..]
+    );
+}
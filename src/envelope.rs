@@ -0,0 +1,315 @@
+//! # Envelope encryption
+//!
+//! A self-describing binary format for data at rest, encrypted with `aead/gimli24v1`. An
+//! envelope records which key it was sealed with, so keys can be looked up by id from a
+//! [`KeyRing`] when opening.
+//!
+//! All integers are little-endian:
+//!
+//! | Field          | Size         | Content                      |
+//! |----------------|--------------|------------------------------|
+//! | magic          | 4            | [`MAGIC`]                    |
+//! | version        | 1            | [`VERSION`]                  |
+//! | algorithm id   | 1            | [`ALGORITHM_GIMLI24V1`]      |
+//! | key id         | 4            | [`KeyId`]                    |
+//! | nonce          | 16           |                              |
+//! | AAD length     | 4            | `n`                          |
+//! | AAD            | `n`          | associated data              |
+//! | ciphertext     | rest         |                              |
+//! | tag            | 16           |                              |
+//!
+//! Everything before the ciphertext is the header, which is authenticated as the associated
+//! data of the AEAD, so no header field can be changed without detection.
+//!
+//! # Usage
+//!
+//! ```
+//! use gimli_crypto::envelope::{self, Envelope};
+//!
+//! let keys = [(1, [0u8; 32]), (2, [1u8; 32])];
+//! let nonce = [0u8; 16]; // Use `envelope::seal` with `getrandom` for a random nonce
+//!
+//! let mut sealed = [0u8; 128];
+//! let len = envelope::seal_with_nonce(2, &keys[1].1, &nonce, b"record 7", b"Secret", &mut sealed)
+//!     .unwrap();
+//!
+//! let envelope = Envelope::parse(&sealed[..len]).unwrap();
+//! assert_eq!(envelope.key_id(), 2);
+//! assert_eq!(envelope.aad(), b"record 7");
+//!
+//! let mut plaintext = [0u8; 6];
+//! envelope.open(&keys, &mut plaintext).unwrap();
+//! assert_eq!(&plaintext, b"Secret");
+//! ```
+
+use crate::{KEY_SIZE, NONCE_SIZE, TAG_SIZE, decrypt_in_place, encrypt_in_place};
+
+mod rotation;
+
+pub use rotation::{KeyStatus, KeyUsage, RotatingKeyRing, UsageLimits};
+
+/// Magic bytes at the start of every envelope.
+pub const MAGIC: [u8; 4] = *b"GMLE";
+
+/// Format version written by this module.
+pub const VERSION: u8 = 1;
+
+/// Algorithm id of `aead/gimli24v1` with 32-byte keys and 16-byte nonces.
+pub const ALGORITHM_GIMLI24V1: u8 = 1;
+
+/// Size of the header without the AAD in bytes.
+pub const HEADER_SIZE: usize = 4 + 1 + 1 + 4 + NONCE_SIZE + 4;
+
+/// Identifier of a key in a [`KeyRing`].
+pub type KeyId = u32;
+
+const VERSION_OFFSET: usize = 4;
+const ALGORITHM_OFFSET: usize = 5;
+const KEY_ID_OFFSET: usize = 6;
+const NONCE_OFFSET: usize = 10;
+const AAD_LEN_OFFSET: usize = NONCE_OFFSET + NONCE_SIZE;
+
+/// Errors when sealing, parsing or opening envelopes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvelopeError {
+    /// The envelope is shorter than its header, AAD and tag.
+    Truncated,
+    /// The envelope does not start with [`MAGIC`].
+    BadMagic,
+    /// The format version is not supported.
+    UnsupportedVersion(u8),
+    /// The algorithm id is not supported.
+    UnsupportedAlgorithm(u8),
+    /// The key ring has no key with this id.
+    UnknownKey(KeyId),
+    /// The envelope was not sealed with the key or was modified.
+    AuthenticationFailed,
+    /// The output buffer is too small for the result.
+    BufferTooSmall,
+    /// The AAD is longer than `u32::MAX` bytes.
+    AadTooLong,
+    /// The key ring has no primary key to seal with.
+    NoPrimaryKey,
+    /// The key ring has no free slot for another key.
+    KeyRingFull,
+    /// The key ring already has a key with this id.
+    DuplicateKey(KeyId),
+}
+
+/// Lookup of keys by id for opening envelopes.
+pub trait KeyRing {
+    /// The key with `key_id`, if present.
+    fn key(&self, key_id: KeyId) -> Option<&[u8; KEY_SIZE]>;
+}
+
+impl KeyRing for [(KeyId, [u8; KEY_SIZE])] {
+    fn key(&self, key_id: KeyId) -> Option<&[u8; KEY_SIZE]> {
+        self.iter()
+            .find(|(id, _)| *id == key_id)
+            .map(|(_, key)| key)
+    }
+}
+
+impl<const N: usize> KeyRing for [(KeyId, [u8; KEY_SIZE]); N] {
+    fn key(&self, key_id: KeyId) -> Option<&[u8; KEY_SIZE]> {
+        self.as_slice().key(key_id)
+    }
+}
+
+/// Size of an envelope with `aad_len` bytes of AAD and `plaintext_len` bytes of plaintext.
+pub const fn sealed_len(aad_len: usize, plaintext_len: usize) -> usize {
+    HEADER_SIZE + aad_len + plaintext_len + TAG_SIZE
+}
+
+/// Seal `plaintext` with `key` into `out` with a random nonce, returning the envelope length.
+///
+/// `key_id` is recorded in the envelope for looking up the key when opening.
+#[cfg(feature = "getrandom")]
+pub fn seal(
+    key_id: KeyId,
+    key: &[u8; KEY_SIZE],
+    aad: &[u8],
+    plaintext: &[u8],
+    out: &mut [u8],
+) -> Result<usize, EnvelopeError> {
+    seal_with_nonce(key_id, key, &random_nonce(), aad, plaintext, out)
+}
+
+/// A nonce from the OS random number generator.
+#[cfg(feature = "getrandom")]
+fn random_nonce() -> [u8; NONCE_SIZE] {
+    use aead::rand_core::RngCore;
+
+    let mut nonce = [0u8; NONCE_SIZE];
+    aead::OsRng.fill_bytes(&mut nonce);
+    nonce
+}
+
+/// Seal `plaintext` with `key` into `out` with a caller-provided nonce, returning the envelope
+/// length.
+///
+/// The nonce must never repeat under the same key. Prefer `seal` unless the nonce comes from
+/// another source of randomness or a counter.
+pub fn seal_with_nonce(
+    key_id: KeyId,
+    key: &[u8; KEY_SIZE],
+    nonce: &[u8; NONCE_SIZE],
+    aad: &[u8],
+    plaintext: &[u8],
+    out: &mut [u8],
+) -> Result<usize, EnvelopeError> {
+    let len = sealed_len(aad.len(), plaintext.len());
+    let out = out.get_mut(..len).ok_or(EnvelopeError::BufferTooSmall)?;
+    out[HEADER_SIZE + aad.len()..len - TAG_SIZE].copy_from_slice(plaintext);
+
+    seal_prepared(key_id, key, nonce, aad, out)
+}
+
+/// Write the header into `out` and encrypt the plaintext already placed after it.
+///
+/// `out` must be exactly `sealed_len(aad.len(), plaintext_len)` bytes.
+fn seal_prepared(
+    key_id: KeyId,
+    key: &[u8; KEY_SIZE],
+    nonce: &[u8; NONCE_SIZE],
+    aad: &[u8],
+    out: &mut [u8],
+) -> Result<usize, EnvelopeError> {
+    let aad_len = u32::try_from(aad.len()).map_err(|_| EnvelopeError::AadTooLong)?;
+
+    let (header, rest) = out.split_at_mut(HEADER_SIZE + aad.len());
+    header[..VERSION_OFFSET].copy_from_slice(&MAGIC);
+    header[VERSION_OFFSET] = VERSION;
+    header[ALGORITHM_OFFSET] = ALGORITHM_GIMLI24V1;
+    header[KEY_ID_OFFSET..NONCE_OFFSET].copy_from_slice(&key_id.to_le_bytes());
+    header[NONCE_OFFSET..AAD_LEN_OFFSET].copy_from_slice(nonce);
+    header[AAD_LEN_OFFSET..HEADER_SIZE].copy_from_slice(&aad_len.to_le_bytes());
+    header[HEADER_SIZE..].copy_from_slice(aad);
+
+    let (ciphertext, tag) = rest.split_at_mut(rest.len() - TAG_SIZE);
+    tag.copy_from_slice(&encrypt_in_place(key, nonce, header, ciphertext));
+    Ok(out.len())
+}
+
+/// Open the envelope `sealed` with a key from `keys` into `out`, returning the plaintext
+/// length.
+///
+/// Shorthand for [`Envelope::parse`] followed by [`Envelope::open`].
+pub fn open<K: KeyRing + ?Sized>(
+    keys: &K,
+    sealed: &[u8],
+    out: &mut [u8],
+) -> Result<usize, EnvelopeError> {
+    Envelope::parse(sealed)?.open(keys, out)
+}
+
+/// A parsed envelope borrowing from the serialized bytes.
+///
+/// The header fields are not authenticated until [`Envelope::open`] succeeds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Envelope<'a> {
+    header: &'a [u8],
+    ciphertext: &'a [u8],
+    tag: &'a [u8; TAG_SIZE],
+}
+
+impl<'a> Envelope<'a> {
+    /// Parse and validate the structure of `sealed`.
+    pub fn parse(sealed: &'a [u8]) -> Result<Self, EnvelopeError> {
+        if sealed.len() < VERSION_OFFSET {
+            return Err(EnvelopeError::Truncated);
+        }
+        if sealed[..VERSION_OFFSET] != MAGIC {
+            return Err(EnvelopeError::BadMagic);
+        }
+        if sealed.len() < HEADER_SIZE {
+            return Err(EnvelopeError::Truncated);
+        }
+        if sealed[VERSION_OFFSET] != VERSION {
+            return Err(EnvelopeError::UnsupportedVersion(sealed[VERSION_OFFSET]));
+        }
+        if sealed[ALGORITHM_OFFSET] != ALGORITHM_GIMLI24V1 {
+            return Err(EnvelopeError::UnsupportedAlgorithm(
+                sealed[ALGORITHM_OFFSET],
+            ));
+        }
+
+        let aad_len = u32::from_le_bytes(sealed[AAD_LEN_OFFSET..HEADER_SIZE].try_into().unwrap());
+        let header_len = usize::try_from(aad_len)
+            .ok()
+            .and_then(|aad_len| HEADER_SIZE.checked_add(aad_len))
+            .ok_or(EnvelopeError::Truncated)?;
+        let ciphertext_len = sealed
+            .len()
+            .checked_sub(header_len)
+            .and_then(|len| len.checked_sub(TAG_SIZE))
+            .ok_or(EnvelopeError::Truncated)?;
+
+        let (header, rest) = sealed.split_at(header_len);
+        let (ciphertext, tag) = rest.split_at(ciphertext_len);
+        Ok(Self {
+            header,
+            ciphertext,
+            tag: tag.try_into().unwrap(),
+        })
+    }
+
+    /// Id of the key the envelope claims to be sealed with.
+    pub fn key_id(&self) -> KeyId {
+        KeyId::from_le_bytes(self.header[KEY_ID_OFFSET..NONCE_OFFSET].try_into().unwrap())
+    }
+
+    /// Nonce used for sealing.
+    pub fn nonce(&self) -> &'a [u8; NONCE_SIZE] {
+        self.header[NONCE_OFFSET..AAD_LEN_OFFSET]
+            .try_into()
+            .unwrap()
+    }
+
+    /// Associated data stored in the envelope.
+    pub fn aad(&self) -> &'a [u8] {
+        &self.header[HEADER_SIZE..]
+    }
+
+    /// Length of the plaintext in bytes.
+    pub fn plaintext_len(&self) -> usize {
+        self.ciphertext.len()
+    }
+
+    /// Authenticate and decrypt into `out` with the key from `keys`, returning the plaintext
+    /// length.
+    ///
+    /// On authentication failure `out` is zeroed.
+    pub fn open<K: KeyRing + ?Sized>(
+        &self,
+        keys: &K,
+        out: &mut [u8],
+    ) -> Result<usize, EnvelopeError> {
+        let key_id = self.key_id();
+        let key = keys.key(key_id).ok_or(EnvelopeError::UnknownKey(key_id))?;
+        self.open_with_key(key, out)
+    }
+
+    /// Authenticate and decrypt into `out` with `key`, returning the plaintext length.
+    ///
+    /// On authentication failure `out` is zeroed.
+    pub fn open_with_key(
+        &self,
+        key: &[u8; KEY_SIZE],
+        out: &mut [u8],
+    ) -> Result<usize, EnvelopeError> {
+        let out = out
+            .get_mut(..self.ciphertext.len())
+            .ok_or(EnvelopeError::BufferTooSmall)?;
+        out.copy_from_slice(self.ciphertext);
+
+        if decrypt_in_place(key, self.nonce(), self.header, out, self.tag).is_err() {
+            out.fill(0);
+            return Err(EnvelopeError::AuthenticationFailed);
+        }
+        Ok(out.len())
+    }
+}
+
+#[cfg(test)]
+mod tests;
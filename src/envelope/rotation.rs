@@ -0,0 +1,335 @@
+use super::{Envelope, EnvelopeError, HEADER_SIZE, KeyId, KeyRing, seal_prepared, sealed_len};
+use crate::{KEY_SIZE, NONCE_SIZE, TAG_SIZE};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Role of a key in a [`RotatingKeyRing`].
+///
+/// Keys of every status open envelopes, only the primary key seals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyStatus {
+    /// Seals new envelopes, at most one key is primary.
+    Primary,
+    /// Opens envelopes, e.g. a key being rolled out or the previous primary key.
+    Active,
+    /// Only kept to open envelopes until they are resealed, then it can be removed.
+    Retired,
+}
+
+/// Number of envelopes and plaintext bytes sealed with a key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyUsage {
+    /// Number of envelopes sealed.
+    pub messages: u64,
+    /// Number of plaintext bytes sealed.
+    pub bytes: u64,
+}
+
+impl KeyUsage {
+    /// Whether either counter is at or above its limit.
+    pub fn has_reached(&self, limits: &UsageLimits) -> bool {
+        self.messages >= limits.max_messages || self.bytes >= limits.max_bytes
+    }
+
+    fn record(&mut self, plaintext_len: usize) {
+        self.messages = self.messages.saturating_add(1);
+        self.bytes = self.bytes.saturating_add(plaintext_len as u64);
+    }
+}
+
+/// Usage after which a key should be rotated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UsageLimits {
+    /// Maximum number of envelopes sealed with one key.
+    pub max_messages: u64,
+    /// Maximum number of plaintext bytes sealed with one key.
+    pub max_bytes: u64,
+}
+
+impl UsageLimits {
+    /// No limits, keys never need rotation because of usage.
+    pub const UNLIMITED: Self = Self {
+        max_messages: u64::MAX,
+        max_bytes: u64::MAX,
+    };
+}
+
+#[derive(Zeroize)]
+struct Entry {
+    key: [u8; KEY_SIZE],
+    #[zeroize(skip)]
+    id: KeyId,
+    #[zeroize(skip)]
+    status: KeyStatus,
+    #[zeroize(skip)]
+    usage: KeyUsage,
+}
+
+/// Key ring with up to `N` keys for rotating the sealing key of envelopes.
+///
+/// New envelopes are sealed with the primary key while envelopes sealed with any key in the ring
+/// can be opened. `reseal` moves an existing envelope to the primary key.
+/// Sealing counts the usage of the primary key, [`RotatingKeyRing::needs_rotation`] flags when it
+/// has reached the configured limits.
+///
+/// The counters only live in memory and start at zero on insert. Persist
+/// [`usage`](RotatingKeyRing::usage) after sealing and restore it with
+/// [`set_usage`](RotatingKeyRing::set_usage) when the ring is rebuilt, otherwise every restart
+/// resets the counts and a long-lived primary key is never flagged.
+///
+/// ```
+/// use gimli_crypto::envelope::{KeyStatus, RotatingKeyRing, UsageLimits};
+///
+/// let limits = UsageLimits { max_messages: 1 << 32, max_bytes: 1 << 40 };
+/// let mut keys = RotatingKeyRing::<4>::new(limits);
+/// keys.insert(1, [1u8; 32], KeyStatus::Primary).unwrap();
+///
+/// let mut old = [0u8; 64];
+/// let len = keys.seal_with_nonce(&[0u8; 16], b"", b"Secret", &mut old).unwrap();
+///
+/// // Rotate: the new key seals, the old one is kept until all envelopes are resealed.
+/// keys.insert(2, [2u8; 32], KeyStatus::Primary).unwrap();
+/// keys.retire(1).unwrap();
+///
+/// let mut new = [0u8; 64];
+/// let len = keys.reseal_with_nonce(&[1u8; 16], &old[..len], &mut new).unwrap();
+/// keys.remove(1).unwrap();
+///
+/// let mut plaintext = [0u8; 6];
+/// gimli_crypto::envelope::open(&keys, &new[..len], &mut plaintext).unwrap();
+/// assert_eq!(&plaintext, b"Secret");
+/// ```
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct RotatingKeyRing<const N: usize> {
+    entries: [Option<Entry>; N],
+    #[zeroize(skip)]
+    limits: UsageLimits,
+}
+
+impl<const N: usize> RotatingKeyRing<N> {
+    /// Create an empty key ring.
+    pub fn new(limits: UsageLimits) -> Self {
+        Self {
+            entries: core::array::from_fn(|_| None),
+            limits,
+        }
+    }
+
+    /// Usage limits of the keys.
+    pub fn limits(&self) -> &UsageLimits {
+        &self.limits
+    }
+
+    /// Add `key` with `key_id` and no usage.
+    ///
+    /// Adding a primary key demotes the current primary key to active. Restore the persisted
+    /// usage of an existing key with [`Self::set_usage`].
+    pub fn insert(
+        &mut self,
+        key_id: KeyId,
+        key: [u8; KEY_SIZE],
+        status: KeyStatus,
+    ) -> Result<(), EnvelopeError> {
+        if self.entry(key_id).is_some() {
+            return Err(EnvelopeError::DuplicateKey(key_id));
+        }
+        let slot = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.is_none())
+            .ok_or(EnvelopeError::KeyRingFull)?;
+        *slot = Some(Entry {
+            key,
+            id: key_id,
+            status: KeyStatus::Active,
+            usage: KeyUsage::default(),
+        });
+
+        self.set_status(key_id, status)
+    }
+
+    /// Remove the key with `key_id`, after which its envelopes can no longer be opened.
+    pub fn remove(&mut self, key_id: KeyId) -> Result<(), EnvelopeError> {
+        let slot = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.as_ref().is_some_and(|entry| entry.id == key_id))
+            .ok_or(EnvelopeError::UnknownKey(key_id))?;
+        slot.zeroize();
+        Ok(())
+    }
+
+    /// Make the key with `key_id` the primary key, demoting the current one to active.
+    pub fn set_primary(&mut self, key_id: KeyId) -> Result<(), EnvelopeError> {
+        self.set_status(key_id, KeyStatus::Primary)
+    }
+
+    /// Retire the key with `key_id`.
+    ///
+    /// Retiring the primary key leaves the ring without one until [`Self::set_primary`].
+    pub fn retire(&mut self, key_id: KeyId) -> Result<(), EnvelopeError> {
+        self.set_status(key_id, KeyStatus::Retired)
+    }
+
+    fn set_status(&mut self, key_id: KeyId, status: KeyStatus) -> Result<(), EnvelopeError> {
+        self.entry(key_id)
+            .ok_or(EnvelopeError::UnknownKey(key_id))?;
+
+        for entry in self.entries.iter_mut().flatten() {
+            if entry.id == key_id {
+                entry.status = status;
+            } else if status == KeyStatus::Primary && entry.status == KeyStatus::Primary {
+                entry.status = KeyStatus::Active;
+            }
+        }
+        Ok(())
+    }
+
+    /// Id of the primary key.
+    pub fn primary_id(&self) -> Option<KeyId> {
+        self.primary().map(|entry| entry.id)
+    }
+
+    /// Status of the key with `key_id`.
+    pub fn status(&self, key_id: KeyId) -> Option<KeyStatus> {
+        self.entry(key_id).map(|entry| entry.status)
+    }
+
+    /// Usage of the key with `key_id`.
+    pub fn usage(&self, key_id: KeyId) -> Option<KeyUsage> {
+        self.entry(key_id).map(|entry| entry.usage)
+    }
+
+    /// Restore the usage of the key with `key_id`, e.g. persisted from [`Self::usage`] before a
+    /// restart.
+    pub fn set_usage(&mut self, key_id: KeyId, usage: KeyUsage) -> Result<(), EnvelopeError> {
+        let entry = self
+            .entries
+            .iter_mut()
+            .flatten()
+            .find(|entry| entry.id == key_id)
+            .ok_or(EnvelopeError::UnknownKey(key_id))?;
+        entry.usage = usage;
+        Ok(())
+    }
+
+    /// Whether the primary key has reached the usage limits and a new one should be set.
+    pub fn needs_rotation(&self) -> bool {
+        self.primary()
+            .is_some_and(|entry| entry.usage.has_reached(&self.limits))
+    }
+
+    /// Seal `plaintext` with the primary key into `out` with a random nonce, returning the
+    /// envelope length.
+    #[cfg(feature = "getrandom")]
+    pub fn seal(
+        &mut self,
+        aad: &[u8],
+        plaintext: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, EnvelopeError> {
+        self.seal_with_nonce(&super::random_nonce(), aad, plaintext, out)
+    }
+
+    /// Seal `plaintext` with the primary key into `out` with a caller-provided nonce, returning
+    /// the envelope length.
+    ///
+    /// The nonce must never repeat under the same key.
+    pub fn seal_with_nonce(
+        &mut self,
+        nonce: &[u8; NONCE_SIZE],
+        aad: &[u8],
+        plaintext: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, EnvelopeError> {
+        if self.primary().is_none() {
+            return Err(EnvelopeError::NoPrimaryKey);
+        }
+
+        let (len, plaintext_out) = Self::prepare(aad.len(), plaintext.len(), out)?;
+        plaintext_out.copy_from_slice(plaintext);
+
+        self.seal_prepared(nonce, aad, &mut out[..len])
+    }
+
+    /// Open `sealed` with any key in the ring and seal it again with the primary key into `out`
+    /// with a random nonce, returning the envelope length.
+    ///
+    /// The AAD is carried over. The plaintext is only ever held in `out`.
+    #[cfg(feature = "getrandom")]
+    pub fn reseal(&mut self, sealed: &[u8], out: &mut [u8]) -> Result<usize, EnvelopeError> {
+        self.reseal_with_nonce(&super::random_nonce(), sealed, out)
+    }
+
+    /// Open `sealed` with any key in the ring and seal it again with the primary key into `out`
+    /// with a caller-provided nonce, returning the envelope length.
+    ///
+    /// The nonce must never repeat under the same key.
+    pub fn reseal_with_nonce(
+        &mut self,
+        nonce: &[u8; NONCE_SIZE],
+        sealed: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, EnvelopeError> {
+        let envelope = Envelope::parse(sealed)?;
+        if self.primary().is_none() {
+            return Err(EnvelopeError::NoPrimaryKey);
+        }
+
+        let (len, plaintext_out) =
+            Self::prepare(envelope.aad().len(), envelope.plaintext_len(), out)?;
+        envelope.open(&*self, plaintext_out)?;
+
+        self.seal_prepared(nonce, envelope.aad(), &mut out[..len])
+    }
+
+    /// Check the size of `out` and return the envelope length and the plaintext part of `out`.
+    fn prepare(
+        aad_len: usize,
+        plaintext_len: usize,
+        out: &mut [u8],
+    ) -> Result<(usize, &mut [u8]), EnvelopeError> {
+        let len = sealed_len(aad_len, plaintext_len);
+        if out.len() < len {
+            return Err(EnvelopeError::BufferTooSmall);
+        }
+        Ok((len, &mut out[HEADER_SIZE + aad_len..len - TAG_SIZE]))
+    }
+
+    fn seal_prepared(
+        &mut self,
+        nonce: &[u8; NONCE_SIZE],
+        aad: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, EnvelopeError> {
+        let primary = self
+            .entries
+            .iter_mut()
+            .flatten()
+            .find(|entry| entry.status == KeyStatus::Primary)
+            .ok_or(EnvelopeError::NoPrimaryKey)?;
+
+        let len = seal_prepared(primary.id, &primary.key, nonce, aad, out)?;
+        primary.usage.record(len - sealed_len(aad.len(), 0));
+        Ok(len)
+    }
+
+    fn entry(&self, key_id: KeyId) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .flatten()
+            .find(|entry| entry.id == key_id)
+    }
+
+    fn primary(&self) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .flatten()
+            .find(|entry| entry.status == KeyStatus::Primary)
+    }
+}
+
+impl<const N: usize> KeyRing for RotatingKeyRing<N> {
+    fn key(&self, key_id: KeyId) -> Option<&[u8; KEY_SIZE]> {
+        self.entry(key_id).map(|entry| &entry.key)
+    }
+}
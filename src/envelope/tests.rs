@@ -0,0 +1,498 @@
+extern crate std;
+use super::*;
+use std::vec;
+use std::vec::Vec;
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+const KEYS: [(KeyId, [u8; KEY_SIZE]); 2] = [(1, [0x11; KEY_SIZE]), (0xdead_beef, [0x42; KEY_SIZE])];
+const NONCE: [u8; NONCE_SIZE] = [7; NONCE_SIZE];
+
+fn seal_test(aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+    let mut out = vec![0u8; sealed_len(aad.len(), plaintext.len())];
+    let len = seal_with_nonce(KEYS[1].0, &KEYS[1].1, &NONCE, aad, plaintext, &mut out).unwrap();
+    assert_eq!(len, out.len());
+    out
+}
+
+fn open_test(sealed: &[u8]) -> Result<Vec<u8>, EnvelopeError> {
+    let mut out = vec![0u8; sealed.len()];
+    let len = open(&KEYS, sealed, &mut out)?;
+    out.truncate(len);
+    Ok(out)
+}
+
+#[test]
+fn test_roundtrip() {
+    for aad_len in [0, 1, 15, 16, 17, 100] {
+        for len in 0..=40 {
+            let aad: Vec<u8> = (0..aad_len).map(|i| i as u8).collect();
+            let plaintext: Vec<u8> = (0..len).map(|i| (i * 7) as u8).collect();
+
+            let sealed = seal_test(&aad, &plaintext);
+            let envelope = Envelope::parse(&sealed).unwrap();
+            assert_eq!(envelope.key_id(), 0xdead_beef);
+            assert_eq!(envelope.nonce(), &NONCE);
+            assert_eq!(envelope.aad(), aad);
+            assert_eq!(envelope.plaintext_len(), len);
+
+            assert_eq!(open_test(&sealed).unwrap(), plaintext);
+        }
+    }
+}
+
+#[test]
+fn test_layout() {
+    let sealed = seal_test(b"aad", b"Hello");
+    assert_eq!(sealed[..4], MAGIC);
+    assert_eq!(sealed[4], VERSION);
+    assert_eq!(sealed[5], ALGORITHM_GIMLI24V1);
+    assert_eq!(sealed[6..10], 0xdead_beefu32.to_le_bytes());
+    assert_eq!(sealed[10..26], NONCE);
+    assert_eq!(sealed[26..30], 3u32.to_le_bytes());
+    assert_eq!(&sealed[30..33], b"aad");
+
+    // The ciphertext and tag are those of `encrypt_in_place` with the header as AAD.
+    let mut ciphertext = *b"Hello";
+    let tag = encrypt_in_place(&KEYS[1].1, &NONCE, &sealed[..33], &mut ciphertext);
+    assert_eq!(sealed[33..38], ciphertext);
+    assert_eq!(sealed[38..], tag);
+}
+
+#[test]
+fn test_header_is_authenticated() {
+    let sealed = seal_test(b"aad", b"Hello");
+
+    for i in 0..sealed.len() {
+        let mut tampered = sealed.clone();
+        tampered[i] ^= 1;
+        assert!(open_test(&tampered).is_err(), "byte {i}");
+    }
+}
+
+#[test]
+fn test_tampered_fields_errors() {
+    let sealed = seal_test(b"aad", b"Hello");
+
+    let mut tampered = sealed.clone();
+    tampered[0] ^= 1;
+    assert_eq!(open_test(&tampered), Err(EnvelopeError::BadMagic));
+
+    let mut tampered = sealed.clone();
+    tampered[4] = 2;
+    assert_eq!(
+        open_test(&tampered),
+        Err(EnvelopeError::UnsupportedVersion(2))
+    );
+
+    let mut tampered = sealed.clone();
+    tampered[5] = 9;
+    assert_eq!(
+        open_test(&tampered),
+        Err(EnvelopeError::UnsupportedAlgorithm(9))
+    );
+
+    // A key id that exists but is not the one used for sealing.
+    let mut tampered = sealed.clone();
+    tampered[6..10].copy_from_slice(&1u32.to_le_bytes());
+    assert_eq!(
+        open_test(&tampered),
+        Err(EnvelopeError::AuthenticationFailed)
+    );
+
+    let mut tampered = sealed.clone();
+    tampered[6..10].copy_from_slice(&2u32.to_le_bytes());
+    assert_eq!(open_test(&tampered), Err(EnvelopeError::UnknownKey(2)));
+
+    // A shorter AAD moves bytes into the ciphertext.
+    let mut tampered = sealed.clone();
+    tampered[26..30].copy_from_slice(&2u32.to_le_bytes());
+    assert_eq!(
+        open_test(&tampered),
+        Err(EnvelopeError::AuthenticationFailed)
+    );
+
+    let mut tampered = sealed.clone();
+    tampered[26..30].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(open_test(&tampered), Err(EnvelopeError::Truncated));
+}
+
+#[test]
+fn test_truncated() {
+    let sealed = seal_test(b"aad", b"Hello");
+    let min_len = HEADER_SIZE + 3 + TAG_SIZE;
+
+    for len in 0..min_len {
+        assert_eq!(
+            open_test(&sealed[..len]),
+            Err(EnvelopeError::Truncated),
+            "len {len}"
+        );
+    }
+
+    // Truncating the ciphertext is only detected by authentication.
+    for len in min_len..sealed.len() {
+        assert_eq!(
+            open_test(&sealed[..len]),
+            Err(EnvelopeError::AuthenticationFailed)
+        );
+    }
+
+    assert_eq!(Envelope::parse(&sealed[..3]), Err(EnvelopeError::Truncated));
+    assert_eq!(Envelope::parse(b"nope"), Err(EnvelopeError::BadMagic));
+}
+
+#[test]
+fn test_failure_zeroes_output() {
+    let mut sealed = seal_test(b"aad", b"Hello");
+    *sealed.last_mut().unwrap() ^= 1;
+
+    let mut out = [0xffu8; 5];
+    let envelope = Envelope::parse(&sealed).unwrap();
+    assert_eq!(
+        envelope.open(&KEYS, &mut out),
+        Err(EnvelopeError::AuthenticationFailed)
+    );
+    assert_eq!(out, [0; 5]);
+}
+
+#[test]
+fn test_buffer_errors() {
+    let mut out = [0u8; 64];
+    let len = sealed_len(3, 5);
+    assert_eq!(
+        seal_with_nonce(1, &KEYS[0].1, &NONCE, b"aad", b"Hello", &mut out[..len - 1]),
+        Err(EnvelopeError::BufferTooSmall)
+    );
+
+    let sealed = seal_test(b"aad", b"Hello");
+    let mut out = [0u8; 4];
+    assert_eq!(
+        open(&KEYS, &sealed, &mut out),
+        Err(EnvelopeError::BufferTooSmall)
+    );
+}
+
+#[test]
+fn test_key_ring_slices() {
+    let sealed = seal_test(b"", b"Hello");
+    let mut out = [0u8; 5];
+
+    let keys: &[(KeyId, [u8; KEY_SIZE])] = &KEYS;
+    assert_eq!(open(keys, &sealed, &mut out), Ok(5));
+    assert_eq!(
+        open(&keys[..1], &sealed, &mut out),
+        Err(EnvelopeError::UnknownKey(0xdead_beef))
+    );
+
+    let envelope = Envelope::parse(&sealed).unwrap();
+    assert_eq!(envelope.open_with_key(&KEYS[1].1, &mut out), Ok(5));
+    assert_eq!(
+        envelope.open_with_key(&KEYS[0].1, &mut out),
+        Err(EnvelopeError::AuthenticationFailed)
+    );
+}
+
+#[cfg(feature = "getrandom")]
+#[test]
+fn test_random_nonces() {
+    let mut first = [0u8; 64];
+    let mut second = [0u8; 64];
+    let len = seal(1, &KEYS[0].1, b"aad", b"Hello", &mut first).unwrap();
+    seal(1, &KEYS[0].1, b"aad", b"Hello", &mut second).unwrap();
+
+    let first = Envelope::parse(&first[..len]).unwrap();
+    let second = Envelope::parse(&second[..len]).unwrap();
+    assert_ne!(first.nonce(), second.nonce());
+
+    let mut out = [0u8; 5];
+    first.open(&KEYS, &mut out).unwrap();
+    assert_eq!(&out, b"Hello");
+}
+
+#[test]
+fn test_known_envelope() {
+    let expected = hex_to_bytes(concat!(
+        "474d4c450101efbeadde",
+        "07070707070707070707070707070707",
+        "03000000616164",
+        "04e53282482f20db57e43ad2ec2b5672c9c9bf7976",
+    ));
+    assert_eq!(seal_test(b"aad", b"Hello"), expected);
+}
+
+const LIMITS: UsageLimits = UsageLimits {
+    max_messages: 3,
+    max_bytes: 100,
+};
+
+fn rotating() -> RotatingKeyRing<3> {
+    let mut keys = RotatingKeyRing::new(LIMITS);
+    keys.insert(KEYS[0].0, KEYS[0].1, KeyStatus::Primary)
+        .unwrap();
+    keys
+}
+
+#[test]
+fn test_rotating_seal_open() {
+    let mut keys = rotating();
+    let mut sealed = [0u8; 64];
+    let len = keys
+        .seal_with_nonce(&NONCE, b"aad", b"Hello", &mut sealed)
+        .unwrap();
+
+    let envelope = Envelope::parse(&sealed[..len]).unwrap();
+    assert_eq!(envelope.key_id(), KEYS[0].0);
+
+    // Identical to sealing with the primary key directly.
+    let mut expected = [0u8; 64];
+    seal_with_nonce(
+        KEYS[0].0,
+        &KEYS[0].1,
+        &NONCE,
+        b"aad",
+        b"Hello",
+        &mut expected,
+    )
+    .unwrap();
+    assert_eq!(sealed[..len], expected[..len]);
+
+    let mut out = [0u8; 5];
+    assert_eq!(envelope.open(&keys, &mut out), Ok(5));
+    assert_eq!(&out, b"Hello");
+}
+
+#[test]
+fn test_rotating_statuses() {
+    let mut keys = rotating();
+    assert_eq!(keys.primary_id(), Some(1));
+
+    keys.insert(2, [2; KEY_SIZE], KeyStatus::Active).unwrap();
+    assert_eq!(keys.primary_id(), Some(1));
+
+    keys.insert(3, [3; KEY_SIZE], KeyStatus::Primary).unwrap();
+    assert_eq!(keys.primary_id(), Some(3));
+    assert_eq!(keys.status(1), Some(KeyStatus::Active));
+
+    keys.set_primary(2).unwrap();
+    assert_eq!(keys.status(2), Some(KeyStatus::Primary));
+    assert_eq!(keys.status(3), Some(KeyStatus::Active));
+
+    keys.retire(1).unwrap();
+    assert_eq!(keys.status(1), Some(KeyStatus::Retired));
+
+    keys.retire(2).unwrap();
+    assert_eq!(keys.primary_id(), None);
+    let mut out = [0u8; 64];
+    assert_eq!(
+        keys.seal_with_nonce(&NONCE, b"", b"Hello", &mut out),
+        Err(EnvelopeError::NoPrimaryKey)
+    );
+
+    assert_eq!(
+        keys.insert(4, [4; KEY_SIZE], KeyStatus::Active),
+        Err(EnvelopeError::KeyRingFull)
+    );
+    assert_eq!(
+        keys.insert(1, [4; KEY_SIZE], KeyStatus::Active),
+        Err(EnvelopeError::DuplicateKey(1))
+    );
+    assert_eq!(keys.set_primary(9), Err(EnvelopeError::UnknownKey(9)));
+    assert_eq!(keys.retire(9), Err(EnvelopeError::UnknownKey(9)));
+
+    keys.remove(1).unwrap();
+    assert_eq!(keys.status(1), None);
+    assert_eq!(keys.remove(1), Err(EnvelopeError::UnknownKey(1)));
+    keys.insert(4, [4; KEY_SIZE], KeyStatus::Active).unwrap();
+}
+
+#[test]
+fn test_rotating_reseal() {
+    let mut keys = rotating();
+    let mut old = [0u8; 64];
+    let old_len = keys
+        .seal_with_nonce(&NONCE, b"aad", b"Hello", &mut old)
+        .unwrap();
+    let old = &old[..old_len];
+
+    keys.insert(2, [2; KEY_SIZE], KeyStatus::Primary).unwrap();
+    keys.retire(1).unwrap();
+
+    let mut new = [0u8; 64];
+    let len = keys
+        .reseal_with_nonce(&[8; NONCE_SIZE], old, &mut new)
+        .unwrap();
+    assert_eq!(len, old_len);
+
+    let envelope = Envelope::parse(&new[..len]).unwrap();
+    assert_eq!(envelope.key_id(), 2);
+    assert_eq!(envelope.nonce(), &[8; NONCE_SIZE]);
+    assert_eq!(envelope.aad(), b"aad");
+
+    // The resealed envelope opens without the retired key, the old one no longer does.
+    keys.remove(1).unwrap();
+    let mut out = [0u8; 5];
+    assert_eq!(envelope.open(&keys, &mut out), Ok(5));
+    assert_eq!(&out, b"Hello");
+    assert_eq!(
+        open(&keys, old, &mut out),
+        Err(EnvelopeError::UnknownKey(1))
+    );
+}
+
+#[test]
+fn test_rotating_reseal_errors() {
+    let mut keys = rotating();
+    let mut sealed = [0u8; 64];
+    let len = keys
+        .seal_with_nonce(&NONCE, b"aad", b"Hello", &mut sealed)
+        .unwrap();
+    let usage = keys.usage(1).unwrap();
+
+    let mut out = [0u8; 64];
+    for short in [len - 1, len - TAG_SIZE, 0] {
+        assert_eq!(
+            keys.reseal_with_nonce(&NONCE, &sealed[..len], &mut out[..short]),
+            Err(EnvelopeError::BufferTooSmall)
+        );
+    }
+
+    let mut tampered = sealed;
+    tampered[len - 1] ^= 1;
+    assert_eq!(
+        keys.reseal_with_nonce(&NONCE, &tampered[..len], &mut out),
+        Err(EnvelopeError::AuthenticationFailed)
+    );
+    assert!(out.iter().all(|&b| b == 0));
+
+    let foreign = seal_test(b"", b"Hello");
+    assert_eq!(
+        keys.reseal_with_nonce(&NONCE, &foreign, &mut out),
+        Err(EnvelopeError::UnknownKey(0xdead_beef))
+    );
+    assert_eq!(
+        keys.reseal_with_nonce(&NONCE, &sealed[..HEADER_SIZE], &mut out),
+        Err(EnvelopeError::Truncated)
+    );
+
+    // Failed reseals do not count as usage.
+    assert_eq!(keys.usage(1), Some(usage));
+
+    keys.retire(1).unwrap();
+    assert_eq!(
+        keys.reseal_with_nonce(&NONCE, &sealed[..len], &mut out),
+        Err(EnvelopeError::NoPrimaryKey)
+    );
+}
+
+#[test]
+fn test_rotating_usage_limits() {
+    let mut keys = rotating();
+    let mut out = [0u8; 128];
+    assert!(!keys.needs_rotation());
+
+    keys.seal_with_nonce(&NONCE, b"aad", &[0; 10], &mut out)
+        .unwrap();
+    keys.seal_with_nonce(&NONCE, b"", &[0; 20], &mut out)
+        .unwrap();
+    assert_eq!(
+        keys.usage(1),
+        Some(KeyUsage {
+            messages: 2,
+            bytes: 30
+        })
+    );
+    assert!(!keys.needs_rotation());
+
+    // Reaching the message limit flags the key, sealing still works.
+    keys.seal_with_nonce(&NONCE, b"", &[], &mut out).unwrap();
+    assert!(keys.needs_rotation());
+    keys.seal_with_nonce(&NONCE, b"", &[], &mut out).unwrap();
+
+    // A new primary key starts from zero, resealing counts against it.
+    let mut old = [0u8; 128];
+    let len = keys
+        .seal_with_nonce(&NONCE, b"", &[0; 60], &mut old)
+        .unwrap();
+    keys.insert(2, [2; KEY_SIZE], KeyStatus::Primary).unwrap();
+    assert!(!keys.needs_rotation());
+    keys.reseal_with_nonce(&NONCE, &old[..len], &mut out)
+        .unwrap();
+    assert_eq!(
+        keys.usage(2),
+        Some(KeyUsage {
+            messages: 1,
+            bytes: 60
+        })
+    );
+    assert!(!keys.needs_rotation());
+
+    // Reaching the byte limit flags the key.
+    keys.seal_with_nonce(&NONCE, b"", &[0; 40], &mut out)
+        .unwrap();
+    assert!(keys.needs_rotation());
+    assert!(keys.usage(2).unwrap().has_reached(keys.limits()));
+    assert!(!keys.usage(2).unwrap().has_reached(&UsageLimits::UNLIMITED));
+}
+
+#[test]
+fn test_rotating_restored_usage() {
+    let mut keys = rotating();
+    let mut out = [0u8; 128];
+    keys.seal_with_nonce(&NONCE, b"", &[0; 10], &mut out)
+        .unwrap();
+    keys.seal_with_nonce(&NONCE, b"", &[0; 10], &mut out)
+        .unwrap();
+    let persisted = keys.usage(1).unwrap();
+
+    // A restarted process rebuilds the ring and restores the persisted usage.
+    let mut keys = rotating();
+    assert_eq!(keys.usage(1), Some(KeyUsage::default()));
+    keys.set_usage(1, persisted).unwrap();
+    assert_eq!(keys.usage(1), Some(persisted));
+    assert!(!keys.needs_rotation());
+
+    keys.seal_with_nonce(&NONCE, b"", &[0; 10], &mut out)
+        .unwrap();
+    assert!(keys.needs_rotation());
+
+    // Restored usage at the byte limit flags the key directly.
+    let mut keys = rotating();
+    keys.set_usage(
+        1,
+        KeyUsage {
+            messages: 0,
+            bytes: LIMITS.max_bytes,
+        },
+    )
+    .unwrap();
+    assert!(keys.needs_rotation());
+
+    assert_eq!(
+        keys.set_usage(7, persisted),
+        Err(EnvelopeError::UnknownKey(7))
+    );
+}
+
+#[cfg(feature = "getrandom")]
+#[test]
+fn test_rotating_random_nonces() {
+    let mut keys = rotating();
+    let mut first = [0u8; 64];
+    let mut second = [0u8; 64];
+    let len = keys.seal(b"aad", b"Hello", &mut first).unwrap();
+    keys.reseal(&first[..len], &mut second).unwrap();
+
+    let first = Envelope::parse(&first[..len]).unwrap();
+    let second = Envelope::parse(&second[..len]).unwrap();
+    assert_ne!(first.nonce(), second.nonce());
+
+    let mut out = [0u8; 5];
+    second.open(&keys, &mut out).unwrap();
+    assert_eq!(&out, b"Hello");
+}
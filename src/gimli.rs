@@ -12,7 +12,12 @@ pub(crate) const ROUND_CONSTANT: u32 = 0x9e37_7900;
 
 #[cfg(target_arch = "aarch64")]
 mod neon;
-#[cfg(any(not(any(target_arch = "aarch64", target_arch = "x86_64")), test))]
+#[cfg(any(
+    not(any(target_arch = "aarch64", target_arch = "x86_64")),
+    test,
+    fuzzing,
+    kani
+))]
 mod portable;
 #[cfg(target_arch = "x86_64")]
 mod sse2;
@@ -81,3 +86,61 @@ pub(crate) fn gimli(state: &mut State) {
 pub(crate) fn gimli(state: &mut State) {
     portable::gimli(state);
 }
+
+#[cfg(kani)]
+mod verification {
+    use super::State;
+
+    /// `as_bytes` views the words in memory order, without copying.
+    #[kani::proof]
+    #[kani::unwind(49)]
+    fn as_bytes_is_memory_view() {
+        let state = State(kani::any());
+        let bytes = state.as_bytes();
+
+        assert!(core::ptr::eq(bytes.as_ptr(), state.0.as_ptr() as *const u8));
+        for i in 0..48 {
+            assert_eq!(bytes[i], state.0[i / 4].to_ne_bytes()[i % 4]);
+        }
+    }
+
+    /// Writes through `as_bytes_mut` land in the corresponding word and nowhere else.
+    #[kani::proof]
+    #[kani::unwind(13)]
+    fn as_bytes_mut_writes_words() {
+        let words: [u32; 12] = kani::any();
+        let index: usize = kani::any_where(|&i: &usize| i < 48);
+        let value: u8 = kani::any();
+
+        let mut state = State(words);
+        state.as_bytes_mut()[index] = value;
+
+        for word in 0..12 {
+            let mut expected = words[word].to_ne_bytes();
+            if word == index / 4 {
+                expected[index % 4] = value;
+            }
+            assert_eq!(state.0[word], u32::from_ne_bytes(expected));
+        }
+    }
+}
+
+/// Hooks used by the `cargo fuzz` targets in `fuzz/`, not part of the public API.
+#[cfg(fuzzing)]
+pub mod fuzzing {
+    use super::State;
+
+    /// Apply the Gimli permutation with the backend selected for this target.
+    pub fn permute(state: &mut [u32; 12]) {
+        let mut s = State(*state);
+        super::gimli(&mut s);
+        *state = s.0;
+    }
+
+    /// Apply the Gimli permutation with the portable reference backend.
+    pub fn permute_portable(state: &mut [u32; 12]) {
+        let mut s = State(*state);
+        super::portable::gimli(&mut s);
+        *state = s.0;
+    }
+}
@@ -115,3 +115,26 @@ mod tests {
         assert_eq!(state_neon.0, state_portable.0);
     }
 }
+
+#[cfg(kani)]
+mod verification {
+    use super::*;
+
+    /// The NEON permutation is equivalent to the portable one on every state.
+    #[kani::proof]
+    #[kani::unwind(25)]
+    fn neon_matches_portable() {
+        use super::super::portable;
+
+        let mut state_neon = State(kani::any());
+        let mut state_portable = state_neon.clone();
+
+        // SAFETY: NEON is available on all aarch64 targets.
+        unsafe {
+            gimli(&mut state_neon);
+        }
+        portable::gimli(&mut state_portable);
+
+        assert_eq!(state_neon.0, state_portable.0);
+    }
+}
@@ -120,3 +120,26 @@ mod tests {
         assert_eq!(state_sse2.0, state_portable.0);
     }
 }
+
+#[cfg(kani)]
+mod verification {
+    use super::*;
+
+    /// The SSE2 permutation is equivalent to the portable one on every state.
+    #[kani::proof]
+    #[kani::unwind(25)]
+    fn sse2_matches_portable() {
+        use super::super::portable;
+
+        let mut state_sse2 = State(kani::any());
+        let mut state_portable = state_sse2.clone();
+
+        // SAFETY: SSE2 is available on all x86_64 targets.
+        unsafe {
+            gimli(&mut state_sse2);
+        }
+        portable::gimli(&mut state_portable);
+
+        assert_eq!(state_sse2.0, state_portable.0);
+    }
+}
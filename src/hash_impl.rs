@@ -31,9 +31,49 @@
 //!
 //! assert_eq!(digest, digest2);
 //! ```
+//!
+//! # Customization
+//!
+//! [`Hasher::with_customization`] domain-separates hashes like cSHAKE (NIST SP 800-185): the
+//! function name and customization string are encoded unambiguously and absorbed in their own
+//! blocks before the message, and the padding uses a different domain byte. Without function name
+//! and customization it is the plain hash. [`tuple_hash`] and [`TupleHasher`] hash a sequence of
+//! byte strings like TupleHash, so that the element boundaries are part of the hash.
+//!
+//! ```
+//! use gimli_crypto::{Hasher, tuple_hash};
+//!
+//! let mut hasher = Hasher::with_customization(b"", b"key confirmation");
+//! hasher.update(b"transcript");
+//! let digest = hasher.finalize();
+//!
+//! assert_ne!(tuple_hash(&[b"ab", b"c"], b""), tuple_hash(&[b"a", b"bc"], b""));
+//! ```
+//!
+//! # Resuming
+//!
+//! [`Hasher::export_state`] encodes the hasher state in a versioned, portable format, e.g. to
+//! persist the progress of hashing a firmware image across reboots, and
+//! [`Hasher::import_state`] resumes from it.
+//!
+//! ```
+//! use gimli_crypto::{Hasher, hash};
+//!
+//! let image = [0xa5u8; 100];
+//!
+//! let mut hasher = Hasher::new();
+//! hasher.update(&image[..37]);
+//! let saved = hasher.export_state();
+//!
+//! // After a reboot.
+//! let mut hasher = Hasher::import_state(&saved).unwrap();
+//! hasher.update(&image[37..]);
+//! assert_eq!(hasher.finalize(), hash(&image));
+//! ```
 
 use crate::RATE;
 use crate::gimli::{State, gimli};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 /// `hash/gimli24v1` hash output size in bytes.
 pub const HASH_SIZE: usize = 32;
@@ -41,9 +81,22 @@ pub const HASH_SIZE: usize = 32;
 /// Domain separation byte for XOF (extendable output function).
 const DOMAIN_XOF: u8 = 0x1f;
 
+/// Domain separation byte for customized hashing, as in cSHAKE.
+const DOMAIN_CUSTOMIZED: u8 = 0x04;
+
+/// Function name of [`tuple_hash`].
+const TUPLE_HASH_NAME: &[u8] = b"TupleHash";
+
 /// Padding marker byte.
 const PADDING_MARKER: u8 = 0x80;
 
+/// Format version of exported [`Hasher`] states.
+const STATE_VERSION: u8 = 1;
+
+/// An exported [`Hasher`] state was rejected by [`Hasher::import_state`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidState;
+
 /// Hash arbitrary-length input data using `hash/gimli24v1`.
 ///
 /// This does not need any internal temporary buffer compared to the [`Hasher`] implementation.
@@ -100,6 +153,9 @@ pub fn hash(input: &[u8]) -> [u8; HASH_SIZE] {
 
 /// Hasher for incremental hashing.
 ///
+/// The state and the buffered input are zeroed on drop, so keyed uses such as MACs and KDFs do
+/// not leave key bytes behind.
+///
 /// # Example
 ///
 /// ```
@@ -110,23 +166,56 @@ pub fn hash(input: &[u8]) -> [u8; HASH_SIZE] {
 /// hasher.update(b"Gimli!");
 /// let digest = hasher.finalize();
 /// ```
-#[derive(Clone)]
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
 pub struct Hasher {
     state: State,
     buffer: [u8; RATE],
     buffer_len: usize,
+    domain: u8,
 }
 
 impl Hasher {
+    /// Size of an exported state in bytes.
+    pub const STATE_SIZE: usize = 3 + 48 + RATE;
+
     /// Create a new hasher.
     pub const fn new() -> Self {
         Self {
             state: State::new(),
             buffer: [0u8; RATE],
             buffer_len: 0,
+            domain: DOMAIN_XOF,
         }
     }
 
+    /// Create a hasher domain-separated by `function_name` and `customization`, like cSHAKE.
+    ///
+    /// `function_name` names a function defined on top of the hash, e.g. `b"TupleHash"`, and is
+    /// meant for such definitions. Applications choose the `customization`, e.g. a protocol and
+    /// role label. If both are empty this is [`Hasher::new`].
+    pub fn with_customization(function_name: &[u8], customization: &[u8]) -> Self {
+        let mut hasher = Self::new();
+        if function_name.is_empty() && customization.is_empty() {
+            return hasher;
+        }
+
+        // `bytepad(encode_string(function_name) || encode_string(customization), RATE)`.
+        hasher.update(left_encode(RATE as u64, &mut [0; 9]));
+        hasher.update_encoded_string(function_name);
+        hasher.update_encoded_string(customization);
+        let padding = (RATE - hasher.buffer_len) % RATE;
+        hasher.update(&[0; RATE][..padding]);
+
+        hasher.domain = DOMAIN_CUSTOMIZED;
+        hasher
+    }
+
+    /// Absorb `encode_string(data)`, the bit length of `data` followed by `data`.
+    fn update_encoded_string(&mut self, data: &[u8]) {
+        self.update(left_encode(8 * data.len() as u64, &mut [0; 9]));
+        self.update(data);
+    }
+
     /// Update the hasher with more data.
     pub fn update(&mut self, data: &[u8]) {
         let mut pos = 0;
@@ -154,6 +243,48 @@ impl Hasher {
         }
     }
 
+    /// Export the state, e.g. to persist partial progress and resume with
+    /// [`import_state`](Self::import_state).
+    ///
+    /// The encoding is `version || domain || buffer_len || state || buffer` with the permutation
+    /// state as little-endian words, so it is the same on all targets. The state holds
+    /// everything absorbed so far, store it like the data being hashed.
+    pub fn export_state(&self) -> [u8; Self::STATE_SIZE] {
+        let mut out = [0u8; Self::STATE_SIZE];
+        out[0] = STATE_VERSION;
+        out[1] = self.domain;
+        out[2] = self.buffer_len as u8;
+        for (bytes, word) in out[3..51].chunks_exact_mut(4).zip(&self.state.0) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        out[51..51 + self.buffer_len].copy_from_slice(&self.buffer[..self.buffer_len]);
+        out
+    }
+
+    /// Resume from a state of [`export_state`](Self::export_state).
+    pub fn import_state(bytes: &[u8; Self::STATE_SIZE]) -> Result<Self, InvalidState> {
+        let (version, domain, buffer_len) = (bytes[0], bytes[1], usize::from(bytes[2]));
+        let buffer: [u8; RATE] = bytes[51..].try_into().unwrap();
+        if version != STATE_VERSION
+            || (domain != DOMAIN_XOF && domain != DOMAIN_CUSTOMIZED)
+            || buffer_len >= RATE
+            || buffer[buffer_len..].iter().any(|&byte| byte != 0)
+        {
+            return Err(InvalidState);
+        }
+
+        let mut state = State::new();
+        for (word, bytes) in state.0.iter_mut().zip(bytes[3..51].chunks_exact(4)) {
+            *word = u32::from_le_bytes(bytes.try_into().unwrap());
+        }
+        Ok(Self {
+            state,
+            buffer,
+            buffer_len,
+            domain,
+        })
+    }
+
     /// Finalize the hash and return the digest.
     pub fn finalize(mut self) -> [u8; HASH_SIZE] {
         // Process buffered data with padding.
@@ -163,7 +294,7 @@ impl Hasher {
         }
 
         // Padding: domain separation at current position, padding marker at end of rate.
-        state_bytes[self.buffer_len] ^= DOMAIN_XOF;
+        state_bytes[self.buffer_len] ^= self.domain;
         state_bytes[RATE - 1] ^= PADDING_MARKER;
 
         gimli(&mut self.state);
@@ -186,5 +317,76 @@ impl Default for Hasher {
     }
 }
 
+/// `left_encode(value)` of SP 800-185, the big-endian bytes of `value` without leading zeros
+/// prefixed by their count.
+fn left_encode(value: u64, out: &mut [u8; 9]) -> &[u8] {
+    let len = (8 - value.leading_zeros() as usize / 8).max(1);
+    out[0] = len as u8;
+    out[1..=len].copy_from_slice(&value.to_be_bytes()[8 - len..]);
+    &out[..=len]
+}
+
+/// `right_encode(value)` of SP 800-185, like [`left_encode`] with the count as suffix.
+fn right_encode(value: u64, out: &mut [u8; 9]) -> &[u8] {
+    let len = (8 - value.leading_zeros() as usize / 8).max(1);
+    out[..len].copy_from_slice(&value.to_be_bytes()[8 - len..]);
+    out[len] = len as u8;
+    &out[..=len]
+}
+
+/// Hash of the sequence `elements` customized by `customization`, like TupleHash.
+///
+/// Each element is encoded with its length, so e.g. `["ab", "c"]` and `["a", "bc"]` hash
+/// differently.
+pub fn tuple_hash(elements: &[&[u8]], customization: &[u8]) -> [u8; HASH_SIZE] {
+    let mut hasher = TupleHasher::new(customization);
+    for element in elements {
+        hasher.push(element);
+    }
+    hasher.finalize()
+}
+
+/// Hasher for incremental [`tuple_hash`], one element at a time.
+///
+/// # Example
+///
+/// ```
+/// use gimli_crypto::{TupleHasher, tuple_hash};
+///
+/// let mut hasher = TupleHasher::new(b"handshake");
+/// hasher.push(b"client hello");
+/// hasher.push(b"server hello");
+///
+/// assert_eq!(
+///     hasher.finalize(),
+///     tuple_hash(&[b"client hello", b"server hello"], b"handshake")
+/// );
+/// ```
+#[derive(Clone)]
+pub struct TupleHasher {
+    hasher: Hasher,
+}
+
+impl TupleHasher {
+    /// Create a hasher customized by `customization`.
+    pub fn new(customization: &[u8]) -> Self {
+        Self {
+            hasher: Hasher::with_customization(TUPLE_HASH_NAME, customization),
+        }
+    }
+
+    /// Append the element `element`.
+    pub fn push(&mut self, element: &[u8]) {
+        self.hasher.update_encoded_string(element);
+    }
+
+    /// Finalize the hash and return the digest.
+    pub fn finalize(mut self) -> [u8; HASH_SIZE] {
+        self.hasher
+            .update(right_encode(8 * HASH_SIZE as u64, &mut [0; 9]));
+        self.hasher.finalize()
+    }
+}
+
 #[cfg(test)]
 mod tests;
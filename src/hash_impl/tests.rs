@@ -1,5 +1,7 @@
 extern crate std;
 use super::*;
+use std::println;
+use std::string::{String, ToString};
 use std::vec::Vec;
 
 fn hex_to_bytes(hex: &str) -> Vec<u8> {
@@ -85,3 +87,266 @@ fn test_different_messages() {
     let digest2 = hash(b"message2");
     assert_ne!(digest1, digest2);
 }
+
+struct TestVector {
+    count: usize,
+    message: Vec<u8>,
+    expected_digest: Vec<u8>,
+}
+
+fn parse_test_vectors() -> Vec<TestVector> {
+    let test_data = include_str!("../../LWC_HASH_KAT_256.txt");
+    let mut vectors = Vec::new();
+
+    let mut count = 0;
+    let mut message_hex = String::new();
+    let mut digest_hex = String::new();
+
+    for line in test_data.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            // Process the test vector
+            if !digest_hex.is_empty() {
+                vectors.push(TestVector {
+                    count,
+                    message: hex_to_bytes(&message_hex),
+                    expected_digest: hex_to_bytes(&digest_hex),
+                });
+            }
+
+            // Reset for next test
+            message_hex.clear();
+            digest_hex.clear();
+        } else if let Some(stripped) = line.strip_prefix("Count = ") {
+            count = stripped.parse().unwrap();
+        } else if let Some(stripped) = line.strip_prefix("Msg = ") {
+            message_hex = stripped.to_string();
+        } else if let Some(stripped) = line.strip_prefix("MD = ") {
+            digest_hex = stripped.to_string();
+        }
+    }
+
+    vectors
+}
+
+#[test]
+fn test_all_official_vectors() {
+    let vectors = parse_test_vectors();
+    assert_eq!(vectors.len(), 1025);
+
+    // Under miri, only test every 20th vector to keep test time reasonable
+    // Full coverage is still validated in regular test runs
+    #[cfg(miri)]
+    let test_vectors = vectors.iter().step_by(20);
+    #[cfg(miri)]
+    let test_vectors_len = test_vectors.clone().count();
+
+    #[cfg(not(miri))]
+    let test_vectors = vectors.iter();
+
+    for vector in test_vectors {
+        assert_eq!(
+            vector.message.len(),
+            vector.count - 1,
+            "Count {}: Message length mismatch",
+            vector.count
+        );
+
+        // One-shot hash
+        assert_eq!(
+            &hash(&vector.message)[..],
+            &vector.expected_digest[..],
+            "Count {}: Digest mismatch",
+            vector.count
+        );
+
+        // Incremental hash, split in uneven chunks
+        let mut hasher = Hasher::new();
+        for chunk in vector.message.chunks(7) {
+            hasher.update(chunk);
+        }
+        assert_eq!(
+            &hasher.finalize()[..],
+            &vector.expected_digest[..],
+            "Count {}: Incremental digest mismatch",
+            vector.count
+        );
+    }
+
+    #[cfg(miri)]
+    println!(
+        "Successfully tested {} of {} test vectors under miri",
+        test_vectors_len,
+        vectors.len()
+    );
+
+    #[cfg(not(miri))]
+    println!("Successfully tested {} test vectors", vectors.len());
+}
+
+#[test]
+fn test_encode() {
+    // SP 800-185 section 2.3.1.
+    assert_eq!(left_encode(0, &mut [0; 9]), [1, 0]);
+    assert_eq!(left_encode(255, &mut [0; 9]), [1, 255]);
+    assert_eq!(left_encode(256, &mut [0; 9]), [2, 1, 0]);
+    assert_eq!(
+        left_encode(u64::MAX, &mut [0; 9]),
+        [8, 255, 255, 255, 255, 255, 255, 255, 255]
+    );
+    assert_eq!(right_encode(0, &mut [0; 9]), [0, 1]);
+    assert_eq!(right_encode(256, &mut [0; 9]), [1, 0, 2]);
+}
+
+#[test]
+fn test_customization_vectors() {
+    let mut hasher = Hasher::with_customization(b"", b"Email Signature");
+    hasher.update(&[0, 1, 2, 3]);
+    assert_eq!(
+        hasher.finalize().to_vec(),
+        hex_to_bytes("f0be74dd67d35354db18d9e62a230238d951216bc86f3afd00b582bf33906e5f")
+    );
+
+    assert_eq!(
+        tuple_hash(
+            &[&[0, 1, 2], &[0x10, 0x11, 0x12, 0x13, 0x14, 0x15]],
+            b"My Tuple App"
+        )
+        .to_vec(),
+        hex_to_bytes("4ec0c36d17968773e80f13e83c36d4c8380802531db8fe57fd2165365835369e")
+    );
+}
+
+#[test]
+fn test_customization() {
+    let message = b"message";
+    let customized = |function_name: &[u8], customization: &[u8]| {
+        let mut hasher = Hasher::with_customization(function_name, customization);
+        hasher.update(message);
+        hasher.finalize()
+    };
+
+    // Without function name and customization it is the plain hash.
+    assert_eq!(customized(b"", b""), hash(message));
+
+    let digest = customized(b"", b"role");
+    assert_ne!(digest, hash(message));
+    assert_ne!(digest, customized(b"role", b""));
+    assert_ne!(digest, customized(b"", b"role2"));
+    assert_ne!(customized(b"ab", b"c"), customized(b"a", b"bc"));
+
+    // The padding domain keeps it apart from a plain hash of the encoded prefix.
+    let mut prefixed = std::vec![1, 16, 1, 0, 1, 32];
+    prefixed.extend_from_slice(b"role");
+    prefixed.resize(16, 0);
+    prefixed.extend_from_slice(message);
+    assert_ne!(digest, hash(&prefixed));
+
+    // Customization strings spanning several blocks.
+    let long = [0x5a; 40];
+    assert_ne!(customized(&long, b""), customized(b"", &long));
+}
+
+#[test]
+fn test_tuple_hash() {
+    let elements: [&[u8]; 3] = [b"client hello", b"", b"server hello"];
+    let digest = tuple_hash(&elements, b"");
+
+    let mut hasher = TupleHasher::new(b"");
+    for element in elements {
+        hasher.push(element);
+    }
+    assert_eq!(hasher.finalize(), digest);
+
+    assert_ne!(tuple_hash(&elements, b"app"), digest);
+    assert_ne!(
+        tuple_hash(&[b"ab", b"c"], b""),
+        tuple_hash(&[b"a", b"bc"], b"")
+    );
+    assert_ne!(tuple_hash(&[], b""), tuple_hash(&[b""], b""));
+    assert_ne!(tuple_hash(&[b""], b""), tuple_hash(&[b"", b""], b""));
+    assert_ne!(tuple_hash(&[b"abc"], b""), hash(b"abc"));
+}
+
+#[test]
+fn test_resume_state() {
+    let message: Vec<u8> = (0..50u8).collect();
+    let expected = hash(&message);
+
+    for split in 0..=message.len() {
+        let mut hasher = Hasher::new();
+        hasher.update(&message[..split]);
+        let state = hasher.export_state();
+
+        let mut resumed = Hasher::import_state(&state).unwrap();
+        assert_eq!(resumed.export_state(), state);
+        resumed.update(&message[split..]);
+        assert_eq!(resumed.finalize(), expected, "split {split}");
+    }
+
+    let mut hasher = Hasher::with_customization(b"", b"Email Signature");
+    let expected = {
+        let mut hasher = hasher.clone();
+        hasher.update(&message);
+        hasher.finalize()
+    };
+    hasher.update(&message[..21]);
+    let mut resumed = Hasher::import_state(&hasher.export_state()).unwrap();
+    resumed.update(&message[21..]);
+    assert_eq!(resumed.finalize(), expected);
+}
+
+#[test]
+fn test_state_format() {
+    let state = Hasher::new().export_state();
+    assert_eq!(state.len(), Hasher::STATE_SIZE);
+    assert_eq!(state[..3], [1, 0x1f, 0]);
+    assert!(state[3..].iter().all(|&byte| byte == 0));
+
+    let mut hasher = Hasher::new();
+    hasher.update(&[0xaa; RATE + 3]);
+    let state = hasher.export_state();
+    assert_eq!(state[..3], [1, 0x1f, 3]);
+    assert_eq!(
+        state[51..],
+        [0xaa, 0xaa, 0xaa, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+    );
+    // The permutation state is little-endian words independent of the target.
+    let mut state_bytes = [0u8; 48];
+    for (bytes, word) in state_bytes.chunks_exact_mut(4).zip(&hasher.state.0) {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+    assert_eq!(state[3..51], state_bytes);
+}
+
+#[test]
+fn test_import_invalid_state() {
+    let mut hasher = Hasher::new();
+    hasher.update(b"abc");
+    let state = hasher.export_state();
+
+    let mut version = state;
+    version[0] = 2;
+    let mut domain = state;
+    domain[1] = 0x06;
+    let mut buffer_len = state;
+    buffer_len[2] = RATE as u8;
+    let mut buffer = state;
+    buffer[51 + 3] = 1;
+
+    for state in [version, domain, buffer_len, buffer] {
+        assert_eq!(Hasher::import_state(&state).err(), Some(InvalidState));
+    }
+}
+
+#[test]
+fn test_zeroize_clears_buffer() {
+    let mut hasher = TupleHasher::new(b"key");
+    hasher.push(&[0xa5; 32]);
+    assert!(hasher.hasher.buffer.contains(&0xa5));
+
+    hasher.hasher.zeroize();
+    assert_eq!(hasher.hasher.buffer, [0; RATE]);
+    assert_eq!(hasher.hasher.buffer_len, 0);
+    assert!(hasher.hasher.state.as_bytes().iter().all(|&byte| byte == 0));
+}
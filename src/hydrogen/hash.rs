@@ -0,0 +1,151 @@
+//! # libhydrogen generic hash, `hydro_hash_*`
+//!
+//! A context-separated hash with an optional key and a variable output length between
+//! [`MIN_OUTPUT_SIZE`] and [`MAX_OUTPUT_SIZE`] bytes. The output length is part of the input, so
+//! a 32-byte digest is not a prefix of a 64-byte digest of the same message.
+//!
+//! # Usage
+//!
+//! ```
+//! use gimli_crypto::hydrogen::hash;
+//!
+//! let mut digest = [0u8; hash::OUTPUT_SIZE];
+//! hash::hash(&mut digest, b"Hello, World!", b"Examples", None).unwrap();
+//!
+//! let mut hasher = hash::Hasher::new(b"Examples", None);
+//! hasher.update(b"Hello, ");
+//! hasher.update(b"World!");
+//! let mut streamed = [0u8; hash::OUTPUT_SIZE];
+//! hasher.finalize(&mut streamed).unwrap();
+//!
+//! assert_eq!(digest, streamed);
+//! ```
+
+use super::{Context, DOMAIN_XOF, InvalidOutputLength, pad, permute, xor_in};
+use crate::RATE;
+use crate::gimli::State;
+
+/// Key size of the keyed hash, `hydro_hash_KEYBYTES`.
+pub const KEY_SIZE: usize = 32;
+
+/// Recommended output size, `hydro_hash_BYTES`.
+pub const OUTPUT_SIZE: usize = 32;
+
+/// Smallest supported output size, `hydro_hash_BYTES_MIN`.
+pub const MIN_OUTPUT_SIZE: usize = 16;
+
+/// Largest supported output size, `hydro_hash_BYTES_MAX`.
+pub const MAX_OUTPUT_SIZE: usize = 65535;
+
+/// Incremental hasher, `hydro_hash_state`.
+#[derive(Clone)]
+pub struct Hasher {
+    state: State,
+    buffer_len: usize,
+}
+
+impl Hasher {
+    /// Create a hasher for `context`, keyed if `key` is given, `hydro_hash_init`.
+    pub fn new(context: &Context, key: Option<&[u8; KEY_SIZE]>) -> Self {
+        // pad(str_enc("kmac") || str_enc(context)) || pad(str_enc(key))
+        let mut block = [0u8; 4 * RATE];
+        block[..6].copy_from_slice(&[4, b'k', b'm', b'a', b'c', 8]);
+        block[6..14].copy_from_slice(context);
+        let len = match key {
+            Some(key) => {
+                block[RATE] = KEY_SIZE as u8;
+                block[RATE + 1..RATE + 1 + KEY_SIZE].copy_from_slice(key);
+                4 * RATE
+            }
+            None => 2 * RATE,
+        };
+
+        Self::with_block(&block[..len])
+    }
+
+    /// Create a hasher for `context` and `key` with a 64-bit tweak, `hydro_hash_init_with_tweak`.
+    pub(super) fn with_tweak(context: &Context, tweak: u64, key: &[u8; KEY_SIZE]) -> Self {
+        // pad(str_enc("tmac") || str_enc(context)) || pad(str_enc(key)) || pad(str_enc(tweak))
+        let mut block = [0u8; 5 * RATE];
+        block[..6].copy_from_slice(&[4, b't', b'm', b'a', b'c', 8]);
+        block[6..14].copy_from_slice(context);
+        block[RATE] = KEY_SIZE as u8;
+        block[RATE + 1..RATE + 1 + KEY_SIZE].copy_from_slice(key);
+        block[4 * RATE] = 8;
+        block[4 * RATE + 1..4 * RATE + 9].copy_from_slice(&tweak.to_le_bytes());
+
+        Self::with_block(&block)
+    }
+
+    fn with_block(block: &[u8]) -> Self {
+        let mut hasher = Self {
+            state: State::new(),
+            buffer_len: 0,
+        };
+        hasher.update(block);
+        hasher
+    }
+
+    /// Absorb more data, `hydro_hash_update`.
+    pub fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let available = data.len().min(RATE - self.buffer_len);
+            xor_in(&mut self.state, self.buffer_len, &data[..available]);
+            self.buffer_len += available;
+            data = &data[available..];
+
+            if self.buffer_len == RATE {
+                permute(&mut self.state, 0);
+                self.buffer_len = 0;
+            }
+        }
+    }
+
+    /// Fill `out` with the digest, `hydro_hash_final`.
+    ///
+    /// The length of `out` is encoded in the digest, so outputs of different lengths are
+    /// unrelated. Fails if the length is outside `MIN_OUTPUT_SIZE..=MAX_OUTPUT_SIZE`.
+    pub fn finalize(self, out: &mut [u8]) -> Result<(), InvalidOutputLength> {
+        if !(MIN_OUTPUT_SIZE..=MAX_OUTPUT_SIZE).contains(&out.len()) {
+            return Err(InvalidOutputLength);
+        }
+        self.squeeze(out);
+        Ok(())
+    }
+
+    /// Finalize into `out` without checking its length, which must be in range.
+    pub(super) fn squeeze(mut self, out: &mut [u8]) {
+        debug_assert!((MIN_OUTPUT_SIZE..=MAX_OUTPUT_SIZE).contains(&out.len()));
+
+        // right_enc(out_len) || 0x00
+        let len = (out.len() as u16).to_le_bytes();
+        if len[1] == 0 {
+            self.update(&[1, len[0], 0]);
+        } else {
+            self.update(&[2, len[0], len[1], 0]);
+        }
+        pad(&mut self.state, self.buffer_len, DOMAIN_XOF);
+
+        for chunk in out.chunks_mut(RATE) {
+            permute(&mut self.state, 0);
+            chunk.copy_from_slice(&self.state.as_bytes()[..chunk.len()]);
+        }
+    }
+}
+
+/// Hash `input` into `out` in one call, `hydro_hash_hash`.
+///
+/// Fails if the length of `out` is outside `MIN_OUTPUT_SIZE..=MAX_OUTPUT_SIZE`.
+pub fn hash(
+    out: &mut [u8],
+    input: &[u8],
+    context: &Context,
+    key: Option<&[u8; KEY_SIZE]>,
+) -> Result<(), InvalidOutputLength> {
+    let mut hasher = Hasher::new(context, key);
+    hasher.update(input);
+    hasher.finalize(out)
+}
+
+#[cfg(test)]
+mod tests;
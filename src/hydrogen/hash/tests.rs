@@ -0,0 +1,175 @@
+extern crate std;
+use super::*;
+use std::vec;
+use std::vec::Vec;
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+const CONTEXT: &Context = b"Examples";
+
+fn test_key() -> [u8; KEY_SIZE] {
+    core::array::from_fn(|i| i as u8)
+}
+
+fn test_message(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 7) as u8).collect()
+}
+
+struct TestVector {
+    message_len: usize,
+    keyed: bool,
+    digest: &'static str,
+}
+
+// Generated with libhydrogen 0.9.5 from `hydro_hash_hash` with the key `00 01 .. 1f` (or no key),
+// the context "Examples" and the message `i * 7` for `i` in `0..message_len`.
+const VECTORS: &[TestVector] = &[
+    TestVector {
+        message_len: 0,
+        keyed: false,
+        digest: "f28da694ba2198c9b30dceb7ad4a2a607fc19983729ae5c50892ecb67b921352",
+    },
+    TestVector {
+        message_len: 0,
+        keyed: true,
+        digest: "08663a06b030c38022bac9549bb028a7d8e80bcaea5a2ed7e1bdf432b962623b",
+    },
+    TestVector {
+        message_len: 1,
+        keyed: true,
+        digest: "5f9dea97bbb2ca6f038f507ac08ccc84",
+    },
+    TestVector {
+        message_len: 15,
+        keyed: false,
+        digest: "575b218a2eb01005a288e7d185d93b78c0",
+    },
+    TestVector {
+        message_len: 16,
+        keyed: true,
+        digest: "ad91aa784b6aeb1d11bb1b634462af19fa8d2ae57418b2f77728f4be9a8fdfaa",
+    },
+    TestVector {
+        message_len: 17,
+        keyed: false,
+        digest: "2cd87405cad08d0c8525c14aea56ac34f043412fd5eaa8b8f3c3dd9829fbe709\
+                 8d8795f288525501296b7f20a30e519b",
+    },
+    TestVector {
+        message_len: 100,
+        keyed: true,
+        digest: "f353c89ed9838d3e1f5f552d3831aa7268723fd5e33a69c54a12842127cebfc6\
+                 126737c1ce970b45fd09d413083586f8810798fbbfabf5ea9ebb03754c80ad9b",
+    },
+    TestVector {
+        message_len: 31,
+        keyed: true,
+        digest: "5933878b059cef1ad63ced2db0cedab694e52f64c2ba2d0063893881da5536ab\
+                 00aecf11eb8893369eb56882881d49d89a08f5b37064fc492748c99a677da599\
+                 f5db2ddb717aac84f84e3b8eef1b05f2a7c14931b329437f4e4403419a09c691\
+                 4e89dc8d350c16c2ddd72fed92b254641fd6de94da78fee99bee6ca61f332eda\
+                 25573625304eb9223f48876b9a54cdf2ecbb5023680a13e0f8714f2ba9664194\
+                 80cd7dff4041897aa2c521827eb5a62513f7450118bda02334c205ca5ecd37ea\
+                 eb64b7b51882a1ea6a28e9bfd2b151bb2c2c93e231b2cf303a6774ed372a8a51\
+                 93d7c4a3d1549b18b9833260cd976c2ed3f9fd0d38e2b3d8f09ecc39c0d4aa",
+    },
+    TestVector {
+        message_len: 48,
+        keyed: false,
+        digest: "7a7a91c3bc493eba2297c77ed009a01e30a975bf03d6f767c7c0b0636df861e7\
+                 d224a008b67aea3176da35d4d2412ea5db463d59d34410f559bd9ef17d05fd8f\
+                 61961e32312cdc18170c8b49b84e7e8c75ec20e54c6df379dbd2b920d38e6f24\
+                 4051a626f8717cb488d27482f0204c010aea0c4385d778c08f9081a980b2e6bb\
+                 f8f76cbfaf4651a3a46565d1f0e4b9e04b5418872b06fabbc42740801b5a35dc\
+                 a529e73c7c88522b9eaed944d8bb4ff0c88e1723ce6ff4137d6de6e2ee0ed14b\
+                 3a069f6f5d5ef7a91640d8179bdc63d8af4ae588a90e143345a942362a4d02ec\
+                 a6a3c813e311e2190cc9ba7b2190b482da869951072b25ba373457bbfc2e0cbe",
+    },
+    TestVector {
+        message_len: 5,
+        keyed: true,
+        digest: "cf78aa31d036d469a2bf23e252684bea3c4f51bc3bce885b8873bb7b52c0dcdb\
+                 f7a33f266869e0bbce58535d0689ab077952868a0cd7f81f07fdd6959cdd475e\
+                 5a6ebaa61a93fc1edef350cac2f1e465203846845e8a126475251d780f137154\
+                 8b90ac1f63672edd13f1e90120f84c59cf1d38401bcf79452dbe59edc47a9d24\
+                 2d7ef23c1970e32b3aec43c97197c06a4cd1dd8e15feb354aef76b8055255d94\
+                 52d2cdd881a130a3cdc343972205a360847abd0307ec8fa6cd184e41cf309c7e\
+                 0e605ac4c9be8ebbc1bd8635dc99e0a318b3ad9e3c4162b5f8b7d6462390299d\
+                 05aa6d3e95343e3d381c935712e5e5ba06aea80bd0a0434f3bf4184d935aa2b4\
+                 4e85c02a560d0d9b11fb62eb7139e77f72e60916521a077d1f4fdd56d28a4ebe\
+                 fbd49dba006f2e9103b5f6ea",
+    },
+];
+
+#[test]
+fn test_libhydrogen_vectors() {
+    let key = test_key();
+
+    for vector in VECTORS {
+        let expected = hex_to_bytes(vector.digest);
+        let key = vector.keyed.then_some(&key);
+        let mut digest = vec![0u8; expected.len()];
+        hash(&mut digest, &test_message(vector.message_len), CONTEXT, key).unwrap();
+        assert_eq!(
+            digest,
+            expected,
+            "message length {}, output length {}",
+            vector.message_len,
+            expected.len()
+        );
+    }
+}
+
+#[test]
+fn test_incremental_matches_libhydrogen() {
+    // Generated with libhydrogen 0.9.5 by updating a keyed state with 3, 40 and 57 bytes.
+    let expected = hex_to_bytes("5eb6988a6c03ab85ab5ca0471b8af7011e05539e0dfa25efdb3931218851d9d6");
+    let message = test_message(100);
+
+    let mut hasher = Hasher::new(CONTEXT, Some(&test_key()));
+    hasher.update(&message[..3]);
+    hasher.update(&message[3..43]);
+    hasher.update(&message[43..]);
+    let mut digest = [0u8; OUTPUT_SIZE];
+    hasher.finalize(&mut digest).unwrap();
+
+    assert_eq!(&digest[..], &expected[..]);
+}
+
+#[test]
+fn test_incremental_splits() {
+    let key = test_key();
+    let message = test_message(100);
+
+    let mut expected = [0u8; OUTPUT_SIZE];
+    hash(&mut expected, &message, CONTEXT, Some(&key)).unwrap();
+
+    for split in 0..=message.len() {
+        let mut hasher = Hasher::new(CONTEXT, Some(&key));
+        hasher.update(&message[..split]);
+        hasher.update(&message[split..]);
+        let mut digest = [0u8; OUTPUT_SIZE];
+        hasher.finalize(&mut digest).unwrap();
+        assert_eq!(digest, expected, "split at {split}");
+    }
+}
+
+#[test]
+fn test_invalid_output_size() {
+    let mut short = [0u8; MIN_OUTPUT_SIZE - 1];
+    assert_eq!(
+        hash(&mut short, b"", CONTEXT, None),
+        Err(InvalidOutputLength)
+    );
+
+    let mut long = vec![0u8; MAX_OUTPUT_SIZE + 1];
+    assert_eq!(
+        hash(&mut long, b"", CONTEXT, None),
+        Err(InvalidOutputLength)
+    );
+}
@@ -0,0 +1,41 @@
+//! # libhydrogen key derivation, `hydro_kdf_*`
+//!
+//! Derives any number of subkeys from a master key, a context and a 64-bit subkey id. Subkeys
+//! with different ids, contexts or lengths are unrelated.
+//!
+//! # Usage
+//!
+//! ```
+//! use gimli_crypto::hydrogen::kdf;
+//!
+//! let master_key = [7u8; kdf::KEY_SIZE];
+//! let mut subkey = [0u8; 32];
+//! kdf::derive_from_key(&mut subkey, 1, b"Examples", &master_key).unwrap();
+//! ```
+
+use super::hash::{Hasher, MAX_OUTPUT_SIZE, MIN_OUTPUT_SIZE};
+use super::{Context, InvalidOutputLength};
+
+/// Master key size in bytes, `hydro_kdf_KEYBYTES`.
+pub const KEY_SIZE: usize = 32;
+
+/// Smallest supported subkey size, `hydro_kdf_BYTES_MIN`.
+pub const MIN_SUBKEY_SIZE: usize = MIN_OUTPUT_SIZE;
+
+/// Largest supported subkey size, `hydro_kdf_BYTES_MAX`.
+pub const MAX_SUBKEY_SIZE: usize = MAX_OUTPUT_SIZE;
+
+/// Fill `subkey` with the subkey `subkey_id` of `key` for `context`, `hydro_kdf_derive_from_key`.
+///
+/// Fails if the length of `subkey` is outside `MIN_SUBKEY_SIZE..=MAX_SUBKEY_SIZE`.
+pub fn derive_from_key(
+    subkey: &mut [u8],
+    subkey_id: u64,
+    context: &Context,
+    key: &[u8; KEY_SIZE],
+) -> Result<(), InvalidOutputLength> {
+    Hasher::with_tweak(context, subkey_id, key).finalize(subkey)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,73 @@
+extern crate std;
+use super::*;
+use std::vec;
+use std::vec::Vec;
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+// Generated with libhydrogen 0.9.5 from `hydro_kdf_derive_from_key` with the key
+// `00 01 .. 1f` and the context "Examples".
+const VECTORS: &[(u64, &str)] = &[
+    (
+        0,
+        "34e4159ce7abb36a188cb8bcc4690ee11582c5d2c0c6cb5c79299ef7dd9e50ce",
+    ),
+    (
+        1,
+        "7cfe68f93e9e687919729357d45f752128a0a630f5a5d48ee58626dfeef4cf76",
+    ),
+    (2, "172a7b107b88e05433d84175ff837d5a"),
+    (
+        0x0123456789abcdef,
+        "258b9de9bb43642b655f2a7502d8c12cc8699f0ae5bcc02fe9988a46dc08866c\
+         aaf228928e34779e591acca73ade85edc648670c9dbff602c5b1d62d4d77fdcb",
+    ),
+    (
+        u64::MAX,
+        "73a02449bd822f8a9cca8b44097eab1f85d4e008c4737cbeb717d1602ce57755\
+         bffed3c6f9eda4ebee7cf909ee40167fda4430735c62d2b08ab84bf39dc33df2\
+         cb26222943af5947973512a8645bcf132c68b94a0d4f8fb95099642c0930ef18\
+         c5bf42fdbdcec0373b9e078a36a481545c7dc8dd0417d8b2723b2954ee8451fd\
+         41aa2fde6fe896d6346b04e7778cc8037270aa3955205ed8d67b41e9d9c1178e\
+         9e72b8cd2aed72651e1cbbcf557a277c6f7ede87dedf5d1bd912b50cb3ba3546\
+         60d1333230bb6b106f9eec625068c08dad9ee3b1ae8146d390136686c38c9ebf\
+         adf179ac5769a2f7c9ceb98f19c1a8fd82a39a91a80543e79b46fecb0d070d9e\
+         83eff56fa8ca438d677bdab80ebbabac40ed3a310d612f577aead60d537a982b\
+         80dca462b9d7c2c1a116f815",
+    ),
+    (42, "94496c72637dd05aa847f973811cad0aad"),
+];
+
+#[test]
+fn test_libhydrogen_vectors() {
+    let key: [u8; KEY_SIZE] = core::array::from_fn(|i| i as u8);
+
+    for (subkey_id, expected) in VECTORS {
+        let expected = hex_to_bytes(expected);
+        let mut subkey = vec![0u8; expected.len()];
+        derive_from_key(&mut subkey, *subkey_id, b"Examples", &key).unwrap();
+        assert_eq!(subkey, expected, "subkey id {subkey_id}");
+    }
+}
+
+#[test]
+fn test_invalid_subkey_size() {
+    let key = [0u8; KEY_SIZE];
+
+    let mut short = [0u8; MIN_SUBKEY_SIZE - 1];
+    assert_eq!(
+        derive_from_key(&mut short, 0, b"Examples", &key),
+        Err(InvalidOutputLength)
+    );
+
+    let mut long = vec![0u8; MAX_SUBKEY_SIZE + 1];
+    assert_eq!(
+        derive_from_key(&mut long, 0, b"Examples", &key),
+        Err(InvalidOutputLength)
+    );
+}
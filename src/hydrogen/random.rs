@@ -0,0 +1,48 @@
+//! # libhydrogen deterministic random generator, `hydro_random_buf_deterministic`
+//!
+//! Expands a 32-byte seed into any number of pseudorandom bytes. The output length is part of
+//! the input, so a shorter output is not a prefix of a longer one for the same seed.
+//!
+//! # Usage
+//!
+//! ```
+//! use gimli_crypto::hydrogen::random;
+//!
+//! let seed = [7u8; random::SEED_SIZE];
+//! let mut a = [0u8; 64];
+//! let mut b = [0u8; 64];
+//! random::buf_deterministic(&mut a, &seed);
+//! random::buf_deterministic(&mut b, &seed);
+//!
+//! assert_eq!(a, b);
+//! ```
+
+use super::{TAG_HEADER, TAG_PAYLOAD, permute, xor_in};
+use crate::RATE;
+use crate::gimli::State;
+
+/// Seed size in bytes, `hydro_random_SEEDBYTES`.
+pub const SEED_SIZE: usize = 32;
+
+/// Fill `out` with bytes derived from `seed`, `hydro_random_buf_deterministic`.
+pub fn buf_deterministic(out: &mut [u8], seed: &[u8; SEED_SIZE]) {
+    let mut state = State::new();
+
+    let state_bytes = state.as_bytes_mut();
+    state_bytes[..8].copy_from_slice(&[7, b'd', b'r', b'b', b'g', b'2', b'5', b'6']);
+    state_bytes[8..RATE].copy_from_slice(&(out.len() as u64).to_le_bytes());
+    permute(&mut state, TAG_HEADER);
+
+    xor_in(&mut state, 0, &seed[..RATE]);
+    permute(&mut state, TAG_PAYLOAD);
+    xor_in(&mut state, 0, &seed[RATE..]);
+    permute(&mut state, TAG_PAYLOAD);
+
+    for chunk in out.chunks_mut(RATE) {
+        permute(&mut state, 0);
+        chunk.copy_from_slice(&state.as_bytes()[..chunk.len()]);
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,42 @@
+extern crate std;
+use super::*;
+use std::vec;
+use std::vec::Vec;
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+// Generated with libhydrogen 0.9.5 from `hydro_random_buf_deterministic` with the seed
+// `00 01 .. 1f`.
+const VECTORS: &[&str] = &[
+    "27",
+    "4afe9f3e978d6a4ae64288528b3908",
+    "08e532c64c0b577b8e24528da71f02cf",
+    "75beebf14c200fef101d3f14ee5835a6b9",
+    "a25e12a4eeb53d096d9a2d2f92ecf05e44d81f6e42c61a9804002779bcd439df",
+    "b95733b63c8a8cf23135b27aa3dc6f4c0482caaeaf5403bde45b55df447a7ff6\
+     e8db9465bfc643e914ed2f4a9cd8f4976472f7246fbc1f91987f43ef8cc0fbbb\
+     ab6c95371dd0334db03e0bc552f770cc43a4053d9f70d6cbd5a0d2b61bf318d7\
+     82f927a2",
+];
+
+#[test]
+fn test_libhydrogen_vectors() {
+    let seed: [u8; SEED_SIZE] = core::array::from_fn(|i| i as u8);
+
+    for expected in VECTORS {
+        let expected = hex_to_bytes(expected);
+        let mut out = vec![0u8; expected.len()];
+        buf_deterministic(&mut out, &seed);
+        assert_eq!(out, expected, "output length {}", expected.len());
+    }
+}
+
+#[test]
+fn test_empty_output() {
+    buf_deterministic(&mut [], &[0u8; SEED_SIZE]);
+}
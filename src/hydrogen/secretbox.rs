@@ -0,0 +1,242 @@
+//! # libhydrogen secret-key encryption, `hydro_secretbox_*`
+//!
+//! Deterministic-nonce-misuse-resistant authenticated encryption with a 64-bit message id and a
+//! context. Encryption makes two passes over the message: the first derives a synthetic IV (SIV)
+//! from a random IV and the message, the second encrypts the message under the SIV and computes
+//! a MAC. The 36-byte header `SIV || MAC` is prepended to the ciphertext by libhydrogen, so a
+//! libhydrogen ciphertext is `header || buffer` with the values of this module.
+//!
+//! # Usage
+//!
+//! ```
+//! use gimli_crypto::hydrogen::secretbox;
+//!
+//! let key = [7u8; secretbox::KEY_SIZE];
+//! let iv = [1u8; secretbox::IV_SIZE]; // Random in practice, see `encrypt_in_place`.
+//! let mut data = *b"Secret message";
+//!
+//! let header = secretbox::encrypt_in_place_with_iv(&key, b"Examples", 0, &iv, &mut data);
+//!
+//! secretbox::decrypt_in_place(&key, b"Examples", 0, &header, &mut data)
+//!     .expect("authentication failed");
+//!
+//! assert_eq!(&data, b"Secret message");
+//! ```
+
+use super::hash::Hasher;
+use super::{
+    Context, DOMAIN_AEAD, DOMAIN_XOF, TAG_FINAL, TAG_FINAL0, TAG_HEADER, TAG_KEY, TAG_KEY0,
+    TAG_PAYLOAD, pad, permute, xor_in,
+};
+use crate::gimli::State;
+use crate::{AuthenticationFailed, RATE};
+use subtle::ConstantTimeEq;
+
+/// Secretbox key size in bytes, `hydro_secretbox_KEYBYTES`.
+pub const KEY_SIZE: usize = 32;
+
+/// Random IV size in bytes.
+pub const IV_SIZE: usize = 20;
+
+/// Synthetic IV size in bytes.
+pub const SIV_SIZE: usize = 20;
+
+/// MAC size in bytes.
+pub const MAC_SIZE: usize = 16;
+
+/// Header size in bytes, `hydro_secretbox_HEADERBYTES`.
+pub const HEADER_SIZE: usize = SIV_SIZE + MAC_SIZE;
+
+/// Probe size in bytes, `hydro_secretbox_PROBEBYTES`.
+pub const PROBE_SIZE: usize = 16;
+
+/// Secretbox header, `SIV || MAC`, prepended to the ciphertext on the wire.
+pub type Header = [u8; HEADER_SIZE];
+
+/// Probe authenticating a header, see [`probe_create`].
+pub type Probe = [u8; PROBE_SIZE];
+
+/// Initialize the state with the context, key, IV and message id, `hydro_secretbox_setup`.
+fn setup(
+    key: &[u8; KEY_SIZE],
+    context: &Context,
+    msg_id: u64,
+    iv: &[u8; IV_SIZE],
+    key_tag: u8,
+) -> State {
+    let mut state = State::new();
+
+    let state_bytes = state.as_bytes_mut();
+    state_bytes[..8].copy_from_slice(&[6, b's', b'b', b'x', b'2', b'5', b'6', 8]);
+    state_bytes[8..RATE].copy_from_slice(context);
+    permute(&mut state, TAG_HEADER);
+
+    xor_in(&mut state, 0, &key[..RATE]);
+    permute(&mut state, key_tag);
+    xor_in(&mut state, 0, &key[RATE..]);
+    permute(&mut state, key_tag);
+
+    xor_in(&mut state, 0, &[IV_SIZE as u8]);
+    xor_in(&mut state, 1, &iv[..RATE - 1]);
+    permute(&mut state, TAG_HEADER);
+    xor_in(&mut state, 0, &iv[RATE - 1..]);
+    xor_in(&mut state, IV_SIZE - RATE, &msg_id.to_le_bytes());
+    permute(&mut state, TAG_HEADER);
+
+    state
+}
+
+/// Mix the key into the capacity twice, `hydro_secretbox_final`.
+fn finalize(state: &mut State, key: &[u8; KEY_SIZE], tag: u8) {
+    xor_in(state, RATE, key);
+    permute(state, tag);
+    xor_in(state, RATE, key);
+    permute(state, tag);
+}
+
+/// Encrypt `buffer` in-place with an explicit `iv` and return the header.
+///
+/// This is `hydro_secretbox_encrypt` with a caller-provided IV. The IV only needs to be
+/// unpredictable for the scheme to stay secure, a repeated IV reveals whether two messages with
+/// the same id and context are equal but nothing else.
+#[must_use]
+pub fn encrypt_in_place_with_iv(
+    key: &[u8; KEY_SIZE],
+    context: &Context,
+    msg_id: u64,
+    iv: &[u8; IV_SIZE],
+    buffer: &mut [u8],
+) -> Header {
+    // First pass: compute the SIV.
+    let mut state = setup(key, context, msg_id, iv, TAG_KEY0);
+
+    let mut iter = buffer.chunks_exact(RATE);
+    for chunk in &mut iter {
+        xor_in(&mut state, 0, chunk);
+        permute(&mut state, TAG_PAYLOAD);
+    }
+    let remainder = iter.remainder();
+    xor_in(&mut state, 0, remainder);
+    pad(&mut state, remainder.len(), DOMAIN_XOF);
+    permute(&mut state, TAG_PAYLOAD);
+
+    finalize(&mut state, key, TAG_FINAL0);
+
+    let mut siv = [0u8; SIV_SIZE];
+    siv.copy_from_slice(&state.as_bytes()[RATE..RATE + SIV_SIZE]);
+
+    // Second pass: encrypt the message and compute the MAC.
+    let mut state = setup(key, context, msg_id, &siv, TAG_KEY);
+
+    let mut iter = buffer.chunks_exact_mut(RATE);
+    for chunk in &mut iter {
+        xor_in(&mut state, 0, chunk);
+        chunk.copy_from_slice(&state.as_bytes()[..RATE]);
+        permute(&mut state, TAG_PAYLOAD);
+    }
+    let remainder = iter.into_remainder();
+    xor_in(&mut state, 0, remainder);
+    remainder.copy_from_slice(&state.as_bytes()[..remainder.len()]);
+    pad(&mut state, remainder.len(), DOMAIN_AEAD);
+    permute(&mut state, TAG_PAYLOAD);
+
+    finalize(&mut state, key, TAG_FINAL);
+
+    let mut header = [0u8; HEADER_SIZE];
+    header[..SIV_SIZE].copy_from_slice(&siv);
+    header[SIV_SIZE..].copy_from_slice(&state.as_bytes()[RATE..RATE + MAC_SIZE]);
+    header
+}
+
+/// Encrypt `buffer` in-place with a random IV and return the header, `hydro_secretbox_encrypt`.
+#[cfg(feature = "getrandom")]
+#[must_use]
+pub fn encrypt_in_place(
+    key: &[u8; KEY_SIZE],
+    context: &Context,
+    msg_id: u64,
+    buffer: &mut [u8],
+) -> Header {
+    use aead::rand_core::RngCore;
+
+    let mut iv = [0u8; IV_SIZE];
+    aead::OsRng.fill_bytes(&mut iv);
+
+    encrypt_in_place_with_iv(key, context, msg_id, &iv, buffer)
+}
+
+/// Decrypt `buffer` in-place, `hydro_secretbox_decrypt`.
+///
+/// The message id and context must match the ones used for encryption. On authentication
+/// failure the buffer is zeroed.
+pub fn decrypt_in_place(
+    key: &[u8; KEY_SIZE],
+    context: &Context,
+    msg_id: u64,
+    header: &Header,
+    buffer: &mut [u8],
+) -> Result<(), AuthenticationFailed> {
+    let (siv, mac) = header.split_at(SIV_SIZE);
+    let mut state = setup(key, context, msg_id, siv.try_into().unwrap(), TAG_KEY);
+
+    let mut iter = buffer.chunks_exact_mut(RATE);
+    for chunk in &mut iter {
+        let state_bytes = state.as_bytes_mut();
+        for i in 0..RATE {
+            let ciphertext_byte = chunk[i];
+            chunk[i] = state_bytes[i] ^ ciphertext_byte;
+            state_bytes[i] = ciphertext_byte;
+        }
+        permute(&mut state, TAG_PAYLOAD);
+    }
+    let remainder = iter.into_remainder();
+    let state_bytes = state.as_bytes_mut();
+    for i in 0..remainder.len() {
+        let ciphertext_byte = remainder[i];
+        remainder[i] = state_bytes[i] ^ ciphertext_byte;
+        state_bytes[i] = ciphertext_byte;
+    }
+    pad(&mut state, remainder.len(), DOMAIN_AEAD);
+    permute(&mut state, TAG_PAYLOAD);
+
+    finalize(&mut state, key, TAG_FINAL);
+
+    // Verify MAC using constant-time comparison.
+    let computed_mac = &state.as_bytes()[RATE..RATE + MAC_SIZE];
+    if computed_mac.ct_eq(mac).into() {
+        Ok(())
+    } else {
+        buffer.fill(0);
+        Err(AuthenticationFailed)
+    }
+}
+
+/// Compute the probe of `header`, `hydro_secretbox_probe_create`.
+///
+/// A probe lets a receiver check that a message was created by a key holder before reading or
+/// decrypting the rest of it, e.g. as part of a challenge-response.
+#[must_use]
+pub fn probe_create(key: &[u8; KEY_SIZE], context: &Context, header: &Header) -> Probe {
+    let mut probe = [0u8; PROBE_SIZE];
+    let mut hasher = Hasher::new(context, Some(key));
+    hasher.update(&header[SIV_SIZE..]);
+    hasher.squeeze(&mut probe);
+    probe
+}
+
+/// Verify the probe of `header`, `hydro_secretbox_probe_verify`.
+pub fn probe_verify(
+    key: &[u8; KEY_SIZE],
+    context: &Context,
+    header: &Header,
+    probe: &Probe,
+) -> Result<(), AuthenticationFailed> {
+    if probe_create(key, context, header).ct_eq(probe).into() {
+        Ok(())
+    } else {
+        Err(AuthenticationFailed)
+    }
+}
+
+#[cfg(test)]
+mod tests;
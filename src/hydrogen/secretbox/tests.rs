@@ -0,0 +1,197 @@
+extern crate std;
+use super::*;
+use std::vec::Vec;
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+const CONTEXT: &Context = b"Examples";
+
+fn test_key() -> [u8; KEY_SIZE] {
+    core::array::from_fn(|i| i as u8)
+}
+
+fn test_iv() -> [u8; IV_SIZE] {
+    core::array::from_fn(|i| 0xa0 + i as u8)
+}
+
+fn test_message(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 7) as u8).collect()
+}
+
+struct TestVector {
+    msg_id: u64,
+    message_len: usize,
+    ciphertext: &'static str,
+    probe: &'static str,
+}
+
+// Generated with libhydrogen 0.9.5 from `hydro_secretbox_encrypt_iv` and
+// `hydro_secretbox_probe_create` with the key `00 01 .. 1f`, the IV `a0 a1 .. b3`, the context
+// "Examples" and the message `i * 7` for `i` in `0..message_len`.
+const VECTORS: &[TestVector] = &[
+    TestVector {
+        msg_id: 0,
+        message_len: 0,
+        ciphertext: "4bb542fc008fa3302df4e910ac8ddf9970aacf6cc4a6e40be4f883c007cb7ab8bb737eab",
+        probe: "53ba3c97e4fff0ef690b34d459d7974b",
+    },
+    TestVector {
+        msg_id: 1,
+        message_len: 1,
+        ciphertext: "c31cf9a40d1264a284120f276e02fa50a130a470aef5d2ec4526be8d16337739521d7ab1ee",
+        probe: "ba9a546e43fe0a9fd975204eec6af22d",
+    },
+    TestVector {
+        msg_id: 42,
+        message_len: 15,
+        ciphertext: "1c7853d30912a6a906fe7dac01a32f9fecebe9e628c9129acf9e53baa71290f4567b71e40c150d6105b60ba1b88d189bddab76",
+        probe: "c6cd9666b5aa0370650a37d49cf97c65",
+    },
+    TestVector {
+        msg_id: 0x0123_4567_89ab_cdef,
+        message_len: 16,
+        ciphertext: "a6be33c785c0a500c8429fb7bb5b8a77ebdc43525de46e49efd679a87f0d190e79f4edf4345a6844343dd90961173af4815f40ec",
+        probe: "06e6f93a3ae2ccbede4cfaa864c84d27",
+    },
+    TestVector {
+        msg_id: 7,
+        message_len: 17,
+        ciphertext: "f409a5ea1eb0a91f65a41047c3e2948c8f500f6b211a381f195f8beb5b8e9786c5c184877f0663eaf4814cbef22804759b10b7ee38",
+        probe: "80cee12ea96772c21a21acbebd8c8754",
+    },
+    TestVector {
+        msg_id: u64::MAX,
+        message_len: 32,
+        ciphertext: "b03911011d17d0e4c204bc5d1145fde86797b4db9fc6dac361d86e43ec71e802dfd61b0919e6ef53eb370e8f74c37f9f7ef20841c92116fe0670eae7145a167f85372809",
+        probe: "4d0f1440959dbeb45c1f6b9d9c3bd013",
+    },
+    TestVector {
+        msg_id: 1000,
+        message_len: 100,
+        ciphertext: "c013c963cc512f0c02689dfe2193418151df55c6fa9d8275d96b563edb921c9527a58931d393975faaf6cb0955e0013b07b8783486d36eefc3146119d1f9d9dedc0ae0b215d61b3a662c69cbb0fe74d4ab735324deeab08d9bef8ce187964a0136875e4141b5a6bfde98b4175d6af63c4e9344812624bbbe04d102fcbf49f2c7c4dcb40e5a6085b0",
+        probe: "544be5512f74c477ac154769d69ecb26",
+    },
+];
+
+#[test]
+fn test_libhydrogen_vectors() {
+    let key = test_key();
+
+    for vector in VECTORS {
+        let expected = hex_to_bytes(vector.ciphertext);
+        let (expected_header, expected_ciphertext) = expected.split_at(HEADER_SIZE);
+        let message = test_message(vector.message_len);
+
+        // Encrypt
+        let mut buffer = message.clone();
+        let header =
+            encrypt_in_place_with_iv(&key, CONTEXT, vector.msg_id, &test_iv(), &mut buffer);
+        assert_eq!(
+            &header[..],
+            expected_header,
+            "Length {}",
+            vector.message_len
+        );
+        assert_eq!(
+            &buffer, expected_ciphertext,
+            "Length {}",
+            vector.message_len
+        );
+
+        // Probe
+        let probe = probe_create(&key, CONTEXT, &header);
+        assert_eq!(&probe[..], &hex_to_bytes(vector.probe)[..]);
+        assert_eq!(probe_verify(&key, CONTEXT, &header, &probe), Ok(()));
+
+        // Decrypt
+        decrypt_in_place(&key, CONTEXT, vector.msg_id, &header, &mut buffer)
+            .expect("Decryption should succeed");
+        assert_eq!(buffer, message);
+    }
+}
+
+#[test]
+fn test_roundtrip_all_lengths() {
+    let key = [42u8; KEY_SIZE];
+
+    for len in 0..64 {
+        let message = test_message(len);
+        let mut buffer = message.clone();
+        let header = encrypt_in_place_with_iv(&key, CONTEXT, len as u64, &test_iv(), &mut buffer);
+
+        decrypt_in_place(&key, CONTEXT, len as u64, &header, &mut buffer)
+            .expect("Decryption should succeed");
+        assert_eq!(buffer, message);
+    }
+}
+
+#[test]
+fn test_authentication_failed() {
+    let key = test_key();
+    let message = *b"Hello, libhydrogen!";
+
+    let mut ciphertext = message;
+    let header = encrypt_in_place_with_iv(&key, CONTEXT, 1, &test_iv(), &mut ciphertext);
+
+    // Wrong message id
+    let mut buffer = ciphertext;
+    assert_eq!(
+        decrypt_in_place(&key, CONTEXT, 2, &header, &mut buffer),
+        Err(AuthenticationFailed)
+    );
+    assert_eq!(buffer, [0u8; 19], "Buffer should be zeroed on failure");
+
+    // Wrong context
+    let mut buffer = ciphertext;
+    assert_eq!(
+        decrypt_in_place(&key, b"Examplez", 1, &header, &mut buffer),
+        Err(AuthenticationFailed)
+    );
+
+    // Modified header
+    for i in 0..HEADER_SIZE {
+        let mut bad_header = header;
+        bad_header[i] ^= 1;
+        let mut buffer = ciphertext;
+        assert_eq!(
+            decrypt_in_place(&key, CONTEXT, 1, &bad_header, &mut buffer),
+            Err(AuthenticationFailed)
+        );
+    }
+
+    // Modified ciphertext
+    let mut buffer = ciphertext;
+    buffer[5] ^= 0x80;
+    assert_eq!(
+        decrypt_in_place(&key, CONTEXT, 1, &header, &mut buffer),
+        Err(AuthenticationFailed)
+    );
+}
+
+#[test]
+fn test_probe_failed() {
+    let key = test_key();
+    let mut buffer = *b"probe me";
+    let header = encrypt_in_place_with_iv(&key, CONTEXT, 0, &test_iv(), &mut buffer);
+    let probe = probe_create(&key, CONTEXT, &header);
+
+    let mut bad_probe = probe;
+    bad_probe[0] ^= 1;
+    assert_eq!(
+        probe_verify(&key, CONTEXT, &header, &bad_probe),
+        Err(AuthenticationFailed)
+    );
+    assert_eq!(
+        probe_verify(&[0u8; KEY_SIZE], CONTEXT, &header, &probe),
+        Err(AuthenticationFailed)
+    );
+    assert_eq!(
+        probe_verify(&key, b"Examplez", &header, &probe),
+        Err(AuthenticationFailed)
+    );
+}
@@ -0,0 +1,148 @@
+//! # Key wrapping
+//!
+//! Deterministic authenticated encryption of keys under a key-encryption key (KEK), for storing
+//! device keys in untrusted memory, with the intent of RFC 3394 AES key wrap. The construction is
+//! SIV (RFC 5297 style) from this crate's primitives:
+//!
+//! ```text
+//! siv        = TupleHash(kek, key) with customization "gimli-crypto key wrap"
+//! ciphertext = key ^ GimliCipher(kek, siv[..16])
+//! wrapped    = siv || ciphertext
+//! ```
+//!
+//! Keys are high-entropy and wrapped once, so no nonce is needed: the synthetic IV is derived
+//! from the key and doubles as a 256-bit integrity check. Unwrapping recomputes it and rejects
+//! any modified wrapped key. Wrapping the same key twice gives the same result, which reveals
+//! only that the keys are equal.
+//!
+//! Keys of 16, 32 and 64 bytes are supported, see [`Key`]. Unwrapped keys are returned as
+//! [`Zeroizing`] arrays that are cleared when dropped, and the hasher and cipher that see the KEK
+//! and key bytes are cleared when dropped as well.
+//!
+//! # Usage
+//!
+//! ```
+//! use gimli_crypto::key_wrap::{self, UnwrapError};
+//!
+//! let kek = [1u8; 32];
+//! let device_key = [2u8; 32];
+//!
+//! let wrapped = key_wrap::wrap(&kek, &device_key);
+//! assert_eq!(wrapped.len(), 32 + key_wrap::OVERHEAD);
+//!
+//! let unwrapped = key_wrap::unwrap::<[u8; 32]>(&kek, &wrapped).unwrap();
+//! assert_eq!(*unwrapped, device_key);
+//!
+//! let mut tampered = wrapped;
+//! tampered[40] ^= 1;
+//! assert_eq!(
+//!     key_wrap::unwrap::<[u8; 32]>(&kek, &tampered).err(),
+//!     Some(UnwrapError::IntegrityCheckFailed)
+//! );
+//! ```
+
+use crate::{GimliCipher, HASH_SIZE, KEY_SIZE, KeyIvInit, NONCE_SIZE, StreamCipher, TupleHasher};
+use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, Zeroizing};
+
+/// Bytes added to every key by wrapping, the synthetic IV.
+pub const OVERHEAD: usize = HASH_SIZE;
+
+/// Customization of the synthetic IV hash.
+const SIV_CUSTOMIZATION: &[u8] = b"gimli-crypto key wrap";
+
+/// Errors of [`unwrap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnwrapError {
+    /// The wrapped key is not `OVERHEAD` bytes longer than the key type.
+    InvalidLength,
+    /// The wrapped key was modified or wrapped under a different KEK.
+    IntegrityCheckFailed,
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Key types that can be wrapped, `[u8; 16]`, `[u8; 32]` and `[u8; 64]`.
+pub trait Key: private::Sealed + AsRef<[u8]> + AsMut<[u8]> + Zeroize + Sized {
+    /// The wrapped key, `siv || ciphertext`.
+    type Wrapped: AsRef<[u8]> + AsMut<[u8]>;
+
+    #[doc(hidden)]
+    fn zeroed() -> Self;
+
+    #[doc(hidden)]
+    fn zeroed_wrapped() -> Self::Wrapped;
+}
+
+macro_rules! impl_key {
+    ($($size:literal),*) => {
+        $(
+            impl private::Sealed for [u8; $size] {}
+
+            impl Key for [u8; $size] {
+                type Wrapped = [u8; $size + OVERHEAD];
+
+                fn zeroed() -> Self {
+                    [0; $size]
+                }
+
+                fn zeroed_wrapped() -> Self::Wrapped {
+                    [0; $size + OVERHEAD]
+                }
+            }
+        )*
+    };
+}
+
+impl_key!(16, 32, 64);
+
+/// Synthetic IV of `key` under `kek`.
+fn siv(kek: &[u8; KEY_SIZE], key: &[u8]) -> [u8; HASH_SIZE] {
+    let mut hasher = TupleHasher::new(SIV_CUSTOMIZATION);
+    hasher.push(kek);
+    hasher.push(key);
+    hasher.finalize()
+}
+
+/// XOR the keystream selected by `siv` into `data`.
+fn apply_keystream(kek: &[u8; KEY_SIZE], siv: &[u8; HASH_SIZE], data: &mut [u8]) {
+    let nonce: &[u8; NONCE_SIZE] = siv[..NONCE_SIZE].try_into().unwrap();
+    GimliCipher::new(kek.into(), nonce.into()).apply_keystream(data);
+}
+
+/// Wrap `key` under `kek`.
+pub fn wrap<K: Key>(kek: &[u8; KEY_SIZE], key: &K) -> K::Wrapped {
+    let key = key.as_ref();
+    let siv = siv(kek, key);
+
+    let mut wrapped = K::zeroed_wrapped();
+    let (siv_out, ciphertext) = wrapped.as_mut().split_at_mut(OVERHEAD);
+    siv_out.copy_from_slice(&siv);
+    ciphertext.copy_from_slice(key);
+    apply_keystream(kek, &siv, ciphertext);
+    wrapped
+}
+
+/// Unwrap a key wrapped under `kek` by [`wrap`], checking its integrity.
+pub fn unwrap<K: Key>(kek: &[u8; KEY_SIZE], wrapped: &[u8]) -> Result<Zeroizing<K>, UnwrapError> {
+    let mut key = Zeroizing::new(K::zeroed());
+    if wrapped.len() != OVERHEAD + key.as_ref().len() {
+        return Err(UnwrapError::InvalidLength);
+    }
+
+    let (siv, ciphertext) = wrapped.split_at(OVERHEAD);
+    let siv: &[u8; HASH_SIZE] = siv.try_into().unwrap();
+    key.as_mut().copy_from_slice(ciphertext);
+    apply_keystream(kek, siv, key.as_mut());
+
+    if bool::from(self::siv(kek, key.as_ref()).ct_eq(siv)) {
+        Ok(key)
+    } else {
+        Err(UnwrapError::IntegrityCheckFailed)
+    }
+}
+
+#[cfg(test)]
+mod tests;
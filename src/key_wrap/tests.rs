@@ -0,0 +1,91 @@
+extern crate std;
+use super::*;
+use std::vec::Vec;
+
+const KEK: [u8; KEY_SIZE] = [1; KEY_SIZE];
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_known_answer() {
+    let key: [u8; 16] = core::array::from_fn(|i| i as u8);
+    assert_eq!(
+        wrap(&KEK, &key).to_vec(),
+        hex_to_bytes(
+            "9e65b7c6a1a1311689154c76094b775eaec7996819504993fc45759cc0843c3f\
+             693363d2dc31709ea873b20f59cb1af9"
+        )
+    );
+}
+
+fn roundtrip<K: Key + Clone + PartialEq + core::fmt::Debug>(key: K) {
+    let wrapped = wrap(&KEK, &key);
+    let wrapped = wrapped.as_ref();
+    assert_eq!(wrapped.len(), key.as_ref().len() + OVERHEAD);
+    assert_ne!(&wrapped[OVERHEAD..], key.as_ref());
+    assert_eq!(*unwrap::<K>(&KEK, wrapped).unwrap(), key);
+
+    // Deterministic.
+    assert_eq!(wrap(&KEK, &key).as_ref(), wrapped);
+}
+
+#[test]
+fn test_roundtrip() {
+    roundtrip([7u8; 16]);
+    roundtrip([7u8; 32]);
+    roundtrip([7u8; 64]);
+}
+
+#[test]
+fn test_tampered() {
+    let wrapped = wrap(&KEK, &[7u8; 32]);
+
+    for position in 0..wrapped.len() {
+        let mut tampered = wrapped;
+        tampered[position] ^= 0x80;
+        assert_eq!(
+            unwrap::<[u8; 32]>(&KEK, &tampered).err(),
+            Some(UnwrapError::IntegrityCheckFailed),
+            "position {position}"
+        );
+    }
+
+    assert_eq!(
+        unwrap::<[u8; 32]>(&[2; KEY_SIZE], &wrapped).err(),
+        Some(UnwrapError::IntegrityCheckFailed)
+    );
+}
+
+#[test]
+fn test_distinct_keys_and_keks() {
+    let wrapped = wrap(&KEK, &[7u8; 32]);
+    assert_ne!(wrap(&KEK, &[8u8; 32]), wrapped);
+    assert_ne!(wrap(&[2; KEY_SIZE], &[7u8; 32]), wrapped);
+
+    // A prefix of a longer key does not wrap like the shorter key.
+    let mut long_key = [0u8; 32];
+    long_key[..16].copy_from_slice(&[7; 16]);
+    assert_ne!(wrap(&KEK, &long_key)[..48], wrap(&KEK, &[7u8; 16]));
+}
+
+#[test]
+fn test_invalid_length() {
+    let wrapped = wrap(&KEK, &[7u8; 32]);
+    assert_eq!(
+        unwrap::<[u8; 16]>(&KEK, &wrapped).err(),
+        Some(UnwrapError::InvalidLength)
+    );
+    assert_eq!(
+        unwrap::<[u8; 32]>(&KEK, &wrapped[..wrapped.len() - 1]).err(),
+        Some(UnwrapError::InvalidLength)
+    );
+    assert_eq!(
+        unwrap::<[u8; 32]>(&KEK, &[]).err(),
+        Some(UnwrapError::InvalidLength)
+    );
+}
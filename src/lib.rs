@@ -1,19 +1,49 @@
 #![no_std]
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
 mod aead_impl;
+mod build_hasher;
+mod duplex;
 mod gimli;
 mod hash_impl;
+mod strobe;
 
 mod rustcrypto_aead;
+mod rustcrypto_cipher;
 mod rustcrypto_hash;
 
+pub mod balloon;
+pub mod envelope;
+pub mod hydrogen;
+pub mod key_wrap;
+pub mod merkle;
+pub mod noise;
+pub mod sealed;
+pub mod sector;
+pub mod session;
+pub mod tree_hash;
+pub mod xmss;
+
 pub use aead_impl::{AuthenticationFailed, Tag, decrypt_in_place, encrypt_in_place};
-pub use hash_impl::{HASH_SIZE, Hasher, hash};
+pub use build_hasher::{GimliBuildHasher, GimliMapHasher, MAP_KEY_SIZE};
+pub use duplex::Duplex;
+pub use hash_impl::{HASH_SIZE, Hasher, InvalidState, TupleHasher, hash, tuple_hash};
 pub use rustcrypto_aead::GimliAead;
+pub use rustcrypto_cipher::{GimliCipher, GimliCipherCore};
 pub use rustcrypto_hash::GimliHash;
+pub use strobe::{STROBE_VERSION, Strobe};
+
+#[cfg(fuzzing)]
+#[doc(hidden)]
+pub use gimli::fuzzing;
 
 pub use aead::{self, AeadInPlace, KeyInit}; // For `GimliAead` users
+pub use cipher::{self, KeyIvInit, StreamCipher, StreamCipherSeek}; // For `GimliCipher` users
 pub use digest::{self, Digest, Update}; // For `GimpiHash` users
 
 /// Gimli state size in bytes (48 bytes = 12 u32 words).
@@ -0,0 +1,356 @@
+//! # Merkle trees
+//!
+//! Binary Merkle trees over `hash/gimli24v1` with the hashing and tree shape of RFC 6962
+//! (Certificate Transparency): a leaf is hashed as `H(0x00 || data)`, an inner node as
+//! `H(0x01 || left || right)` and a tree of `n > 1` leaves splits into a left subtree of the
+//! largest power of two below `n` leaves and a right subtree of the rest. The distinct prefixes
+//! keep a leaf from ever being passed off as an inner node. The root of the empty tree is
+//! `H("")`.
+//!
+//! [`Builder`] computes the root from a stream of leaves keeping one hash per tree level, so
+//! inputs larger than RAM, e.g. a firmware image read block by block, are never stored. Created
+//! with [`Builder::with_proof`] it also collects the inclusion [`Proof`] of one leaf in the same
+//! pass. Proofs are verified against the root in constant memory.
+//!
+//! # Usage
+//!
+//! ```
+//! use gimli_crypto::merkle::{Builder, Proof};
+//!
+//! let mut image = [0u8; 10 * 1024];
+//! image[4096..8192].fill(1);
+//!
+//! // On the update server, the root is signed and block 1 is sent with its proof.
+//! let mut builder: Builder = Builder::with_proof(1);
+//! for block in image.chunks(4096) {
+//!     builder.push_leaf(block).unwrap();
+//! }
+//! let (root, proof) = builder.finalize_with_proof().unwrap();
+//!
+//! let mut encoded = [0u8; Proof::<32>::MAX_SIZE];
+//! let len = proof.encode(&mut encoded).unwrap();
+//!
+//! // On the device, with the root from a verified signature.
+//! let proof: Proof = Proof::decode(&encoded[..len]).unwrap();
+//! assert!(proof.verify(&image[4096..8192], &root).is_ok());
+//! assert!(proof.verify(&image[..4096], &root).is_err());
+//! ```
+
+use crate::{HASH_SIZE, Hasher};
+use subtle::ConstantTimeEq;
+
+/// A leaf or node hash.
+pub type Hash = [u8; HASH_SIZE];
+
+/// Size of the index and size prefix of an encoded proof in bytes.
+const PROOF_HEADER_SIZE: usize = 16;
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// Errors of the Merkle tree types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The tree already has `2^DEPTH` leaves.
+    TreeFull,
+    /// The proof index is not a leaf of the tree, or no proof was requested.
+    IndexOutOfRange,
+    /// The proof does not show that the leaf is part of the tree with the root.
+    InvalidProof,
+    /// The encoded proof is malformed or longer than `DEPTH` hashes.
+    InvalidEncoding,
+    /// The output buffer is too small for the result.
+    BufferTooSmall,
+}
+
+/// Hash of the leaf `data`.
+pub fn leaf_hash(data: &[u8]) -> Hash {
+    let mut hasher = Hasher::new();
+    hasher.update(&[LEAF_PREFIX]);
+    hasher.update(data);
+    hasher.finalize()
+}
+
+/// Hash of the inner node with children `left` and `right`.
+pub fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Hasher::new();
+    hasher.update(&[NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize()
+}
+
+/// Root of the tree over `leaves`.
+pub fn root<'a>(leaves: impl IntoIterator<Item = &'a [u8]>) -> Hash {
+    let mut builder = Builder::<64>::new();
+    for leaf in leaves {
+        // 2^64 leaves cannot be counted, so the tree is never full.
+        builder.push_leaf(leaf).unwrap();
+    }
+    builder.finalize()
+}
+
+/// Streaming root and proof computation for trees of up to `2^DEPTH` leaves.
+///
+/// Memory is one hash per level, for the default `DEPTH` of 32 about 1 KiB and twice that with
+/// a proof.
+#[derive(Clone)]
+pub struct Builder<const DEPTH: usize = 32> {
+    /// Roots of the complete subtrees so far, one per set bit of `count` from the highest.
+    stack: [Hash; DEPTH],
+    count: u64,
+    /// Leaf to collect the proof for and its path so far.
+    proof: Option<Proof<DEPTH>>,
+}
+
+impl<const DEPTH: usize> Builder<DEPTH> {
+    /// Create a builder computing the root.
+    pub fn new() -> Self {
+        const { assert!(DEPTH > 0 && DEPTH <= 64, "the depth must be 1 to 64") };
+
+        Self {
+            stack: [[0; HASH_SIZE]; DEPTH],
+            count: 0,
+            proof: None,
+        }
+    }
+
+    /// Create a builder computing the root and the inclusion proof of leaf `index`.
+    pub fn with_proof(index: u64) -> Self {
+        Self {
+            proof: Some(Proof::empty(index)),
+            ..Self::new()
+        }
+    }
+
+    /// Number of leaves pushed.
+    pub fn leaf_count(&self) -> u64 {
+        self.count
+    }
+
+    /// Append the leaf `data`.
+    pub fn push_leaf(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.push_leaf_hash(leaf_hash(data))
+    }
+
+    /// Append a leaf given its [`leaf_hash`].
+    pub fn push_leaf_hash(&mut self, hash: Hash) -> Result<(), Error> {
+        if self
+            .count
+            .checked_shr(DEPTH as u32)
+            .is_some_and(|high| high != 0)
+        {
+            return Err(Error::TreeFull);
+        }
+
+        // Merge with the complete subtrees of the same height, one per trailing set bit.
+        let leaf = self.count;
+        let mut len = self.count.count_ones() as usize;
+        let mut node = hash;
+        for height in 0..leaf.trailing_ones() {
+            len -= 1;
+            let left = &self.stack[len];
+            if let Some(proof) = &mut self.proof {
+                // `node` holds the `2^height` leaves up to `leaf`, `left` the ones before.
+                if proof.index >> height == leaf >> height {
+                    proof.push(left);
+                } else if proof.index >> (height + 1) == leaf >> (height + 1) {
+                    proof.push(&node);
+                }
+            }
+            node = node_hash(left, &node);
+        }
+
+        self.stack[len] = node;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Root of the leaves so far.
+    pub fn root(&self) -> Hash {
+        self.fold(None)
+    }
+
+    /// Root of all leaves.
+    pub fn finalize(self) -> Hash {
+        self.root()
+    }
+
+    /// Root of all leaves and the inclusion proof of the leaf from
+    /// [`with_proof`](Self::with_proof).
+    pub fn finalize_with_proof(mut self) -> Result<(Hash, Proof<DEPTH>), Error> {
+        let mut proof = self.proof.take().ok_or(Error::IndexOutOfRange)?;
+        if proof.index >= self.count {
+            return Err(Error::IndexOutOfRange);
+        }
+        proof.size = self.count;
+        let root = self.fold(Some(&mut proof));
+        Ok((root, proof))
+    }
+
+    /// Merge the complete subtrees from the right, completing `proof` on the way.
+    fn fold(&self, mut proof: Option<&mut Proof<DEPTH>>) -> Hash {
+        let len = self.count.count_ones() as usize;
+        if len == 0 {
+            return crate::hash(&[]);
+        }
+
+        // First leaf of each subtree, the subtrees follow the set bits of `count`.
+        let mut starts = [0u64; DEPTH];
+        let mut start = 0;
+        let mut bits = self.count;
+        for first in starts.iter_mut().take(len) {
+            let size = 1u64 << (63 - bits.leading_zeros());
+            *first = start;
+            start += size;
+            bits -= size;
+        }
+
+        let mut root = self.stack[len - 1];
+        let mut contains_index = proof
+            .as_ref()
+            .is_some_and(|proof| proof.index >= starts[len - 1]);
+        for i in (0..len - 1).rev() {
+            if let Some(proof) = &mut proof {
+                if contains_index {
+                    proof.push(&self.stack[i]);
+                } else if proof.index >= starts[i] {
+                    proof.push(&root);
+                    contains_index = true;
+                }
+            }
+            root = node_hash(&self.stack[i], &root);
+        }
+        root
+    }
+}
+
+impl<const DEPTH: usize> Default for Builder<DEPTH> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Inclusion proof of a leaf, the sibling hashes on the path from the leaf to the root.
+///
+/// The root does not commit to the tree size, trees of different sizes can share a proof path.
+/// Authenticate the size together with the root, e.g. sign both, if the size matters.
+///
+/// Encoded as `index || size || path` with the index and tree size as little-endian 64-bit
+/// integers, the path length follows from the encoded length.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proof<const DEPTH: usize = 32> {
+    index: u64,
+    size: u64,
+    path: [Hash; DEPTH],
+    len: usize,
+}
+
+impl<const DEPTH: usize> Proof<DEPTH> {
+    /// Largest encoded size in bytes.
+    pub const MAX_SIZE: usize = PROOF_HEADER_SIZE + DEPTH * HASH_SIZE;
+
+    fn empty(index: u64) -> Self {
+        Self {
+            index,
+            size: 0,
+            path: [[0; HASH_SIZE]; DEPTH],
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, hash: &Hash) {
+        self.path[self.len] = *hash;
+        self.len += 1;
+    }
+
+    /// Index of the leaf.
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Number of leaves of the tree.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Sibling hashes from the leaf to the root.
+    pub fn path(&self) -> &[Hash] {
+        &self.path[..self.len]
+    }
+
+    /// Check that the leaf `data` is part of the tree with `root`.
+    pub fn verify(&self, data: &[u8], root: &Hash) -> Result<(), Error> {
+        self.verify_leaf_hash(&leaf_hash(data), root)
+    }
+
+    /// Check that the leaf with [`leaf_hash`] `hash` is part of the tree with `root`.
+    pub fn verify_leaf_hash(&self, hash: &Hash, root: &Hash) -> Result<(), Error> {
+        if self.index >= self.size {
+            return Err(Error::InvalidProof);
+        }
+
+        // RFC 9162 section 2.1.3.2.
+        let mut index = self.index;
+        let mut last = self.size - 1;
+        let mut node = *hash;
+        for sibling in self.path() {
+            if last == 0 {
+                return Err(Error::InvalidProof);
+            }
+            if index & 1 == 1 || index == last {
+                node = node_hash(sibling, &node);
+                while index & 1 == 0 && index != 0 {
+                    index >>= 1;
+                    last >>= 1;
+                }
+            } else {
+                node = node_hash(&node, sibling);
+            }
+            index >>= 1;
+            last >>= 1;
+        }
+
+        if last == 0 && bool::from(node.ct_eq(root)) {
+            Ok(())
+        } else {
+            Err(Error::InvalidProof)
+        }
+    }
+
+    /// Size of the encoding in bytes.
+    pub fn encoded_len(&self) -> usize {
+        PROOF_HEADER_SIZE + self.len * HASH_SIZE
+    }
+
+    /// Encode into `out`, returning the encoded length.
+    pub fn encode(&self, out: &mut [u8]) -> Result<usize, Error> {
+        let len = self.encoded_len();
+        let out = out.get_mut(..len).ok_or(Error::BufferTooSmall)?;
+        let (header, path) = out.split_at_mut(PROOF_HEADER_SIZE);
+        header[..8].copy_from_slice(&self.index.to_le_bytes());
+        header[8..].copy_from_slice(&self.size.to_le_bytes());
+        path.copy_from_slice(self.path().as_flattened());
+        Ok(len)
+    }
+
+    /// Decode from `bytes`.
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let (header, path) = bytes
+            .split_at_checked(PROOF_HEADER_SIZE)
+            .ok_or(Error::InvalidEncoding)?;
+        if !path.len().is_multiple_of(HASH_SIZE) || path.len() / HASH_SIZE > DEPTH {
+            return Err(Error::InvalidEncoding);
+        }
+
+        let mut proof = Self::empty(u64::from_le_bytes(header[..8].try_into().unwrap()));
+        proof.size = u64::from_le_bytes(header[8..].try_into().unwrap());
+        for hash in path.chunks_exact(HASH_SIZE) {
+            proof.push(hash.try_into().unwrap());
+        }
+        Ok(proof)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,242 @@
+extern crate std;
+use super::*;
+use std::vec::Vec;
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+// Under miri, only test small trees to keep test time reasonable.
+#[cfg(miri)]
+const MAX_LEAVES: usize = 6;
+#[cfg(not(miri))]
+const MAX_LEAVES: usize = 40;
+
+fn leaves(count: usize) -> Vec<Vec<u8>> {
+    (0..count).map(|i| std::vec![i as u8; i % 5]).collect()
+}
+
+/// `MTH` of RFC 6962 section 2.1.
+fn reference_root(leaves: &[Vec<u8>]) -> Hash {
+    match leaves.len() {
+        0 => crate::hash(&[]),
+        1 => leaf_hash(&leaves[0]),
+        n => {
+            let k = split(n);
+            node_hash(&reference_root(&leaves[..k]), &reference_root(&leaves[k..]))
+        }
+    }
+}
+
+/// `PATH` of RFC 6962 section 2.1.1.
+fn reference_path(index: usize, leaves: &[Vec<u8>]) -> Vec<Hash> {
+    if leaves.len() <= 1 {
+        return Vec::new();
+    }
+    let k = split(leaves.len());
+    let (mut path, sibling) = if index < k {
+        (
+            reference_path(index, &leaves[..k]),
+            reference_root(&leaves[k..]),
+        )
+    } else {
+        (
+            reference_path(index - k, &leaves[k..]),
+            reference_root(&leaves[..k]),
+        )
+    };
+    path.push(sibling);
+    path
+}
+
+/// Largest power of two below `n`.
+fn split(n: usize) -> usize {
+    1 << (usize::BITS - 1 - (n - 1).leading_zeros())
+}
+
+fn build(leaves: &[Vec<u8>], index: u64) -> (Hash, Proof) {
+    let mut builder = Builder::with_proof(index);
+    for leaf in leaves {
+        builder.push_leaf(leaf).unwrap();
+    }
+    builder.finalize_with_proof().unwrap()
+}
+
+#[test]
+fn test_known_roots() {
+    let cases = [
+        // The empty root is the hash of the empty string.
+        (
+            0,
+            "b0634b2c0b082aedc5c0a2fe4ee3adcfc989ec05de6f00addb04b3aaac271f67",
+        ),
+        (
+            1,
+            "d7e9228614a42f34f32442ec52b2062f1db859725f3909300e4c4f2ea6ef5be9",
+        ),
+        (
+            2,
+            "eeb9a13c70d3efc216820b704679bd64652ee7cb1dc3f6d0662aba24213b438a",
+        ),
+        (
+            7,
+            "d3e0a2924d16edab8ef3f4d723c8a265e33316c3a1172a8b5f1b85875387b09c",
+        ),
+    ];
+    for (count, expected) in cases {
+        let leaves = leaves(count);
+        assert_eq!(
+            root(leaves.iter().map(Vec::as_slice)).to_vec(),
+            hex_to_bytes(expected),
+            "{count} leaves"
+        );
+    }
+}
+
+#[test]
+fn test_root_matches_reference() {
+    for count in 0..=MAX_LEAVES {
+        let leaves = leaves(count);
+        let expected = reference_root(&leaves);
+
+        let mut builder: Builder = Builder::new();
+        for (i, leaf) in leaves.iter().enumerate() {
+            builder.push_leaf(leaf).unwrap();
+            assert_eq!(builder.root(), reference_root(&leaves[..=i]));
+        }
+        assert_eq!(builder.leaf_count(), count as u64);
+        assert_eq!(builder.finalize(), expected, "{count} leaves");
+        assert_eq!(root(leaves.iter().map(Vec::as_slice)), expected);
+    }
+}
+
+#[test]
+fn test_proofs_match_reference() {
+    for count in 1..=MAX_LEAVES {
+        let leaves = leaves(count);
+        let expected_root = reference_root(&leaves);
+
+        for index in 0..count {
+            let (root, proof) = build(&leaves, index as u64);
+            assert_eq!(root, expected_root);
+            assert_eq!(proof.index(), index as u64);
+            assert_eq!(proof.size(), count as u64);
+            assert_eq!(
+                proof.path(),
+                reference_path(index, &leaves),
+                "leaf {index} of {count}"
+            );
+
+            proof.verify(&leaves[index], &root).unwrap();
+            assert_eq!(proof.verify(b"other leaf", &root), Err(Error::InvalidProof));
+        }
+    }
+}
+
+#[test]
+fn test_tampered_proofs() {
+    let leaves = leaves(11);
+    let (root, proof) = build(&leaves, 6);
+    proof.verify(&leaves[6], &root).unwrap();
+
+    let mut encoded = [0u8; Proof::<32>::MAX_SIZE];
+    let len = proof.encode(&mut encoded).unwrap();
+    let encoded = &encoded[..len];
+    let tampered = |position: usize, value: u8| {
+        let mut tampered = encoded.to_vec();
+        tampered[position] = value;
+        Proof::<32>::decode(&tampered).unwrap()
+    };
+
+    // Other index, tree size, sibling hash, or a leaf hash passed off as a node.
+    for tampered in [
+        tampered(0, 5),
+        tampered(0, 7),
+        tampered(8, 6),
+        tampered(8, 7),
+        tampered(8, 8),
+        tampered(8, 17),
+        tampered(16, encoded[16] ^ 1),
+        tampered(len - 1, encoded[len - 1] ^ 1),
+    ] {
+        assert_eq!(tampered.verify(&leaves[6], &root), Err(Error::InvalidProof));
+    }
+    let truncated = Proof::<32>::decode(&encoded[..len - HASH_SIZE]).unwrap();
+    assert_eq!(
+        truncated.verify(&leaves[6], &root),
+        Err(Error::InvalidProof)
+    );
+    let extended = Proof::<32>::decode(&[encoded, &[0; HASH_SIZE]].concat()).unwrap();
+    assert_eq!(extended.verify(&leaves[6], &root), Err(Error::InvalidProof));
+    assert_eq!(
+        proof.verify_leaf_hash(
+            &node_hash(&leaf_hash(&leaves[6]), &leaf_hash(&leaves[7])),
+            &root
+        ),
+        Err(Error::InvalidProof)
+    );
+}
+
+#[test]
+fn test_proof_encoding() {
+    let leaves = leaves(5);
+    let (_, proof) = build(&leaves, 4);
+    assert_eq!(proof.encoded_len(), 16 + 32);
+
+    let mut encoded = [0u8; Proof::<32>::MAX_SIZE];
+    assert_eq!(proof.encode(&mut encoded), Ok(48));
+    assert_eq!(
+        &encoded[..16],
+        &[4, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 0, 0, 0, 0]
+    );
+    assert_eq!(Proof::decode(&encoded[..48]), Ok(proof.clone()));
+    assert_eq!(proof.encode(&mut encoded[..47]), Err(Error::BufferTooSmall));
+
+    assert_eq!(
+        Proof::<32>::decode(&encoded[..15]),
+        Err(Error::InvalidEncoding)
+    );
+    assert_eq!(
+        Proof::<32>::decode(&encoded[..47]),
+        Err(Error::InvalidEncoding)
+    );
+
+    // At most `DEPTH` hashes.
+    let long = [0u8; 16 + 3 * HASH_SIZE];
+    assert!(Proof::<3>::decode(&long).is_ok());
+    assert_eq!(Proof::<2>::decode(&long), Err(Error::InvalidEncoding));
+}
+
+#[test]
+fn test_depth_limit() {
+    let mut builder = Builder::<2>::with_proof(3);
+    for i in 0..4u8 {
+        builder.push_leaf(&[i]).unwrap();
+    }
+    assert_eq!(builder.push_leaf(b"full"), Err(Error::TreeFull));
+
+    let (root, proof) = builder.finalize_with_proof().unwrap();
+    assert_eq!(proof.path().len(), 2);
+    proof.verify(&[3], &root).unwrap();
+}
+
+#[test]
+fn test_index_out_of_range() {
+    let mut builder: Builder = Builder::with_proof(2);
+    builder.push_leaf(b"a").unwrap();
+    builder.push_leaf(b"b").unwrap();
+    assert_eq!(
+        builder.clone().finalize_with_proof(),
+        Err(Error::IndexOutOfRange)
+    );
+
+    let mut builder: Builder = Builder::new();
+    builder.push_leaf(b"a").unwrap();
+    assert_eq!(builder.finalize_with_proof(), Err(Error::IndexOutOfRange));
+
+    let builder: Builder = Builder::with_proof(0);
+    assert_eq!(builder.finalize_with_proof(), Err(Error::IndexOutOfRange));
+}
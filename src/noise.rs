@@ -0,0 +1,215 @@
+//! # Noise protocol framework backend
+//!
+//! [Noise](https://noiseprotocol.org/noise.html) cipher and hash functions on top of
+//! `aead/gimli24v1` and `hash/gimli24v1`, with the `CipherState`, `SymmetricState` and
+//! `HandshakeState` objects of the specification. The Diffie-Hellman function is supplied by the
+//! caller through the [`Dh`] trait, so protocols are named e.g.
+//! `Noise_NN_25519_GimliAEAD_GimliHash`.
+//!
+//! The functions are defined as follows:
+//!
+//! - `GimliAEAD`: `aead/gimli24v1` with a 16-byte nonce of 8 zero bytes followed by the
+//!   little-endian encoding of `n`, analogous to `ChaChaPoly`. The tag is 16 bytes.
+//! - `GimliHash`: `hash/gimli24v1` with `HASHLEN = 32` and `BLOCKLEN = 64`. The sponge has no
+//!   natural block length for HMAC; 64 matches SHA-256 and BLAKE2s.
+//!
+//! Messages are written to and read from caller-provided buffers, nothing allocates.
+//!
+//! # Usage
+//!
+//! ```
+//! use gimli_crypto::noise::{Dh, HandshakePattern, HandshakeState};
+//! # use gimli_crypto::noise::Error;
+//! # // Insecure stand-in for X25519 so the example has no dependencies.
+//! # struct ToyDh(u8);
+//! # impl Dh for ToyDh {
+//! #     const NAME: &'static str = "25519";
+//! #     const DH_LEN: usize = 32;
+//! #     type PrivateKey = [u8; 32];
+//! #     type PublicKey = [u8; 32];
+//! #     type SharedSecret = [u8; 32];
+//! #     fn generate_keypair(&mut self) -> ([u8; 32], [u8; 32]) {
+//! #         self.0 += 1;
+//! #         let private = [self.0; 32];
+//! #         (private, self.public_key(&private))
+//! #     }
+//! #     fn public_key(&self, private: &[u8; 32]) -> [u8; 32] {
+//! #         private.map(|b| b.wrapping_mul(3))
+//! #     }
+//! #     fn public_key_from_bytes(&self, bytes: &[u8]) -> Option<[u8; 32]> {
+//! #         bytes.try_into().ok()
+//! #     }
+//! #     fn dh(&self, private: &[u8; 32], public: &[u8; 32]) -> Option<[u8; 32]> {
+//! #         Some(core::array::from_fn(|i| private[i].wrapping_mul(public[i])))
+//! #     }
+//! # }
+//! # fn main() -> Result<(), Error> {
+//! # let (initiator_dh, responder_dh) = (ToyDh(0), ToyDh(100));
+//! let mut initiator =
+//!     HandshakeState::new(initiator_dh, HandshakePattern::NN, true, b"prologue", None, None)?;
+//! let mut responder =
+//!     HandshakeState::new(responder_dh, HandshakePattern::NN, false, b"prologue", None, None)?;
+//!
+//! let (mut message, mut payload) = ([0u8; 128], [0u8; 128]);
+//!
+//! // -> e
+//! let len = initiator.write_message(b"", &mut message)?;
+//! responder.read_message(&message[..len], &mut payload)?;
+//!
+//! // <- e, ee
+//! let len = responder.write_message(b"", &mut message)?;
+//! initiator.read_message(&message[..len], &mut payload)?;
+//!
+//! let mut initiator = initiator.into_transport()?;
+//! let mut responder = responder.into_transport()?;
+//!
+//! let len = initiator.write_message(b"Hello", &mut message)?;
+//! let len = responder.read_message(&message[..len], &mut payload)?;
+//! assert_eq!(&payload[..len], b"Hello");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::Hasher;
+
+mod cipher_state;
+mod handshake_state;
+mod symmetric_state;
+
+pub use cipher_state::CipherState;
+pub use handshake_state::{HandshakePattern, HandshakeState, TransportState};
+pub use symmetric_state::SymmetricState;
+
+/// Hash output size in bytes, `HASHLEN`.
+pub const HASH_LEN: usize = 32;
+
+/// Hash block size in bytes used by HMAC, `BLOCKLEN`.
+pub const BLOCK_LEN: usize = 64;
+
+/// Cipher key size in bytes.
+pub const KEY_LEN: usize = 32;
+
+/// Authentication tag size in bytes added to every encrypted payload.
+pub const TAG_LEN: usize = crate::TAG_SIZE;
+
+/// Maximum size of a Noise message in bytes.
+pub const MAX_MESSAGE_LEN: usize = 65535;
+
+/// Cipher name used in protocol names.
+pub const CIPHER_NAME: &str = "GimliAEAD";
+
+/// Hash name used in protocol names.
+pub const HASH_NAME: &str = "GimliHash";
+
+/// Errors of the Noise objects.
+///
+/// After an error during the handshake the [`HandshakeState`] must be discarded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// Decryption failed, the message was not produced by the peer or was modified.
+    Decrypt,
+    /// The nonce counter reached its maximum, rekey or start a new session.
+    NonceExhausted,
+    /// The output buffer is too small for the result.
+    BufferTooSmall,
+    /// The message is too short or too long.
+    InvalidMessage,
+    /// The Diffie-Hellman function rejected a public key.
+    Dh,
+    /// A static key required by the pattern was not provided.
+    MissingKey,
+    /// The operation does not match the state of the handshake, e.g. writing when a message from
+    /// the peer is expected or finishing an incomplete handshake.
+    WrongState,
+}
+
+/// Diffie-Hellman function used by the handshake, e.g. X25519.
+///
+/// Implementations generate ephemeral key pairs themselves, typically from an RNG they own.
+pub trait Dh {
+    /// Name used in protocol names, e.g. `"25519"`.
+    const NAME: &'static str;
+
+    /// Public key size in bytes, `DHLEN`.
+    const DH_LEN: usize;
+
+    /// Private key.
+    type PrivateKey;
+
+    /// Public key, `DH_LEN` bytes.
+    type PublicKey: AsRef<[u8]>;
+
+    /// Shared secret, `DH_LEN` bytes.
+    type SharedSecret: AsRef<[u8]>;
+
+    /// Generate a new key pair, `GENERATE_KEYPAIR()`.
+    fn generate_keypair(&mut self) -> (Self::PrivateKey, Self::PublicKey);
+
+    /// Compute the public key of `private`.
+    fn public_key(&self, private: &Self::PrivateKey) -> Self::PublicKey;
+
+    /// Parse a public key of `DH_LEN` bytes received from the peer.
+    fn public_key_from_bytes(&self, bytes: &[u8]) -> Option<Self::PublicKey>;
+
+    /// Compute the shared secret, `DH(key_pair, public_key)`, or `None` for an invalid public key.
+    fn dh(
+        &self,
+        private: &Self::PrivateKey,
+        public: &Self::PublicKey,
+    ) -> Option<Self::SharedSecret>;
+}
+
+/// `HASH(data)` of the concatenation of `data`.
+fn hash(data: &[&[u8]]) -> [u8; HASH_LEN] {
+    let mut hasher = Hasher::new();
+    for part in data {
+        hasher.update(part);
+    }
+    hasher.finalize()
+}
+
+/// `HMAC-HASH(key, data)` of the concatenation of `data`, as in RFC 2104.
+pub fn hmac(key: &[u8], data: &[&[u8]]) -> [u8; HASH_LEN] {
+    let mut block = [0u8; BLOCK_LEN];
+    if key.len() > BLOCK_LEN {
+        block[..HASH_LEN].copy_from_slice(&hash(&[key]));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Hasher::new();
+    inner.update(&block.map(|b| b ^ 0x36));
+    for part in data {
+        inner.update(part);
+    }
+    let inner = inner.finalize();
+
+    let mut outer = Hasher::new();
+    outer.update(&block.map(|b| b ^ 0x5c));
+    outer.update(&inner);
+    outer.finalize()
+}
+
+/// `HKDF(chaining_key, input_key_material, N)` of the specification.
+///
+/// Noise uses `N = 2` or `N = 3`.
+pub fn hkdf<const N: usize>(
+    chaining_key: &[u8; HASH_LEN],
+    input_key_material: &[u8],
+) -> [[u8; HASH_LEN]; N] {
+    let temp_key = hmac(chaining_key, &[input_key_material]);
+
+    let mut outputs = [[0u8; HASH_LEN]; N];
+    for i in 0..N {
+        let counter = [i as u8 + 1];
+        outputs[i] = if i == 0 {
+            hmac(&temp_key, &[&counter])
+        } else {
+            hmac(&temp_key, &[&outputs[i - 1], &counter])
+        };
+    }
+    outputs
+}
+
+#[cfg(test)]
+mod tests;
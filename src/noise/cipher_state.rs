@@ -0,0 +1,127 @@
+use super::{Error, KEY_LEN, TAG_LEN};
+use crate::{NONCE_SIZE, decrypt_in_place, encrypt_in_place};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Cipher key and nonce counter, `CipherState`.
+///
+/// Without a key, encryption and decryption copy the data unchanged as the specification
+/// requires for the first handshake messages.
+#[derive(Clone, Default, Zeroize, ZeroizeOnDrop)]
+pub struct CipherState {
+    key: Option<[u8; KEY_LEN]>,
+    nonce: u64,
+}
+
+impl CipherState {
+    /// Create a cipher state with `key` and a nonce of zero, `InitializeKey(key)`.
+    pub fn new(key: [u8; KEY_LEN]) -> Self {
+        Self {
+            key: Some(key),
+            nonce: 0,
+        }
+    }
+
+    /// Whether a key is set, `HasKey()`.
+    pub fn has_key(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// Current nonce counter.
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    /// Set the nonce counter, `SetNonce(nonce)`, e.g. for out-of-order transports.
+    pub fn set_nonce(&mut self, nonce: u64) {
+        self.nonce = nonce;
+    }
+
+    /// Encrypt `plaintext` into `out` as `ciphertext || tag`, `EncryptWithAd(ad, plaintext)`.
+    ///
+    /// Returns the number of bytes written.
+    pub fn encrypt_with_ad(
+        &mut self,
+        ad: &[u8],
+        plaintext: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, Error> {
+        let Some(key) = &self.key else {
+            let out = out
+                .get_mut(..plaintext.len())
+                .ok_or(Error::BufferTooSmall)?;
+            out.copy_from_slice(plaintext);
+            return Ok(plaintext.len());
+        };
+
+        // The maximum nonce is reserved for `rekey`.
+        if self.nonce == u64::MAX {
+            return Err(Error::NonceExhausted);
+        }
+        let len = plaintext.len() + TAG_LEN;
+        let out = out.get_mut(..len).ok_or(Error::BufferTooSmall)?;
+
+        let (ciphertext, tag) = out.split_at_mut(plaintext.len());
+        ciphertext.copy_from_slice(plaintext);
+        tag.copy_from_slice(&encrypt_in_place(key, &nonce(self.nonce), ad, ciphertext));
+
+        self.nonce += 1;
+        Ok(len)
+    }
+
+    /// Decrypt `ciphertext || tag` into `out`, `DecryptWithAd(ad, ciphertext)`.
+    ///
+    /// Returns the number of bytes written. On failure the nonce is not incremented and `out` is
+    /// zeroed.
+    pub fn decrypt_with_ad(
+        &mut self,
+        ad: &[u8],
+        ciphertext: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, Error> {
+        let Some(key) = &self.key else {
+            let out = out
+                .get_mut(..ciphertext.len())
+                .ok_or(Error::BufferTooSmall)?;
+            out.copy_from_slice(ciphertext);
+            return Ok(ciphertext.len());
+        };
+
+        if self.nonce == u64::MAX {
+            return Err(Error::NonceExhausted);
+        }
+        let len = ciphertext
+            .len()
+            .checked_sub(TAG_LEN)
+            .ok_or(Error::Decrypt)?;
+        let (ciphertext, tag) = ciphertext.split_at(len);
+        let out = out.get_mut(..len).ok_or(Error::BufferTooSmall)?;
+
+        out.copy_from_slice(ciphertext);
+        if decrypt_in_place(key, &nonce(self.nonce), ad, out, tag.try_into().unwrap()).is_err() {
+            out.fill(0);
+            return Err(Error::Decrypt);
+        }
+
+        self.nonce += 1;
+        Ok(len)
+    }
+
+    /// Replace the key with one derived from it, `Rekey()`.
+    ///
+    /// The nonce counter is left unchanged.
+    pub fn rekey(&mut self) {
+        if let Some(key) = &mut self.key {
+            let mut new_key = [0u8; KEY_LEN];
+            let _ = encrypt_in_place(key, &nonce(u64::MAX), &[], &mut new_key);
+            *key = new_key;
+            new_key.zeroize();
+        }
+    }
+}
+
+/// The 16-byte nonce for counter `n`: 8 zero bytes followed by `n` in little-endian.
+fn nonce(n: u64) -> [u8; NONCE_SIZE] {
+    let mut nonce = [0u8; NONCE_SIZE];
+    nonce[8..].copy_from_slice(&n.to_le_bytes());
+    nonce
+}
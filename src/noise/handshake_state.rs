@@ -0,0 +1,387 @@
+use super::{CipherState, Dh, Error, HASH_LEN, MAX_MESSAGE_LEN, SymmetricState, TAG_LEN};
+
+/// Largest supported `DHLEN`, enough for X448.
+const MAX_DH_LEN: usize = 64;
+
+/// Message pattern tokens of the specification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    E,
+    S,
+    EE,
+    ES,
+    SE,
+    SS,
+}
+
+use Token::{E, EE, ES, S, SE, SS};
+
+/// Supported handshake patterns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandshakePattern {
+    /// No static keys.
+    ///
+    /// ```text
+    /// -> e
+    /// <- e, ee
+    /// ```
+    NN,
+    /// The initiator knows the responder's static key in advance.
+    ///
+    /// ```text
+    /// <- s
+    /// ...
+    /// -> e, es
+    /// <- e, ee
+    /// ```
+    NK,
+    /// Both static keys are transmitted, mutual authentication.
+    ///
+    /// ```text
+    /// -> e
+    /// <- e, ee, s, es
+    /// -> s, se
+    /// ```
+    XX,
+    /// The initiator knows the responder's static key and sends its own immediately.
+    ///
+    /// ```text
+    /// <- s
+    /// ...
+    /// -> e, es, s, ss
+    /// <- e, ee, se
+    /// ```
+    IK,
+}
+
+impl HandshakePattern {
+    /// Name used in protocol names.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::NN => "NN",
+            Self::NK => "NK",
+            Self::XX => "XX",
+            Self::IK => "IK",
+        }
+    }
+
+    /// Pre-message tokens of the initiator and the responder.
+    fn pre_messages(self) -> (&'static [Token], &'static [Token]) {
+        match self {
+            Self::NN | Self::XX => (&[], &[]),
+            Self::NK | Self::IK => (&[], &[S]),
+        }
+    }
+
+    /// Message tokens, alternating between initiator and responder starting with the initiator.
+    fn messages(self) -> &'static [&'static [Token]] {
+        match self {
+            Self::NN => &[&[E], &[E, EE]],
+            Self::NK => &[&[E, ES], &[E, EE]],
+            Self::XX => &[&[E], &[E, EE, S, ES], &[S, SE]],
+            Self::IK => &[&[E, ES, S, SS], &[E, EE, SE]],
+        }
+    }
+
+    /// Whether the party needs its own static key, and the peer's before the handshake.
+    fn required_keys(self, initiator: bool) -> (bool, bool) {
+        let (initiator_pre, responder_pre) = self.pre_messages();
+        let (local_pre, remote_pre) = if initiator {
+            (initiator_pre, responder_pre)
+        } else {
+            (responder_pre, initiator_pre)
+        };
+
+        let sends_static = self
+            .messages()
+            .iter()
+            .skip(if initiator { 0 } else { 1 })
+            .step_by(2)
+            .any(|tokens| tokens.contains(&S));
+
+        (
+            local_pre.contains(&S) || sends_static,
+            remote_pre.contains(&S),
+        )
+    }
+}
+
+/// State of a handshake in progress, `HandshakeState`.
+///
+/// The private keys are dropped with this object, use a `D::PrivateKey` that zeroizes itself.
+pub struct HandshakeState<D: Dh> {
+    dh: D,
+    symmetric: SymmetricState,
+    s: Option<(D::PrivateKey, D::PublicKey)>,
+    e: Option<(D::PrivateKey, D::PublicKey)>,
+    rs: Option<D::PublicKey>,
+    re: Option<D::PublicKey>,
+    initiator: bool,
+    pattern: HandshakePattern,
+    message_index: usize,
+}
+
+impl<D: Dh> HandshakeState<D> {
+    /// Start a handshake, `Initialize(...)`.
+    ///
+    /// `s` is the local static private key and `rs` the remote static public key, required if
+    /// the pattern uses them. The prologue must be the same on both sides.
+    pub fn new(
+        dh: D,
+        pattern: HandshakePattern,
+        initiator: bool,
+        prologue: &[u8],
+        s: Option<D::PrivateKey>,
+        rs: Option<D::PublicKey>,
+    ) -> Result<Self, Error> {
+        const { assert!(D::DH_LEN <= MAX_DH_LEN) };
+
+        let (needs_s, needs_rs) = pattern.required_keys(initiator);
+        if (needs_s && s.is_none()) || (needs_rs && rs.is_none()) {
+            return Err(Error::MissingKey);
+        }
+
+        let mut symmetric = SymmetricState::for_protocol(pattern.name(), D::NAME);
+        symmetric.mix_hash(prologue);
+
+        let s = s.map(|private| {
+            let public = dh.public_key(&private);
+            (private, public)
+        });
+
+        let (initiator_pre, responder_pre) = pattern.pre_messages();
+        for (tokens, local) in [(initiator_pre, initiator), (responder_pre, !initiator)] {
+            if tokens.contains(&S) {
+                let public = if local {
+                    s.as_ref().map(|(_, public)| public)
+                } else {
+                    rs.as_ref()
+                };
+                symmetric.mix_hash(public.ok_or(Error::MissingKey)?.as_ref());
+            }
+        }
+
+        Ok(Self {
+            dh,
+            symmetric,
+            s,
+            e: None,
+            rs,
+            re: None,
+            initiator,
+            pattern,
+            message_index: 0,
+        })
+    }
+
+    /// Whether the next message is written by this party.
+    pub fn is_my_turn(&self) -> bool {
+        self.message_index.is_multiple_of(2) == self.initiator
+    }
+
+    /// Whether all handshake messages have been processed.
+    pub fn is_finished(&self) -> bool {
+        self.message_index == self.pattern.messages().len()
+    }
+
+    /// Current handshake hash, e.g. for channel binding once the handshake is finished.
+    pub fn handshake_hash(&self) -> &[u8; HASH_LEN] {
+        self.symmetric.handshake_hash()
+    }
+
+    /// Static public key of the peer, if known.
+    pub fn remote_static(&self) -> Option<&D::PublicKey> {
+        self.rs.as_ref()
+    }
+
+    /// Write the next handshake message with `payload` into `out`, `WriteMessage`.
+    ///
+    /// Returns the number of bytes written. A message longer than [`MAX_MESSAGE_LEN`] is
+    /// rejected with [`Error::InvalidMessage`] before the state changes.
+    pub fn write_message(&mut self, payload: &[u8], out: &mut [u8]) -> Result<usize, Error> {
+        if self.is_finished() || !self.is_my_turn() {
+            return Err(Error::WrongState);
+        }
+        if self.message_len(payload.len()) > MAX_MESSAGE_LEN {
+            return Err(Error::InvalidMessage);
+        }
+
+        let mut offset = 0;
+        for token in self.pattern.messages()[self.message_index] {
+            match token {
+                E => {
+                    let (private, public) = self.dh.generate_keypair();
+                    let public_bytes = public.as_ref();
+                    out.get_mut(offset..offset + D::DH_LEN)
+                        .ok_or(Error::BufferTooSmall)?
+                        .copy_from_slice(public_bytes);
+                    self.symmetric.mix_hash(public_bytes);
+                    offset += D::DH_LEN;
+                    self.e = Some((private, public));
+                }
+                S => {
+                    let (_, public) = self.s.as_ref().ok_or(Error::MissingKey)?;
+                    let out = out.get_mut(offset..).ok_or(Error::BufferTooSmall)?;
+                    offset += self.symmetric.encrypt_and_hash(public.as_ref(), out)?;
+                }
+                _ => self.mix_dh(*token)?,
+            }
+        }
+
+        let out = out.get_mut(offset..).ok_or(Error::BufferTooSmall)?;
+        offset += self.symmetric.encrypt_and_hash(payload, out)?;
+
+        self.message_index += 1;
+        Ok(offset)
+    }
+
+    /// Read the next handshake message and write its payload into `out`, `ReadMessage`.
+    ///
+    /// Returns the payload length.
+    pub fn read_message(&mut self, message: &[u8], out: &mut [u8]) -> Result<usize, Error> {
+        if self.is_finished() || self.is_my_turn() {
+            return Err(Error::WrongState);
+        }
+        if message.len() > MAX_MESSAGE_LEN {
+            return Err(Error::InvalidMessage);
+        }
+
+        let mut message = message;
+        for token in self.pattern.messages()[self.message_index] {
+            match token {
+                E => {
+                    let (public, rest) = message
+                        .split_at_checked(D::DH_LEN)
+                        .ok_or(Error::InvalidMessage)?;
+                    self.re = Some(self.dh.public_key_from_bytes(public).ok_or(Error::Dh)?);
+                    self.symmetric.mix_hash(public);
+                    message = rest;
+                }
+                S => {
+                    let len = D::DH_LEN + if self.symmetric.has_key() { TAG_LEN } else { 0 };
+                    let (ciphertext, rest) =
+                        message.split_at_checked(len).ok_or(Error::InvalidMessage)?;
+                    let mut public = [0u8; MAX_DH_LEN];
+                    let public_len = self.symmetric.decrypt_and_hash(ciphertext, &mut public)?;
+                    let public = self
+                        .dh
+                        .public_key_from_bytes(&public[..public_len])
+                        .ok_or(Error::Dh)?;
+                    self.rs = Some(public);
+                    message = rest;
+                }
+                _ => self.mix_dh(*token)?,
+            }
+        }
+
+        let len = self.symmetric.decrypt_and_hash(message, out)?;
+        self.message_index += 1;
+        Ok(len)
+    }
+
+    /// Finish the handshake and derive the transport keys, `Split()`.
+    pub fn into_transport(self) -> Result<TransportState, Error> {
+        if !self.is_finished() {
+            return Err(Error::WrongState);
+        }
+
+        let (initiator_to_responder, responder_to_initiator) = self.symmetric.split();
+        let (send, receive) = if self.initiator {
+            (initiator_to_responder, responder_to_initiator)
+        } else {
+            (responder_to_initiator, initiator_to_responder)
+        };
+
+        Ok(TransportState {
+            send,
+            receive,
+            handshake_hash: *self.symmetric.handshake_hash(),
+        })
+    }
+
+    /// Length of the next message with a payload of `payload_len` bytes.
+    ///
+    /// Computed before writing, so that an oversized message leaves the state untouched.
+    fn message_len(&self, payload_len: usize) -> usize {
+        let mut has_key = self.symmetric.has_key();
+        let tag_len = |has_key| if has_key { TAG_LEN } else { 0 };
+
+        let mut len = 0;
+        for token in self.pattern.messages()[self.message_index] {
+            match token {
+                E => len += D::DH_LEN,
+                S => len += D::DH_LEN + tag_len(has_key),
+                _ => has_key = true,
+            }
+        }
+        len.saturating_add(payload_len)
+            .saturating_add(tag_len(has_key))
+    }
+
+    /// Process a DH token, `MixKey(DH(...))`.
+    fn mix_dh(&mut self, token: Token) -> Result<(), Error> {
+        let (local, remote) = match (token, self.initiator) {
+            (EE, _) => (&self.e, &self.re),
+            (ES, true) | (SE, false) => (&self.e, &self.rs),
+            (ES, false) | (SE, true) => (&self.s, &self.re),
+            (SS, _) => (&self.s, &self.rs),
+            (E | S, _) => unreachable!("not a DH token"),
+        };
+        let (private, _) = local.as_ref().ok_or(Error::MissingKey)?;
+        let public = remote.as_ref().ok_or(Error::MissingKey)?;
+
+        let shared_secret = self.dh.dh(private, public).ok_or(Error::Dh)?;
+        self.symmetric.mix_key(shared_secret.as_ref());
+        Ok(())
+    }
+}
+
+/// Cipher states after a completed handshake.
+pub struct TransportState {
+    send: CipherState,
+    receive: CipherState,
+    handshake_hash: [u8; HASH_LEN],
+}
+
+impl TransportState {
+    /// Encrypt `payload` into `out`, returning the message length.
+    pub fn write_message(&mut self, payload: &[u8], out: &mut [u8]) -> Result<usize, Error> {
+        if payload.len() + TAG_LEN > MAX_MESSAGE_LEN {
+            return Err(Error::InvalidMessage);
+        }
+        self.send.encrypt_with_ad(&[], payload, out)
+    }
+
+    /// Decrypt `message` into `out`, returning the payload length.
+    pub fn read_message(&mut self, message: &[u8], out: &mut [u8]) -> Result<usize, Error> {
+        if message.len() > MAX_MESSAGE_LEN {
+            return Err(Error::InvalidMessage);
+        }
+        self.receive.decrypt_with_ad(&[], message, out)
+    }
+
+    /// Handshake hash of the completed handshake, e.g. for channel binding.
+    pub fn handshake_hash(&self) -> &[u8; HASH_LEN] {
+        &self.handshake_hash
+    }
+
+    /// Rekey the outgoing cipher, the peer must call [`rekey_incoming`](Self::rekey_incoming).
+    pub fn rekey_outgoing(&mut self) {
+        self.send.rekey();
+    }
+
+    /// Rekey the incoming cipher.
+    pub fn rekey_incoming(&mut self) {
+        self.receive.rekey();
+    }
+
+    /// Outgoing cipher state, e.g. to read or set the nonce.
+    pub fn sender(&mut self) -> &mut CipherState {
+        &mut self.send
+    }
+
+    /// Incoming cipher state, e.g. to set the nonce for out-of-order transports.
+    pub fn receiver(&mut self) -> &mut CipherState {
+        &mut self.receive
+    }
+}
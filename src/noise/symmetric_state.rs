@@ -0,0 +1,103 @@
+use super::{CIPHER_NAME, CipherState, Error, HASH_LEN, HASH_NAME, hash, hkdf};
+use crate::Hasher;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Chaining key, handshake hash and cipher state, `SymmetricState`.
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct SymmetricState {
+    chaining_key: [u8; HASH_LEN],
+    handshake_hash: [u8; HASH_LEN],
+    cipher: CipherState,
+}
+
+impl SymmetricState {
+    /// Initialize from the protocol name, `InitializeSymmetric(protocol_name)`.
+    ///
+    /// The name is the concatenation of `protocol_name`, e.g.
+    /// `["Noise_NN_25519_", "GimliAEAD", "_", "GimliHash"]`.
+    pub fn new(protocol_name: &[&str]) -> Self {
+        let len: usize = protocol_name.iter().map(|part| part.len()).sum();
+
+        let mut handshake_hash = [0u8; HASH_LEN];
+        if len <= HASH_LEN {
+            let mut offset = 0;
+            for part in protocol_name {
+                handshake_hash[offset..offset + part.len()].copy_from_slice(part.as_bytes());
+                offset += part.len();
+            }
+        } else {
+            let mut hasher = Hasher::new();
+            for part in protocol_name {
+                hasher.update(part.as_bytes());
+            }
+            handshake_hash = hasher.finalize();
+        }
+
+        Self {
+            chaining_key: handshake_hash,
+            handshake_hash,
+            cipher: CipherState::default(),
+        }
+    }
+
+    /// Initialize for `Noise_<pattern>_<dh>_GimliAEAD_GimliHash`.
+    pub(super) fn for_protocol(pattern: &str, dh: &str) -> Self {
+        Self::new(&["Noise_", pattern, "_", dh, "_", CIPHER_NAME, "_", HASH_NAME])
+    }
+
+    /// Mix `input_key_material` into the chaining key and set a new cipher key, `MixKey`.
+    pub fn mix_key(&mut self, input_key_material: &[u8]) {
+        let [chaining_key, key] = hkdf(&self.chaining_key, input_key_material);
+        self.chaining_key = chaining_key;
+        self.cipher = CipherState::new(key);
+    }
+
+    /// Mix `data` into the handshake hash, `MixHash`.
+    pub fn mix_hash(&mut self, data: &[u8]) {
+        self.handshake_hash = hash(&[&self.handshake_hash, data]);
+    }
+
+    /// Mix a pre-shared key into the chaining key and handshake hash, `MixKeyAndHash`.
+    pub fn mix_key_and_hash(&mut self, input_key_material: &[u8]) {
+        let [chaining_key, temp_hash, key] = hkdf(&self.chaining_key, input_key_material);
+        self.chaining_key = chaining_key;
+        self.mix_hash(&temp_hash);
+        self.cipher = CipherState::new(key);
+    }
+
+    /// Current handshake hash, `GetHandshakeHash()`.
+    pub fn handshake_hash(&self) -> &[u8; HASH_LEN] {
+        &self.handshake_hash
+    }
+
+    /// Encrypt `plaintext` into `out` and mix the result into the hash, `EncryptAndHash`.
+    pub fn encrypt_and_hash(&mut self, plaintext: &[u8], out: &mut [u8]) -> Result<usize, Error> {
+        let len = self
+            .cipher
+            .encrypt_with_ad(&self.handshake_hash, plaintext, out)?;
+        self.mix_hash(&out[..len]);
+        Ok(len)
+    }
+
+    /// Decrypt `ciphertext` into `out` and mix it into the hash, `DecryptAndHash`.
+    pub fn decrypt_and_hash(&mut self, ciphertext: &[u8], out: &mut [u8]) -> Result<usize, Error> {
+        let len = self
+            .cipher
+            .decrypt_with_ad(&self.handshake_hash, ciphertext, out)?;
+        self.mix_hash(ciphertext);
+        Ok(len)
+    }
+
+    /// Whether the cipher state has a key, i.e. encrypted payloads carry a tag.
+    pub fn has_key(&self) -> bool {
+        self.cipher.has_key()
+    }
+
+    /// Derive the two transport cipher states, `Split()`.
+    ///
+    /// The first is for messages from the initiator to the responder.
+    pub fn split(&self) -> (CipherState, CipherState) {
+        let [initiator, responder] = hkdf(&self.chaining_key, &[]);
+        (CipherState::new(initiator), CipherState::new(responder))
+    }
+}
@@ -0,0 +1,477 @@
+extern crate std;
+use super::*;
+use std::vec::Vec;
+use x25519_dalek::{PublicKey, SharedSecret, StaticSecret};
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// X25519 with deterministic ephemeral keys so that the tests are reproducible.
+struct X25519 {
+    counter: u8,
+}
+
+impl X25519 {
+    fn new(seed: u8) -> Self {
+        Self { counter: seed }
+    }
+}
+
+impl Dh for X25519 {
+    const NAME: &'static str = "25519";
+    const DH_LEN: usize = 32;
+    type PrivateKey = StaticSecret;
+    type PublicKey = PublicKey;
+    type SharedSecret = SharedSecret;
+
+    fn generate_keypair(&mut self) -> (StaticSecret, PublicKey) {
+        self.counter = self.counter.wrapping_add(1);
+        let private = StaticSecret::from(crate::hash(&[b'e', self.counter]));
+        let public = PublicKey::from(&private);
+        (private, public)
+    }
+
+    fn public_key(&self, private: &StaticSecret) -> PublicKey {
+        PublicKey::from(private)
+    }
+
+    fn public_key_from_bytes(&self, bytes: &[u8]) -> Option<PublicKey> {
+        Some(PublicKey::from(<[u8; 32]>::try_from(bytes).ok()?))
+    }
+
+    fn dh(&self, private: &StaticSecret, public: &PublicKey) -> Option<SharedSecret> {
+        let shared_secret = private.diffie_hellman(public);
+        shared_secret.was_contributory().then_some(shared_secret)
+    }
+}
+
+fn static_key(seed: u8) -> StaticSecret {
+    StaticSecret::from(crate::hash(&[b's', seed]))
+}
+
+fn start(pattern: HandshakePattern) -> (HandshakeState<X25519>, HandshakeState<X25519>) {
+    let initiator_static = static_key(1);
+    let responder_static = static_key(2);
+    let responder_public = PublicKey::from(&responder_static);
+
+    let initiator = HandshakeState::new(
+        X25519::new(0),
+        pattern,
+        true,
+        b"prologue",
+        Some(initiator_static),
+        Some(responder_public),
+    )
+    .unwrap();
+    let responder = HandshakeState::new(
+        X25519::new(100),
+        pattern,
+        false,
+        b"prologue",
+        Some(responder_static),
+        None,
+    )
+    .unwrap();
+    (initiator, responder)
+}
+
+/// Run the handshake to completion with payloads in every message, return the messages.
+fn run_handshake(
+    initiator: &mut HandshakeState<X25519>,
+    responder: &mut HandshakeState<X25519>,
+) -> Vec<Vec<u8>> {
+    let mut messages = Vec::new();
+    let mut buffer = [0u8; 256];
+    let mut payload = [0u8; 256];
+
+    while !initiator.is_finished() {
+        let (writer, reader) = if initiator.is_my_turn() {
+            (&mut *initiator, &mut *responder)
+        } else {
+            (&mut *responder, &mut *initiator)
+        };
+        let sent = [messages.len() as u8; 5];
+        let len = writer.write_message(&sent, &mut buffer).unwrap();
+        let payload_len = reader.read_message(&buffer[..len], &mut payload).unwrap();
+        assert_eq!(&payload[..payload_len], &sent);
+        messages.push(buffer[..len].to_vec());
+    }
+    assert!(responder.is_finished());
+    messages
+}
+
+fn assert_transport_works(initiator: &mut TransportState, responder: &mut TransportState) {
+    let mut buffer = [0u8; 64];
+    let mut payload = [0u8; 64];
+
+    for i in 0..3 {
+        let len = initiator.write_message(&[i; 20], &mut buffer).unwrap();
+        assert_eq!(len, 20 + TAG_LEN);
+        let payload_len = responder
+            .read_message(&buffer[..len], &mut payload)
+            .unwrap();
+        assert_eq!(&payload[..payload_len], &[i; 20]);
+
+        let len = responder.write_message(&[i; 3], &mut buffer).unwrap();
+        let payload_len = initiator
+            .read_message(&buffer[..len], &mut payload)
+            .unwrap();
+        assert_eq!(&payload[..payload_len], &[i; 3]);
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "X25519 is too slow under Miri")]
+fn test_loopback_all_patterns() {
+    for pattern in [
+        HandshakePattern::NN,
+        HandshakePattern::NK,
+        HandshakePattern::XX,
+        HandshakePattern::IK,
+    ] {
+        let (mut initiator, mut responder) = start(pattern);
+        run_handshake(&mut initiator, &mut responder);
+        assert_eq!(initiator.handshake_hash(), responder.handshake_hash());
+
+        if matches!(pattern, HandshakePattern::XX | HandshakePattern::IK) {
+            let initiator_public = PublicKey::from(&static_key(1));
+            assert_eq!(responder.remote_static(), Some(&initiator_public));
+        }
+
+        let mut initiator = initiator.into_transport().unwrap();
+        let mut responder = responder.into_transport().unwrap();
+        assert_eq!(initiator.handshake_hash(), responder.handshake_hash());
+        assert_transport_works(&mut initiator, &mut responder);
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "X25519 is too slow under Miri")]
+fn test_message_sizes() {
+    // NN: e, then e || ee || encrypted payload.
+    let (mut initiator, mut responder) = start(HandshakePattern::NN);
+    let messages = run_handshake(&mut initiator, &mut responder);
+    assert_eq!(messages[0].len(), 32 + 5);
+    assert_eq!(messages[1].len(), 32 + 5 + TAG_LEN);
+
+    // XX: the static keys are encrypted.
+    let (mut initiator, mut responder) = start(HandshakePattern::XX);
+    let messages = run_handshake(&mut initiator, &mut responder);
+    assert_eq!(messages[0].len(), 32 + 5);
+    assert_eq!(messages[1].len(), 32 + 32 + TAG_LEN + 5 + TAG_LEN);
+    assert_eq!(messages[2].len(), 32 + TAG_LEN + 5 + TAG_LEN);
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "X25519 is too slow under Miri")]
+fn test_handshake_is_stable() {
+    let (mut initiator, mut responder) = start(HandshakePattern::NN);
+    run_handshake(&mut initiator, &mut responder);
+
+    let expected = hex_to_bytes("21310b7c8f2bbb46850c873e57348bdb4e12ad817d37eb17e131af4d35c37e12");
+    assert_eq!(&initiator.handshake_hash()[..], &expected[..]);
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "X25519 is too slow under Miri")]
+fn test_tampered_handshake_message() {
+    let (mut initiator, mut responder) = start(HandshakePattern::XX);
+    let mut buffer = [0u8; 256];
+    let mut payload = [0u8; 256];
+
+    let len = initiator.write_message(b"", &mut buffer).unwrap();
+    responder
+        .read_message(&buffer[..len], &mut payload)
+        .unwrap();
+
+    let len = responder.write_message(b"", &mut buffer).unwrap();
+    buffer[40] ^= 1;
+    assert_eq!(
+        initiator.read_message(&buffer[..len], &mut payload),
+        Err(Error::Decrypt)
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "X25519 is too slow under Miri")]
+fn test_different_prologue() {
+    let (mut initiator, _) = start(HandshakePattern::NN);
+    let mut responder = HandshakeState::new(
+        X25519::new(100),
+        HandshakePattern::NN,
+        false,
+        b"other prologue",
+        None,
+        None,
+    )
+    .unwrap();
+    let mut buffer = [0u8; 256];
+    let mut payload = [0u8; 256];
+
+    let len = initiator.write_message(b"", &mut buffer).unwrap();
+    responder
+        .read_message(&buffer[..len], &mut payload)
+        .unwrap();
+    let len = responder.write_message(b"", &mut buffer).unwrap();
+    assert_eq!(
+        initiator.read_message(&buffer[..len], &mut payload),
+        Err(Error::Decrypt)
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "X25519 is too slow under Miri")]
+fn test_tampered_transport_message() {
+    let (mut initiator, mut responder) = start(HandshakePattern::NN);
+    run_handshake(&mut initiator, &mut responder);
+    let mut initiator = initiator.into_transport().unwrap();
+    let mut responder = responder.into_transport().unwrap();
+
+    let mut buffer = [0u8; 64];
+    let mut payload = [0xffu8; 64];
+    let len = initiator.write_message(b"Hello", &mut buffer).unwrap();
+    buffer[0] ^= 1;
+    assert_eq!(
+        responder.read_message(&buffer[..len], &mut payload),
+        Err(Error::Decrypt)
+    );
+    assert_eq!(&payload[..5], &[0; 5]);
+
+    // The nonce was not consumed, the untampered message still decrypts.
+    buffer[0] ^= 1;
+    assert_eq!(responder.read_message(&buffer[..len], &mut payload), Ok(5));
+
+    // Replays fail.
+    assert_eq!(
+        responder.read_message(&buffer[..len], &mut payload),
+        Err(Error::Decrypt)
+    );
+    assert_eq!(
+        responder.read_message(&buffer[..TAG_LEN - 1], &mut payload),
+        Err(Error::Decrypt)
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "X25519 is too slow under Miri")]
+fn test_state_errors() {
+    let (mut initiator, mut responder) = start(HandshakePattern::NN);
+    let mut buffer = [0u8; 256];
+    let mut payload = [0u8; 256];
+
+    assert_eq!(
+        responder.write_message(b"", &mut buffer),
+        Err(Error::WrongState)
+    );
+    assert_eq!(
+        initiator.read_message(&buffer, &mut payload),
+        Err(Error::WrongState)
+    );
+    assert!(matches!(initiator.into_transport(), Err(Error::WrongState)));
+
+    assert_eq!(
+        responder.read_message(&buffer[..31], &mut payload),
+        Err(Error::InvalidMessage)
+    );
+    let mut short = [0u8; 31];
+    let (mut initiator, _) = start(HandshakePattern::NN);
+    assert_eq!(
+        initiator.write_message(b"", &mut short),
+        Err(Error::BufferTooSmall)
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "X25519 is too slow under Miri")]
+fn test_oversized_payload() {
+    let (mut initiator, mut responder) = start(HandshakePattern::NN);
+    let hash = *initiator.handshake_hash();
+
+    // The first NN message is `e` in the clear followed by the payload.
+    let payload = std::vec![0u8; MAX_MESSAGE_LEN - X25519::DH_LEN + 1];
+    let mut buffer = std::vec![0u8; MAX_MESSAGE_LEN + 1];
+    assert_eq!(
+        initiator.write_message(&payload, &mut buffer),
+        Err(Error::InvalidMessage)
+    );
+    assert_eq!(initiator.handshake_hash(), &hash);
+
+    // The state is untouched, the handshake continues as if nothing happened.
+    let messages = run_handshake(&mut initiator, &mut responder);
+    let (mut fresh_initiator, mut fresh_responder) = start(HandshakePattern::NN);
+    assert_eq!(
+        messages,
+        run_handshake(&mut fresh_initiator, &mut fresh_responder)
+    );
+
+    let (mut initiator, _) = start(HandshakePattern::NN);
+    let payload = &payload[..MAX_MESSAGE_LEN - X25519::DH_LEN];
+    assert_eq!(
+        initiator.write_message(payload, &mut buffer),
+        Ok(MAX_MESSAGE_LEN)
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "X25519 is too slow under Miri")]
+fn test_missing_keys() {
+    let missing = |pattern, initiator, s: bool, rs: bool| {
+        HandshakeState::new(
+            X25519::new(0),
+            pattern,
+            initiator,
+            b"",
+            s.then(|| static_key(1)),
+            rs.then(|| PublicKey::from(&static_key(2))),
+        )
+        .err()
+    };
+
+    assert_eq!(missing(HandshakePattern::NN, true, false, false), None);
+    assert_eq!(
+        missing(HandshakePattern::NK, true, false, false),
+        Some(Error::MissingKey)
+    );
+    assert_eq!(
+        missing(HandshakePattern::NK, false, false, false),
+        Some(Error::MissingKey)
+    );
+    assert_eq!(missing(HandshakePattern::NK, true, false, true), None);
+    assert_eq!(
+        missing(HandshakePattern::XX, true, false, false),
+        Some(Error::MissingKey)
+    );
+    assert_eq!(
+        missing(HandshakePattern::IK, true, true, false),
+        Some(Error::MissingKey)
+    );
+    assert_eq!(missing(HandshakePattern::IK, false, true, false), None);
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "X25519 is too slow under Miri")]
+fn test_low_order_point_rejected() {
+    let (_, mut responder) = start(HandshakePattern::NN);
+    let mut payload = [0u8; 64];
+    let message = [0u8; 32];
+    responder.read_message(&message, &mut payload).unwrap();
+
+    let mut buffer = [0u8; 64];
+    assert_eq!(responder.write_message(b"", &mut buffer), Err(Error::Dh));
+}
+
+#[test]
+fn test_cipher_state_nonces() {
+    let mut sender = CipherState::new([7; KEY_LEN]);
+    let mut receiver = CipherState::new([7; KEY_LEN]);
+    let mut buffer = [0u8; 32];
+    let mut out = [0u8; 32];
+
+    let len = sender
+        .encrypt_with_ad(b"ad", b"message", &mut buffer)
+        .unwrap();
+    assert_eq!(sender.nonce(), 1);
+    assert_eq!(
+        receiver.decrypt_with_ad(b"other", &buffer[..len], &mut out),
+        Err(Error::Decrypt)
+    );
+    assert_eq!(receiver.nonce(), 0);
+    assert_eq!(
+        receiver.decrypt_with_ad(b"ad", &buffer[..len], &mut out),
+        Ok(7)
+    );
+
+    // Skipped nonces must be set explicitly.
+    sender.encrypt_with_ad(b"", b"lost", &mut buffer).unwrap();
+    let len = sender.encrypt_with_ad(b"", b"next", &mut buffer).unwrap();
+    receiver.set_nonce(2);
+    assert_eq!(
+        receiver.decrypt_with_ad(b"", &buffer[..len], &mut out),
+        Ok(4)
+    );
+
+    assert_eq!(
+        sender.encrypt_with_ad(b"", &[0; 17], &mut buffer),
+        Err(Error::BufferTooSmall)
+    );
+
+    sender.set_nonce(u64::MAX);
+    assert_eq!(
+        sender.encrypt_with_ad(b"", b"", &mut buffer),
+        Err(Error::NonceExhausted)
+    );
+}
+
+#[test]
+fn test_cipher_state_without_key() {
+    let mut cipher = CipherState::default();
+    assert!(!cipher.has_key());
+
+    let mut out = [0u8; 8];
+    assert_eq!(cipher.encrypt_with_ad(b"ad", b"clear", &mut out), Ok(5));
+    assert_eq!(&out[..5], b"clear");
+    assert_eq!(cipher.decrypt_with_ad(b"ad", b"clear", &mut out), Ok(5));
+    assert_eq!(cipher.nonce(), 0);
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "X25519 is too slow under Miri")]
+fn test_rekey() {
+    let (mut initiator, mut responder) = start(HandshakePattern::NN);
+    run_handshake(&mut initiator, &mut responder);
+    let mut initiator = initiator.into_transport().unwrap();
+    let mut responder = responder.into_transport().unwrap();
+
+    let mut buffer = [0u8; 64];
+    let mut payload = [0u8; 64];
+
+    initiator.rekey_outgoing();
+    let len = initiator.write_message(b"Hello", &mut buffer).unwrap();
+    assert_eq!(
+        responder.read_message(&buffer[..len], &mut payload),
+        Err(Error::Decrypt)
+    );
+
+    responder.rekey_incoming();
+    assert_eq!(responder.read_message(&buffer[..len], &mut payload), Ok(5));
+    assert_eq!(responder.receiver().nonce(), 1);
+}
+
+#[test]
+fn test_hmac() {
+    // A key longer than a block is hashed first.
+    let long_key = [0x0b; BLOCK_LEN + 1];
+    assert_eq!(
+        hmac(&long_key, &[b"data"]),
+        hmac(&crate::hash(&long_key), &[b"data"])
+    );
+
+    // The data parts are concatenated.
+    assert_eq!(hmac(b"key", &[b"da", b"ta"]), hmac(b"key", &[b"data"]));
+    assert_ne!(hmac(b"key", &[b"data"]), hmac(b"key2", &[b"data"]));
+
+    // HKDF outputs chain.
+    let [first, second] = hkdf::<2>(&[1; HASH_LEN], b"ikm");
+    let [first3, second3, third3] = hkdf::<3>(&[1; HASH_LEN], b"ikm");
+    assert_eq!((first, second), (first3, second3));
+    assert_ne!(third3, second3);
+}
+
+#[test]
+fn test_protocol_name_padding() {
+    // Names up to HASHLEN bytes are zero-padded, longer names are hashed.
+    let short = SymmetricState::new(&["Noise_NN_", "short"]);
+    let mut expected = [0u8; HASH_LEN];
+    expected[..14].copy_from_slice(b"Noise_NN_short");
+    assert_eq!(short.handshake_hash(), &expected);
+
+    let long = SymmetricState::for_protocol("NN", "25519");
+    assert_eq!(
+        long.handshake_hash(),
+        &crate::hash(b"Noise_NN_25519_GimliAEAD_GimliHash")
+    );
+}
@@ -120,6 +120,44 @@ impl AeadInPlace for GimliAead {
     }
 }
 
+#[cfg(kani)]
+mod verification {
+    use super::*;
+
+    /// `ga_nonce_to_array` returns a view of the same bytes.
+    #[kani::proof]
+    #[kani::unwind(17)]
+    fn ga_nonce_to_array_is_view() {
+        let nonce = GenericArray::from(kani::any::<[u8; NONCE_SIZE]>());
+        let array = ga_nonce_to_array(&nonce);
+
+        assert!(core::ptr::eq(array.as_ptr(), nonce.as_ptr()));
+        assert_eq!(&array[..], nonce.as_slice());
+    }
+
+    /// `ga_tag_to_array` returns a view of the same bytes.
+    #[kani::proof]
+    #[kani::unwind(17)]
+    fn ga_tag_to_array_is_view() {
+        let tag = GenericArray::from(kani::any::<[u8; TAG_SIZE]>());
+        let array = ga_tag_to_array(&tag);
+
+        assert!(core::ptr::eq(array.as_ptr(), tag.as_ptr()));
+        assert_eq!(&array[..], tag.as_slice());
+    }
+
+    /// `tag_array_to_ga` preserves every byte and roundtrips through `ga_tag_to_array`.
+    #[kani::proof]
+    #[kani::unwind(17)]
+    fn tag_array_to_ga_preserves_bytes() {
+        let tag: [u8; TAG_SIZE] = kani::any();
+        let ga = tag_array_to_ga(tag);
+
+        assert_eq!(ga.as_slice(), &tag[..]);
+        assert_eq!(ga_tag_to_array(&ga), &tag);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,203 @@
+//! # RustCrypto stream cipher trait implementation
+//!
+//! This module provides an unauthenticated Gimli stream cipher implementing the RustCrypto
+//! `cipher` traits, for protocols that authenticate separately, e.g. disk sectors with their own
+//! MACs. Without a MAC an attacker can flip any plaintext bit, use [`GimliAead`](crate::GimliAead)
+//! unless the data is authenticated otherwise.
+//!
+//! The state is initialized from key and nonce like `aead/gimli24v1`. Keystream block `i` is
+//! the rate of the permuted initial state with `i` as little-endian 64-bit integer XORed into the
+//! rate and a domain byte into the capacity, so every block can be computed directly and the
+//! cipher seeks in constant time. Never reuse a nonce with the same key, and use keys distinct
+//! from the AEAD ones.
+
+use crate::aead_impl::initialize;
+use crate::gimli::{State, gimli};
+use crate::{KEY_SIZE, NONCE_SIZE, RATE, STATE_LAST_BYTE};
+use cipher::{
+    Block, BlockSizeUser, IvSizeUser, KeyIvInit, KeySizeUser, ParBlocksSizeUser, StreamBackend,
+    StreamCipherCore, StreamCipherCoreWrapper, StreamCipherSeekCore, StreamClosure,
+    consts::{U1, U16, U32},
+};
+use zeroize::ZeroizeOnDrop;
+
+/// Domain separation byte of keystream blocks, distinct from the `aead/gimli24v1` padding.
+const DOMAIN_KEYSTREAM: u8 = 0x02;
+
+/// Gimli stream cipher core, use [`GimliCipher`].
+#[derive(Clone, ZeroizeOnDrop)]
+pub struct GimliCipherCore {
+    /// State after initialization with key and nonce.
+    state: State,
+    /// Position of the next keystream block.
+    counter: u64,
+}
+
+impl KeySizeUser for GimliCipherCore {
+    type KeySize = U32;
+}
+
+impl IvSizeUser for GimliCipherCore {
+    type IvSize = U16;
+}
+
+impl BlockSizeUser for GimliCipherCore {
+    type BlockSize = U16;
+}
+
+impl KeyIvInit for GimliCipherCore {
+    fn new(key: &cipher::Key<Self>, iv: &cipher::Iv<Self>) -> Self {
+        let key: &[u8; KEY_SIZE] = key.as_slice().try_into().unwrap();
+        let nonce: &[u8; NONCE_SIZE] = iv.as_slice().try_into().unwrap();
+        Self {
+            state: initialize(key, nonce),
+            counter: 0,
+        }
+    }
+}
+
+impl StreamCipherCore for GimliCipherCore {
+    fn remaining_blocks(&self) -> Option<usize> {
+        (u64::MAX - self.counter).try_into().ok()
+    }
+
+    fn process_with_backend(&mut self, f: impl StreamClosure<BlockSize = Self::BlockSize>) {
+        f.call(self);
+    }
+}
+
+impl StreamCipherSeekCore for GimliCipherCore {
+    type Counter = u64;
+
+    fn get_block_pos(&self) -> u64 {
+        self.counter
+    }
+
+    fn set_block_pos(&mut self, pos: u64) {
+        self.counter = pos;
+    }
+}
+
+impl ParBlocksSizeUser for GimliCipherCore {
+    type ParBlocksSize = U1;
+}
+
+impl StreamBackend for GimliCipherCore {
+    fn gen_ks_block(&mut self, block: &mut Block<Self>) {
+        let mut state = self.state.clone();
+        let state_bytes = state.as_bytes_mut();
+        for (byte, counter) in state_bytes.iter_mut().zip(self.counter.to_le_bytes()) {
+            *byte ^= counter;
+        }
+        state_bytes[STATE_LAST_BYTE] ^= DOMAIN_KEYSTREAM;
+        gimli(&mut state);
+
+        block.copy_from_slice(&state.as_bytes()[..RATE]);
+        self.counter = self.counter.wrapping_add(1);
+    }
+}
+
+/// Unauthenticated Gimli stream cipher implementing RustCrypto `cipher` traits.
+pub type GimliCipher = StreamCipherCoreWrapper<GimliCipherCore>;
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::*;
+    use cipher::{StreamCipher, StreamCipherSeek};
+    use std::vec;
+    use std::vec::Vec;
+
+    fn cipher() -> GimliCipher {
+        GimliCipher::new(&[7u8; KEY_SIZE].into(), &[9u8; NONCE_SIZE].into())
+    }
+
+    fn keystream(len: usize) -> Vec<u8> {
+        let mut buffer = vec![0u8; len];
+        cipher().apply_keystream(&mut buffer);
+        buffer
+    }
+
+    fn hex_to_bytes(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn keystream_known_answer() {
+        assert_eq!(
+            keystream(40),
+            hex_to_bytes(
+                "c35c4af4e1d877606981f37d4ed8372cb72ee1dd5a0ed7b516da614b838e738c\
+                 374cd714aade1d36"
+            )
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut buffer = *b"Hello, unauthenticated Gimli keystream!";
+        cipher().apply_keystream(&mut buffer);
+        assert_ne!(&buffer, b"Hello, unauthenticated Gimli keystream!");
+        cipher().apply_keystream(&mut buffer);
+        assert_eq!(&buffer, b"Hello, unauthenticated Gimli keystream!");
+    }
+
+    #[test]
+    fn split_calls() {
+        let expected = keystream(50);
+        for split in 0..=50 {
+            let mut buffer = vec![0u8; 50];
+            let mut cipher = cipher();
+            let (head, tail) = buffer.split_at_mut(split);
+            cipher.apply_keystream(head);
+            cipher.apply_keystream(tail);
+            assert_eq!(buffer, expected, "split {split}");
+        }
+    }
+
+    #[test]
+    fn seek() {
+        let expected = keystream(50);
+        for pos in 0..50u64 {
+            let mut cipher = cipher();
+            cipher.seek(pos);
+            assert_eq!(cipher.current_pos::<u64>(), pos);
+
+            let mut buffer = vec![0u8; 50 - pos as usize];
+            cipher.apply_keystream(&mut buffer);
+            assert_eq!(buffer, expected[pos as usize..], "pos {pos}");
+        }
+    }
+
+    #[test]
+    fn distinct_key_nonce_and_aead() {
+        let expected = keystream(16);
+
+        let mut buffer = [0u8; 16];
+        GimliCipher::new(&[8u8; KEY_SIZE].into(), &[9u8; NONCE_SIZE].into())
+            .apply_keystream(&mut buffer);
+        assert_ne!(buffer.to_vec(), expected);
+
+        let mut buffer = [0u8; 16];
+        GimliCipher::new(&[7u8; KEY_SIZE].into(), &[10u8; NONCE_SIZE].into())
+            .apply_keystream(&mut buffer);
+        assert_ne!(buffer.to_vec(), expected);
+
+        // Not the keystream of the AEAD with the same key and nonce.
+        let mut buffer = [0u8; 16];
+        let _ = crate::encrypt_in_place(&[7; KEY_SIZE], &[9; NONCE_SIZE], &[], &mut buffer);
+        assert_ne!(buffer.to_vec(), expected);
+    }
+
+    #[test]
+    fn end_of_keystream() {
+        let mut cipher = cipher();
+        cipher.seek((u64::MAX - 1) as u128 * 16);
+        let mut buffer = [0u8; 16];
+        assert!(cipher.try_apply_keystream(&mut buffer).is_ok());
+        assert!(cipher.try_apply_keystream(&mut buffer[..1]).is_err());
+    }
+}
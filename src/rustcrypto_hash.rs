@@ -1,6 +1,24 @@
 //! # RustCrypto Digest trait implementation
 //!
 //! This module provides implementations of the RustCrypto `digest` traits for Gimli hash.
+//!
+//! `digest` 0.10 has no trait for serializing hasher states. To persist a partial hash, convert
+//! it into a [`Hasher`](crate::Hasher), which absorbs the bytes still buffered by the wrapper, and
+//! use [`Hasher::export_state`](crate::Hasher::export_state). Convert the imported `Hasher` back
+//! to resume:
+//!
+//! ```
+//! use digest::Digest;
+//! use gimli_crypto::{GimliHash, Hasher};
+//!
+//! let mut hash = GimliHash::new();
+//! hash.update(b"Hello, ");
+//! let state = Hasher::from(&hash).export_state();
+//!
+//! let mut resumed = GimliHash::from(Hasher::import_state(&state).unwrap());
+//! resumed.update(b"World!");
+//! assert_eq!(resumed.finalize(), GimliHash::digest(b"Hello, World!"));
+//! ```
 
 use crate::Hasher as GimliHasher;
 use digest::{
@@ -66,6 +84,23 @@ impl HashMarker for GimliHashCore {}
 /// `hash/gimli24v1` hash function implementing RustCrypto digest traits.
 pub type GimliHash = CoreWrapper<GimliHashCore>;
 
+impl From<GimliHasher> for GimliHash {
+    /// Continue the hash of `hasher`, e.g. one resumed with `Hasher::import_state`.
+    fn from(hasher: GimliHasher) -> Self {
+        Self::from_core(GimliHashCore { hasher })
+    }
+}
+
+impl From<&GimliHash> for GimliHasher {
+    /// The state of `hash`, including the bytes buffered by the wrapper.
+    fn from(hash: &GimliHash) -> Self {
+        let (core, buffer) = hash.clone().decompose();
+        let mut hasher = core.hasher;
+        hasher.update(buffer.get_data());
+        hasher
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -148,4 +183,25 @@ mod tests {
 
         assert_ne!(result1, result2);
     }
+
+    #[test]
+    fn hash_export_import() {
+        let message = [0x5a; 100];
+        let expected = GimliHash::digest(message);
+
+        // Split points inside and at the edges of the wrapper's block buffer.
+        for split in [0, 1, 15, 16, 31, 32, 33, 64, 99, 100] {
+            let mut hasher = GimliHash::new();
+            hasher.update(&message[..split]);
+
+            let state = GimliHasher::from(&hasher).export_state();
+            let mut resumed = GimliHash::from(GimliHasher::import_state(&state).unwrap());
+            resumed.update(&message[split..]);
+            assert_eq!(resumed.finalize(), expected, "split at {split}");
+
+            // Converting does not consume the buffered bytes of the original.
+            hasher.update(&message[split..]);
+            assert_eq!(hasher.finalize(), expected, "split at {split}");
+        }
+    }
 }
@@ -0,0 +1,126 @@
+//! # Sealed messages
+//!
+//! `aead/gimli24v1` with the nonce embedded in the message, so application code never handles
+//! nonces. A sealed message is `nonce || ciphertext || tag`, with a random nonce drawn from the
+//! OS by the `getrandom` feature.
+//!
+//! `seal` and `open` work on `Vec<u8>` with the `alloc` feature. `seal_in_place` and
+//! [`open_in_place`] work on any [`aead::Buffer`], e.g. a `heapless::Vec` with the `heapless`
+//! feature, and need no allocator.
+//!
+//! Random 128-bit nonces make collisions negligible for any realistic number of messages under
+//! one key.
+//!
+//! # Usage
+//!
+//! ```
+//! # #[cfg(all(feature = "alloc", feature = "getrandom"))]
+//! # {
+//! use gimli_crypto::sealed;
+//!
+//! let key = [0u8; 32];
+//! let message = sealed::seal(&key, b"header", b"Secret message");
+//! assert_eq!(message.len(), 14 + sealed::OVERHEAD);
+//!
+//! let plaintext = sealed::open(&key, b"header", &message).unwrap();
+//! assert_eq!(plaintext, b"Secret message");
+//! # }
+//! ```
+
+use crate::{KEY_SIZE, NONCE_SIZE, TAG_SIZE, decrypt_in_place, encrypt_in_place};
+use aead::{Buffer, Error};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Bytes added to every plaintext, `NONCE_SIZE + TAG_SIZE`.
+pub const OVERHEAD: usize = NONCE_SIZE + TAG_SIZE;
+
+/// Seal `plaintext` into a new `nonce || ciphertext || tag` message with a random nonce.
+#[cfg(all(feature = "alloc", feature = "getrandom"))]
+pub fn seal(key: &[u8; KEY_SIZE], associated_data: &[u8], plaintext: &[u8]) -> Vec<u8> {
+    let mut buffer = Vec::with_capacity(plaintext.len() + OVERHEAD);
+    buffer.extend_from_slice(plaintext);
+    seal_in_place(key, associated_data, &mut buffer).expect("`Vec` cannot run out of capacity");
+    buffer
+}
+
+/// Open a message produced by `seal`, returning the plaintext.
+#[cfg(feature = "alloc")]
+pub fn open(key: &[u8; KEY_SIZE], associated_data: &[u8], sealed: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut buffer = Vec::from(sealed);
+    open_in_place(key, associated_data, &mut buffer)?;
+    Ok(buffer)
+}
+
+/// Seal the plaintext in `buffer` in-place into `nonce || ciphertext || tag` with a random
+/// nonce.
+///
+/// Fails if `buffer` cannot grow by [`OVERHEAD`] bytes, leaving it unchanged.
+#[cfg(feature = "getrandom")]
+pub fn seal_in_place(
+    key: &[u8; KEY_SIZE],
+    associated_data: &[u8],
+    buffer: &mut dyn Buffer,
+) -> Result<(), Error> {
+    use aead::rand_core::RngCore;
+
+    let mut nonce = [0u8; NONCE_SIZE];
+    aead::OsRng.fill_bytes(&mut nonce);
+
+    seal_in_place_with_nonce(key, &nonce, associated_data, buffer)
+}
+
+/// Seal the plaintext in `buffer` in-place with a caller-provided nonce.
+///
+/// The nonce must never repeat under the same key. Prefer `seal_in_place` unless the nonce
+/// comes from another source of randomness or a counter.
+///
+/// Fails if `buffer` cannot grow by [`OVERHEAD`] bytes, leaving it unchanged.
+pub fn seal_in_place_with_nonce(
+    key: &[u8; KEY_SIZE],
+    nonce: &[u8; NONCE_SIZE],
+    associated_data: &[u8],
+    buffer: &mut dyn Buffer,
+) -> Result<(), Error> {
+    let len = buffer.len();
+    buffer.extend_from_slice(&[0u8; OVERHEAD])?;
+
+    let message = buffer.as_mut();
+    message.copy_within(..len, NONCE_SIZE);
+    message[..NONCE_SIZE].copy_from_slice(nonce);
+
+    let (ciphertext, tag) = message[NONCE_SIZE..].split_at_mut(len);
+    tag.copy_from_slice(&encrypt_in_place(key, nonce, associated_data, ciphertext));
+    Ok(())
+}
+
+/// Open the sealed message in `buffer` in-place, leaving the plaintext.
+///
+/// Fails if the message is shorter than [`OVERHEAD`] or does not authenticate, in which case
+/// `buffer` is zeroed.
+pub fn open_in_place(
+    key: &[u8; KEY_SIZE],
+    associated_data: &[u8],
+    buffer: &mut dyn Buffer,
+) -> Result<(), Error> {
+    let len = buffer.len().checked_sub(OVERHEAD).ok_or(Error)?;
+
+    let message = buffer.as_mut();
+    let (nonce, rest) = message.split_at_mut(NONCE_SIZE);
+    let (ciphertext, tag) = rest.split_at_mut(len);
+    let nonce: &[u8; NONCE_SIZE] = (&*nonce).try_into().unwrap();
+    let tag: &[u8; TAG_SIZE] = (&*tag).try_into().unwrap();
+
+    if decrypt_in_place(key, nonce, associated_data, ciphertext, tag).is_err() {
+        message.fill(0);
+        return Err(Error);
+    }
+
+    message.copy_within(NONCE_SIZE..NONCE_SIZE + len, 0);
+    buffer.truncate(len);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
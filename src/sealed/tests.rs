@@ -0,0 +1,177 @@
+extern crate std;
+use super::*;
+use std::vec::Vec;
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+const KEY: [u8; KEY_SIZE] = [0x42; KEY_SIZE];
+
+/// Fixed-capacity buffer, standing in for `heapless::Vec` without the feature.
+struct ArrayBuffer<const N: usize> {
+    data: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> ArrayBuffer<N> {
+    fn from_slice(data: &[u8]) -> Self {
+        let mut buffer = Self {
+            data: [0; N],
+            len: 0,
+        };
+        buffer.extend_from_slice(data).unwrap();
+        buffer
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for ArrayBuffer<N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+}
+
+impl<const N: usize> AsMut<[u8]> for ArrayBuffer<N> {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.data[..self.len]
+    }
+}
+
+impl<const N: usize> Buffer for ArrayBuffer<N> {
+    fn extend_from_slice(&mut self, other: &[u8]) -> Result<(), Error> {
+        let end = self.len + other.len();
+        self.data
+            .get_mut(self.len..end)
+            .ok_or(Error)?
+            .copy_from_slice(other);
+        self.len = end;
+        Ok(())
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+}
+
+#[test]
+fn test_roundtrip_all_lengths() {
+    let nonce = [7u8; NONCE_SIZE];
+    for len in 0..=64 {
+        let plaintext: Vec<u8> = (0..len).map(|i| (i * 7) as u8).collect();
+
+        let mut buffer = ArrayBuffer::<96>::from_slice(&plaintext);
+        seal_in_place_with_nonce(&KEY, &nonce, b"ad", &mut buffer).unwrap();
+        assert_eq!(buffer.len(), len + OVERHEAD);
+
+        open_in_place(&KEY, b"ad", &mut buffer).unwrap();
+        assert_eq!(buffer.as_ref(), plaintext);
+    }
+}
+
+#[test]
+fn test_matches_detached_aead() {
+    let nonce = [7u8; NONCE_SIZE];
+    let mut buffer = ArrayBuffer::<64>::from_slice(b"Secret message");
+    seal_in_place_with_nonce(&KEY, &nonce, b"ad", &mut buffer).unwrap();
+
+    let mut ciphertext = *b"Secret message";
+    let tag = encrypt_in_place(&KEY, &nonce, b"ad", &mut ciphertext);
+
+    let message = buffer.as_ref();
+    assert_eq!(message[..NONCE_SIZE], nonce);
+    assert_eq!(message[NONCE_SIZE..NONCE_SIZE + 14], ciphertext);
+    assert_eq!(message[NONCE_SIZE + 14..], tag);
+}
+
+#[test]
+fn test_tampering_rejected() {
+    let mut buffer = ArrayBuffer::<64>::from_slice(b"Hello");
+    seal_in_place_with_nonce(&KEY, &[7; NONCE_SIZE], b"ad", &mut buffer).unwrap();
+    let sealed = buffer.as_ref().to_vec();
+
+    for i in 0..sealed.len() {
+        let mut tampered = ArrayBuffer::<64>::from_slice(&sealed);
+        tampered.as_mut()[i] ^= 1;
+        assert_eq!(
+            open_in_place(&KEY, b"ad", &mut tampered),
+            Err(Error),
+            "byte {i}"
+        );
+        assert!(tampered.as_ref().iter().all(|&b| b == 0));
+    }
+
+    let mut wrong_ad = ArrayBuffer::<64>::from_slice(&sealed);
+    assert_eq!(open_in_place(&KEY, b"other", &mut wrong_ad), Err(Error));
+
+    let mut wrong_key = ArrayBuffer::<64>::from_slice(&sealed);
+    assert_eq!(
+        open_in_place(&[0; KEY_SIZE], b"ad", &mut wrong_key),
+        Err(Error)
+    );
+}
+
+#[test]
+fn test_too_short() {
+    for len in 0..OVERHEAD {
+        let mut buffer = ArrayBuffer::<64>::from_slice(&[0u8; 64][..len]);
+        assert_eq!(open_in_place(&KEY, b"ad", &mut buffer), Err(Error));
+    }
+}
+
+#[test]
+fn test_insufficient_capacity_leaves_buffer_unchanged() {
+    let mut buffer = ArrayBuffer::<{ 5 + OVERHEAD - 1 }>::from_slice(b"Hello");
+    assert_eq!(
+        seal_in_place_with_nonce(&KEY, &[7; NONCE_SIZE], b"ad", &mut buffer),
+        Err(Error)
+    );
+    assert_eq!(buffer.as_ref(), b"Hello");
+}
+
+#[test]
+fn test_known_message() {
+    let mut buffer = ArrayBuffer::<64>::from_slice(b"Hello");
+    seal_in_place_with_nonce(&KEY, &[7; NONCE_SIZE], b"ad", &mut buffer).unwrap();
+    assert_eq!(
+        buffer.as_ref(),
+        hex_to_bytes("07070707070707070707070707070707c5871c9bdf5ef053298049355b3707400a174e5f6f")
+    );
+}
+
+#[cfg(feature = "getrandom")]
+#[test]
+fn test_random_nonces() {
+    let mut first = ArrayBuffer::<64>::from_slice(b"Hello");
+    let mut second = ArrayBuffer::<64>::from_slice(b"Hello");
+    seal_in_place(&KEY, b"ad", &mut first).unwrap();
+    seal_in_place(&KEY, b"ad", &mut second).unwrap();
+    assert_ne!(first.as_ref()[..NONCE_SIZE], second.as_ref()[..NONCE_SIZE]);
+
+    open_in_place(&KEY, b"ad", &mut first).unwrap();
+    assert_eq!(first.as_ref(), b"Hello");
+}
+
+#[cfg(all(feature = "alloc", feature = "getrandom"))]
+#[test]
+fn test_seal_open() {
+    let sealed = seal(&KEY, b"ad", b"Secret message");
+    assert_eq!(sealed.len(), 14 + OVERHEAD);
+    assert_eq!(open(&KEY, b"ad", &sealed).unwrap(), b"Secret message");
+    assert_eq!(open(&KEY, b"other", &sealed), Err(Error));
+    assert_eq!(open(&KEY, b"ad", &sealed[..OVERHEAD - 1]), Err(Error));
+}
+
+#[cfg(feature = "heapless")]
+#[test]
+fn test_heapless() {
+    let mut buffer: aead::heapless::Vec<u8, 64> = aead::heapless::Vec::new();
+    buffer.extend_from_slice(b"Hello").unwrap();
+    seal_in_place_with_nonce(&KEY, &[7; NONCE_SIZE], b"ad", &mut buffer).unwrap();
+    assert_eq!(buffer.len(), 5 + OVERHEAD);
+
+    open_in_place(&KEY, b"ad", &mut buffer).unwrap();
+    assert_eq!(buffer, b"Hello");
+}
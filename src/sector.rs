@@ -0,0 +1,108 @@
+//! # Sector encryption
+//!
+//! Length-preserving, tweakable wide-block encryption for disk and flash sectors, where the
+//! ciphertext must fit in place of the plaintext. The construction is BEAR (Anderson, Biham),
+//! an unbalanced three round Feistel network over the sector split into `L`, the first
+//! [`HASH_SIZE`] bytes, and `R`, the rest:
+//!
+//! ```text
+//! L ^= H1(key, tweak, R)
+//! R ^= S(L)
+//! L ^= H2(key, tweak, R)
+//! ```
+//!
+//! `H1` and `H2` are [`TupleHasher`]s over key, tweak and `R` with distinct customizations, `S`
+//! is the [`GimliCipher`] keystream keyed by `L`. Every ciphertext bit depends on every plaintext
+//! bit and the other way around, so a single changed bit garbles the whole sector on decryption,
+//! unlike a stream cipher where an attacker can flip chosen bits.
+//!
+//! The tweak, e.g. the sector number, makes equal sectors at different positions encrypt
+//! differently. Encryption is deterministic: rewriting a sector with the same contents produces
+//! the same ciphertext, and the scheme does not detect modified sectors, only garble them.
+//!
+//! # Usage
+//!
+//! ```
+//! use gimli_crypto::sector::{decrypt_sector, encrypt_sector};
+//!
+//! let key = [7u8; 32];
+//! let mut sector = [0u8; 512];
+//! sector[..5].copy_from_slice(b"hello");
+//!
+//! encrypt_sector(&key, 42, &mut sector).unwrap();
+//! assert_ne!(&sector[..5], b"hello");
+//!
+//! decrypt_sector(&key, 42, &mut sector).unwrap();
+//! assert_eq!(&sector[..5], b"hello");
+//! ```
+
+use crate::{GimliCipher, HASH_SIZE, KEY_SIZE, KeyIvInit, NONCE_SIZE, StreamCipher, TupleHasher};
+
+/// Smallest sector size in bytes, so that both halves have at least 256 bits.
+pub const MIN_SECTOR_SIZE: usize = 2 * HASH_SIZE;
+
+/// Customization of the first hash round.
+const H1_CUSTOMIZATION: &[u8] = b"gimli-crypto sector H1";
+
+/// Customization of the last hash round.
+const H2_CUSTOMIZATION: &[u8] = b"gimli-crypto sector H2";
+
+/// Errors of sector encryption.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The sector is shorter than [`MIN_SECTOR_SIZE`].
+    SectorTooSmall,
+}
+
+/// Encrypt `sector` in place under `key` and `tweak`.
+pub fn encrypt_sector(key: &[u8; KEY_SIZE], tweak: u64, sector: &mut [u8]) -> Result<(), Error> {
+    let (left, right) = split(sector)?;
+    hash_round(H1_CUSTOMIZATION, key, tweak, left, right);
+    stream_round(left, right);
+    hash_round(H2_CUSTOMIZATION, key, tweak, left, right);
+    Ok(())
+}
+
+/// Decrypt `sector` in place under `key` and `tweak`.
+pub fn decrypt_sector(key: &[u8; KEY_SIZE], tweak: u64, sector: &mut [u8]) -> Result<(), Error> {
+    let (left, right) = split(sector)?;
+    hash_round(H2_CUSTOMIZATION, key, tweak, left, right);
+    stream_round(left, right);
+    hash_round(H1_CUSTOMIZATION, key, tweak, left, right);
+    Ok(())
+}
+
+fn split(sector: &mut [u8]) -> Result<(&mut [u8; HASH_SIZE], &mut [u8]), Error> {
+    if sector.len() < MIN_SECTOR_SIZE {
+        return Err(Error::SectorTooSmall);
+    }
+    let (left, right) = sector.split_at_mut(HASH_SIZE);
+    Ok((left.try_into().unwrap(), right))
+}
+
+/// `left ^= H(key, tweak, right)`.
+///
+/// The hasher holds key bytes and is zeroed when dropped at the end.
+fn hash_round(
+    customization: &[u8],
+    key: &[u8; KEY_SIZE],
+    tweak: u64,
+    left: &mut [u8; HASH_SIZE],
+    right: &[u8],
+) {
+    let mut hasher = TupleHasher::new(customization);
+    hasher.push(key);
+    hasher.push(&tweak.to_le_bytes());
+    hasher.push(right);
+    for (byte, mask) in left.iter_mut().zip(hasher.finalize()) {
+        *byte ^= mask;
+    }
+}
+
+/// `right ^= S(left)`.
+fn stream_round(left: &[u8; HASH_SIZE], right: &mut [u8]) {
+    GimliCipher::new(left.into(), &[0; NONCE_SIZE].into()).apply_keystream(right);
+}
+
+#[cfg(test)]
+mod tests;
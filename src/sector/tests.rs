@@ -0,0 +1,118 @@
+extern crate std;
+use super::*;
+use std::vec;
+use std::vec::Vec;
+
+// Under miri, only flip a few bits of a small sector to keep test time reasonable.
+#[cfg(miri)]
+const SECTOR_SIZE: usize = MIN_SECTOR_SIZE;
+#[cfg(not(miri))]
+const SECTOR_SIZE: usize = 512;
+#[cfg(miri)]
+const BIT_STEP: usize = 97;
+#[cfg(not(miri))]
+const BIT_STEP: usize = 1;
+
+const KEY: [u8; KEY_SIZE] = [7; KEY_SIZE];
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn sector() -> Vec<u8> {
+    (0..SECTOR_SIZE).map(|i| i as u8).collect()
+}
+
+/// Assert that every 16-byte block of `a` and `b` differs, which for unrelated random blocks
+/// fails with probability `2^-128` per block.
+fn assert_all_blocks_differ(a: &[u8], b: &[u8], bit: usize) {
+    for (i, (a, b)) in a.chunks(16).zip(b.chunks(16)).enumerate() {
+        assert_ne!(a, b, "bit {bit} block {i}");
+    }
+}
+
+#[test]
+fn test_known_answer() {
+    let mut sector = vec![0u8; MIN_SECTOR_SIZE];
+    encrypt_sector(&KEY, 1, &mut sector).unwrap();
+    assert_eq!(
+        sector,
+        hex_to_bytes(
+            "bd2ce070435ea80c32866d2e06d25aae53a2c0ff732c79ab8ce2b8a6489c2f54\
+             4a20db2c49e1234ae30c3b0134c1f859a3bdd4ac966a386c08cd1537a3b5db2b"
+        )
+    );
+}
+
+#[test]
+fn test_roundtrip() {
+    for len in [MIN_SECTOR_SIZE, MIN_SECTOR_SIZE + 1, 100, SECTOR_SIZE] {
+        let plaintext: Vec<u8> = (0..len).map(|i| i as u8).collect();
+        let mut sector = plaintext.clone();
+        encrypt_sector(&KEY, 3, &mut sector).unwrap();
+        assert_ne!(sector, plaintext);
+        decrypt_sector(&KEY, 3, &mut sector).unwrap();
+        assert_eq!(sector, plaintext, "len {len}");
+    }
+}
+
+#[test]
+fn test_plaintext_bit_flip_diffuses() {
+    let mut expected = sector();
+    encrypt_sector(&KEY, 0, &mut expected).unwrap();
+
+    for bit in (0..8 * SECTOR_SIZE).step_by(BIT_STEP) {
+        let mut sector = sector();
+        sector[bit / 8] ^= 1 << (bit % 8);
+        encrypt_sector(&KEY, 0, &mut sector).unwrap();
+        assert_all_blocks_differ(&sector, &expected, bit);
+    }
+}
+
+#[test]
+fn test_ciphertext_bit_flip_diffuses() {
+    let mut ciphertext = sector();
+    encrypt_sector(&KEY, 0, &mut ciphertext).unwrap();
+
+    for bit in (0..8 * SECTOR_SIZE).step_by(BIT_STEP) {
+        let mut sector = ciphertext.clone();
+        sector[bit / 8] ^= 1 << (bit % 8);
+        decrypt_sector(&KEY, 0, &mut sector).unwrap();
+        assert_all_blocks_differ(&sector, &self::sector(), bit);
+    }
+}
+
+#[test]
+fn test_tweak_and_key() {
+    let mut expected = sector();
+    encrypt_sector(&KEY, 0, &mut expected).unwrap();
+
+    let mut other_tweak = sector();
+    encrypt_sector(&KEY, 1, &mut other_tweak).unwrap();
+    assert_all_blocks_differ(&other_tweak, &expected, 0);
+
+    let mut other_key = sector();
+    encrypt_sector(&[8; KEY_SIZE], 0, &mut other_key).unwrap();
+    assert_all_blocks_differ(&other_key, &expected, 0);
+
+    // Decrypting with the wrong tweak garbles the whole sector.
+    decrypt_sector(&KEY, 0, &mut other_tweak).unwrap();
+    assert_all_blocks_differ(&other_tweak, &sector(), 0);
+}
+
+#[test]
+fn test_sector_too_small() {
+    let mut sector = [0u8; MIN_SECTOR_SIZE - 1];
+    assert_eq!(
+        encrypt_sector(&KEY, 0, &mut sector),
+        Err(Error::SectorTooSmall)
+    );
+    assert_eq!(
+        decrypt_sector(&KEY, 0, &mut sector),
+        Err(Error::SectorTooSmall)
+    );
+    assert_eq!(sector, [0; MIN_SECTOR_SIZE - 1]);
+}
@@ -0,0 +1,280 @@
+//! # Secure channel sessions
+//!
+//! [`Sender`] and [`Receiver`] wrap `aead/gimli24v1` for a stream of packets between two
+//! parties sharing a secret, e.g. the output of a key exchange. Each direction has its own key
+//! derived from the secret, a session id and the party's [`Role`], the nonce is built from a 64-bit
+//! sequence number and packets are framed as `seq || ciphertext || tag` with `seq` in
+//! little-endian.
+//!
+//! # Session ids
+//!
+//! Sequence numbers restart at 0 for every new [`Sender`], so the keys must never repeat between
+//! sessions: building two sessions with the same secret and session id reuses `(key, nonce)`
+//! pairs, which reveals the XOR of the plaintexts and allows forgeries. The session id must be
+//! unique for every session built from the same secret, e.g. the Noise handshake hash or random
+//! nonces contributed by both peers. Prefer a fresh secret per session from a key exchange and do
+//! not use a long-term key as the secret with a predictable session id.
+//!
+//! The receiver accepts packets out of order but rejects replays and packets older than its
+//! sliding window of `64 * WORDS` sequence numbers.
+//!
+//! # Usage
+//!
+//! ```
+//! use gimli_crypto::session::{OVERHEAD, Receiver, Role, Sender};
+//!
+//! let shared_secret = [7u8; 32];
+//! // Unique per session, e.g. the handshake hash or nonces from both peers.
+//! let session_id = [9u8; 32];
+//! let mut sender = Sender::new(&shared_secret, &session_id, Role::Initiator);
+//! let mut receiver: Receiver = Receiver::new(&shared_secret, &session_id, Role::Responder);
+//!
+//! let mut packet = [0u8; 5 + OVERHEAD];
+//! let len = sender.encrypt(b"header", b"Hello", &mut packet).unwrap();
+//!
+//! let mut payload = [0u8; 5];
+//! let len = receiver.decrypt(b"header", &packet[..len], &mut payload).unwrap();
+//! assert_eq!(&payload[..len], b"Hello");
+//!
+//! // Replays are rejected.
+//! assert!(receiver.decrypt(b"header", &packet, &mut payload).is_err());
+//! ```
+
+use crate::{Duplex, KEY_SIZE, NONCE_SIZE, TAG_SIZE, decrypt_in_place, encrypt_in_place};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Size of the sequence number prefix in bytes.
+pub const SEQ_SIZE: usize = 8;
+
+/// Bytes added to every payload, `SEQ_SIZE + TAG_SIZE`.
+pub const OVERHEAD: usize = SEQ_SIZE + TAG_SIZE;
+
+/// Protocol label for the key derivation.
+const KDF_PROTOCOL: &[u8] = b"gimli-crypto session v1";
+
+/// Which side of the session this party is, selecting the directional keys.
+///
+/// The two parties must use different roles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// The party that started the session, e.g. the client.
+    Initiator,
+    /// The other party, e.g. the server.
+    Responder,
+}
+
+/// Errors of the session types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The output buffer is too small for the result.
+    BufferTooSmall,
+    /// The packet is shorter than [`OVERHEAD`].
+    InvalidPacket,
+    /// The sequence number was already received or is older than the replay window.
+    Replay,
+    /// The packet was not produced by the peer or was modified.
+    AuthenticationFailed,
+    /// All sequence numbers have been used, establish a new session.
+    SequenceExhausted,
+}
+
+/// Derive the key of `session_id` for `direction`, the role of the sending party.
+fn derive_key(shared_secret: &[u8], session_id: &[u8], direction: Role) -> [u8; KEY_SIZE] {
+    let mut duplex = Duplex::new(KDF_PROTOCOL);
+    duplex.absorb_key(shared_secret);
+    duplex.absorb(session_id);
+    duplex.absorb(match direction {
+        Role::Initiator => b"initiator to responder",
+        Role::Responder => b"responder to initiator",
+    });
+
+    let mut key = [0u8; KEY_SIZE];
+    duplex.squeeze_key(&mut key);
+    key
+}
+
+/// Nonce for sequence number `seq`.
+fn nonce(seq: u64) -> [u8; NONCE_SIZE] {
+    let mut nonce = [0u8; NONCE_SIZE];
+    nonce[..SEQ_SIZE].copy_from_slice(&seq.to_le_bytes());
+    nonce
+}
+
+/// Sending half of a session.
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct Sender {
+    key: [u8; KEY_SIZE],
+    next_seq: u64,
+}
+
+impl Sender {
+    /// Create the sending half for `role` from `shared_secret` and `session_id`.
+    ///
+    /// `session_id` must be unique for every session built from `shared_secret`, see the
+    /// [module documentation](self#session-ids).
+    pub fn new(shared_secret: &[u8], session_id: &[u8], role: Role) -> Self {
+        Self {
+            key: derive_key(shared_secret, session_id, role),
+            next_seq: 0,
+        }
+    }
+
+    /// Sequence number of the next packet.
+    pub fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    /// Encrypt `payload` into `out` as `seq || ciphertext || tag`, returning the packet length.
+    ///
+    /// `associated_data` is authenticated but not sent, the receiver must supply the same.
+    pub fn encrypt(
+        &mut self,
+        associated_data: &[u8],
+        payload: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, Error> {
+        let seq = self.next_seq;
+        let next_seq = seq.checked_add(1).ok_or(Error::SequenceExhausted)?;
+
+        let len = payload.len() + OVERHEAD;
+        let out = out.get_mut(..len).ok_or(Error::BufferTooSmall)?;
+        let (seq_bytes, rest) = out.split_at_mut(SEQ_SIZE);
+        let (ciphertext, tag) = rest.split_at_mut(payload.len());
+
+        seq_bytes.copy_from_slice(&seq.to_le_bytes());
+        ciphertext.copy_from_slice(payload);
+        tag.copy_from_slice(&encrypt_in_place(
+            &self.key,
+            &nonce(seq),
+            associated_data,
+            ciphertext,
+        ));
+
+        self.next_seq = next_seq;
+        Ok(len)
+    }
+}
+
+/// Receiving half of a session with a replay window of `64 * WORDS` sequence numbers.
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct Receiver<const WORDS: usize = 1> {
+    key: [u8; KEY_SIZE],
+    /// Highest sequence number received so far, `None` before the first packet.
+    highest_seq: Option<u64>,
+    /// Bit `i` is set if `highest_seq - i` was received.
+    window: [u64; WORDS],
+}
+
+impl<const WORDS: usize> Receiver<WORDS> {
+    /// Size of the replay window in sequence numbers.
+    pub const WINDOW: u64 = 64 * WORDS as u64;
+
+    /// Create the receiving half for `role` from `shared_secret` and `session_id`.
+    ///
+    /// The peer's [`Sender`] must use the same `session_id`.
+    pub fn new(shared_secret: &[u8], session_id: &[u8], role: Role) -> Self {
+        const { assert!(WORDS > 0, "the replay window needs at least one word") };
+
+        let peer = match role {
+            Role::Initiator => Role::Responder,
+            Role::Responder => Role::Initiator,
+        };
+        Self {
+            key: derive_key(shared_secret, session_id, peer),
+            highest_seq: None,
+            window: [0; WORDS],
+        }
+    }
+
+    /// Highest sequence number received so far.
+    pub fn highest_seq(&self) -> Option<u64> {
+        self.highest_seq
+    }
+
+    /// Authenticate and decrypt `packet` into `out`, returning the payload length.
+    ///
+    /// The window is only updated for authentic packets. On failure `out` is zeroed.
+    pub fn decrypt(
+        &mut self,
+        associated_data: &[u8],
+        packet: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, Error> {
+        if packet.len() < OVERHEAD {
+            return Err(Error::InvalidPacket);
+        }
+        let (seq_bytes, rest) = packet.split_at(SEQ_SIZE);
+        let (ciphertext, tag) = rest.split_at(rest.len() - TAG_SIZE);
+        let seq = u64::from_le_bytes(seq_bytes.try_into().unwrap());
+
+        if self.is_replay(seq) {
+            return Err(Error::Replay);
+        }
+
+        let out = out
+            .get_mut(..ciphertext.len())
+            .ok_or(Error::BufferTooSmall)?;
+        out.copy_from_slice(ciphertext);
+        let tag = tag.try_into().unwrap();
+        if decrypt_in_place(&self.key, &nonce(seq), associated_data, out, tag).is_err() {
+            out.fill(0);
+            return Err(Error::AuthenticationFailed);
+        }
+
+        self.mark_received(seq);
+        Ok(out.len())
+    }
+
+    /// Whether `seq` was already received or is older than the window.
+    fn is_replay(&self, seq: u64) -> bool {
+        let Some(highest_seq) = self.highest_seq else {
+            return false;
+        };
+        if seq > highest_seq {
+            return false;
+        }
+
+        let age = highest_seq - seq;
+        age >= Self::WINDOW || self.window[(age / 64) as usize] & (1 << (age % 64)) != 0
+    }
+
+    fn mark_received(&mut self, seq: u64) {
+        let highest_seq = match self.highest_seq {
+            Some(highest_seq) if seq <= highest_seq => highest_seq,
+            Some(highest_seq) => {
+                self.shift_window(seq - highest_seq);
+                self.highest_seq = Some(seq);
+                seq
+            }
+            None => {
+                self.highest_seq = Some(seq);
+                seq
+            }
+        };
+
+        let age = highest_seq - seq;
+        self.window[(age / 64) as usize] |= 1 << (age % 64);
+    }
+
+    /// Age all entries by `shift` sequence numbers, dropping those that leave the window.
+    fn shift_window(&mut self, shift: u64) {
+        if shift >= Self::WINDOW {
+            self.window = [0; WORDS];
+            return;
+        }
+
+        let words = (shift / 64) as usize;
+        let bits = (shift % 64) as u32;
+        for i in (0..WORDS).rev() {
+            let low = i.checked_sub(words).map_or(0, |j| self.window[j]);
+            let carry = match i.checked_sub(words + 1) {
+                Some(j) if bits > 0 => self.window[j] >> (64 - bits),
+                _ => 0,
+            };
+            self.window[i] = (low << bits) | carry;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,298 @@
+extern crate std;
+use super::*;
+use std::vec;
+use std::vec::Vec;
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+const SECRET: &[u8] = &[0x42; 32];
+const SESSION_ID: &[u8] = &[0x24; 32];
+
+fn pair<const WORDS: usize>() -> (Sender, Receiver<WORDS>) {
+    (
+        Sender::new(SECRET, SESSION_ID, Role::Initiator),
+        Receiver::new(SECRET, SESSION_ID, Role::Responder),
+    )
+}
+
+fn send(sender: &mut Sender, payload: &[u8]) -> Vec<u8> {
+    let mut packet = vec![0u8; payload.len() + OVERHEAD];
+    let len = sender.encrypt(b"ad", payload, &mut packet).unwrap();
+    assert_eq!(len, packet.len());
+    packet
+}
+
+fn receive<const WORDS: usize>(
+    receiver: &mut Receiver<WORDS>,
+    packet: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let mut payload = vec![0u8; packet.len().saturating_sub(OVERHEAD)];
+    let len = receiver.decrypt(b"ad", packet, &mut payload)?;
+    payload.truncate(len);
+    Ok(payload)
+}
+
+#[test]
+fn test_roundtrip_both_directions() {
+    let mut initiator = Sender::new(SECRET, SESSION_ID, Role::Initiator);
+    let mut initiator_rx: Receiver = Receiver::new(SECRET, SESSION_ID, Role::Initiator);
+    let mut responder = Sender::new(SECRET, SESSION_ID, Role::Responder);
+    let mut responder_rx: Receiver = Receiver::new(SECRET, SESSION_ID, Role::Responder);
+
+    for len in 0..=40 {
+        let payload: Vec<u8> = (0..len).map(|i| (i * 7) as u8).collect();
+
+        let packet = send(&mut initiator, &payload);
+        assert_eq!(receive(&mut responder_rx, &packet).unwrap(), payload);
+
+        let packet = send(&mut responder, &payload);
+        assert_eq!(receive(&mut initiator_rx, &packet).unwrap(), payload);
+    }
+    assert_eq!(initiator.next_seq(), 41);
+    assert_eq!(responder_rx.highest_seq(), Some(40));
+}
+
+#[test]
+fn test_packet_layout() {
+    let (mut sender, _) = pair::<1>();
+    for seq in 0..3u64 {
+        let packet = send(&mut sender, b"Hello");
+        assert_eq!(packet.len(), 5 + SEQ_SIZE + TAG_SIZE);
+        assert_eq!(packet[..SEQ_SIZE], seq.to_le_bytes());
+    }
+}
+
+#[test]
+fn test_directions_use_different_keys() {
+    let mut initiator = Sender::new(SECRET, SESSION_ID, Role::Initiator);
+    let mut responder = Sender::new(SECRET, SESSION_ID, Role::Responder);
+    assert_ne!(
+        send(&mut initiator, b"Hello"),
+        send(&mut responder, b"Hello")
+    );
+
+    // A packet is not accepted by the sender's own receiving half.
+    let mut initiator_rx: Receiver = Receiver::new(SECRET, SESSION_ID, Role::Initiator);
+    let packet = send(&mut initiator, b"Hello");
+    assert_eq!(
+        receive(&mut initiator_rx, &packet),
+        Err(Error::AuthenticationFailed)
+    );
+}
+
+#[test]
+fn test_session_ids_use_different_keys() {
+    let mut first = Sender::new(SECRET, b"first session", Role::Initiator);
+    let mut second = Sender::new(SECRET, b"second session", Role::Initiator);
+
+    // Same secret, role, sequence number and payload, but no shared keystream.
+    let payload = [0u8; 64];
+    let first_packet = send(&mut first, &payload);
+    let second_packet = send(&mut second, &payload);
+    assert_eq!(first_packet[..SEQ_SIZE], second_packet[..SEQ_SIZE]);
+    assert_ne!(
+        first_packet[SEQ_SIZE..SEQ_SIZE + 64],
+        second_packet[SEQ_SIZE..SEQ_SIZE + 64]
+    );
+
+    let mut receiver: Receiver = Receiver::new(SECRET, b"second session", Role::Responder);
+    assert_eq!(
+        receive(&mut receiver, &first_packet),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(receive(&mut receiver, &second_packet).unwrap(), payload);
+}
+
+#[test]
+fn test_wrong_secret_or_associated_data() {
+    let (mut sender, _) = pair::<1>();
+    let packet = send(&mut sender, b"Hello");
+
+    let mut receiver: Receiver = Receiver::new(&[0x43; 32], SESSION_ID, Role::Responder);
+    assert_eq!(
+        receive(&mut receiver, &packet),
+        Err(Error::AuthenticationFailed)
+    );
+
+    let mut receiver: Receiver = Receiver::new(SECRET, SESSION_ID, Role::Responder);
+    let mut payload = [0xffu8; 5];
+    assert_eq!(
+        receiver.decrypt(b"other", &packet, &mut payload),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(payload, [0; 5], "output must be zeroed on failure");
+}
+
+#[test]
+fn test_tampering_rejected_and_window_unchanged() {
+    let (mut sender, mut receiver) = pair::<1>();
+    let packet = send(&mut sender, b"Hello");
+
+    for i in 0..packet.len() {
+        let mut tampered = packet.clone();
+        tampered[i] ^= 1;
+        assert!(receive(&mut receiver, &tampered).is_err(), "byte {i}");
+        assert_eq!(receiver.highest_seq(), None);
+    }
+
+    assert_eq!(receive(&mut receiver, &packet).unwrap(), b"Hello");
+}
+
+#[test]
+fn test_forged_sequence_does_not_advance_window() {
+    let (mut sender, mut receiver) = pair::<1>();
+    let packet = send(&mut sender, b"Hello");
+
+    let mut forged = packet.clone();
+    forged[..SEQ_SIZE].copy_from_slice(&1000u64.to_le_bytes());
+    assert_eq!(
+        receive(&mut receiver, &forged),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(receive(&mut receiver, &packet).unwrap(), b"Hello");
+}
+
+#[test]
+fn test_replay_rejected() {
+    let (mut sender, mut receiver) = pair::<1>();
+    let packets: Vec<_> = (0..3).map(|_| send(&mut sender, b"Hello")).collect();
+
+    for packet in &packets {
+        receive(&mut receiver, packet).unwrap();
+    }
+    for packet in &packets {
+        assert_eq!(receive(&mut receiver, packet), Err(Error::Replay));
+    }
+}
+
+#[test]
+fn test_out_of_order_within_window() {
+    let (mut sender, mut receiver) = pair::<1>();
+    let packets: Vec<_> = (0..64).map(|_| send(&mut sender, b"Hello")).collect();
+
+    receive(&mut receiver, &packets[63]).unwrap();
+    for packet in packets[..63].iter().rev() {
+        receive(&mut receiver, packet).unwrap();
+    }
+    for packet in &packets {
+        assert_eq!(receive(&mut receiver, packet), Err(Error::Replay));
+    }
+    assert_eq!(receiver.highest_seq(), Some(63));
+}
+
+#[test]
+fn test_too_old_rejected() {
+    let (mut sender, mut receiver) = pair::<1>();
+    let packets: Vec<_> = (0..66).map(|_| send(&mut sender, b"Hello")).collect();
+
+    receive(&mut receiver, &packets[65]).unwrap();
+    assert_eq!(receive(&mut receiver, &packets[0]), Err(Error::Replay));
+    assert_eq!(receive(&mut receiver, &packets[1]), Err(Error::Replay));
+    receive(&mut receiver, &packets[2]).unwrap();
+}
+
+#[test]
+fn test_window_sizes() {
+    assert_eq!(Receiver::<1>::WINDOW, 64);
+    assert_eq!(Receiver::<4>::WINDOW, 256);
+
+    let (mut sender, mut receiver) = pair::<4>();
+    let packets: Vec<_> = (0..300).map(|_| send(&mut sender, b"")).collect();
+
+    receive(&mut receiver, &packets[299]).unwrap();
+    assert_eq!(receive(&mut receiver, &packets[43]), Err(Error::Replay));
+    for packet in packets[44..299].iter().rev() {
+        receive(&mut receiver, packet).unwrap();
+    }
+    for packet in &packets {
+        assert_eq!(receive(&mut receiver, packet), Err(Error::Replay));
+    }
+}
+
+/// Compare the bitmap against a naive set of received sequence numbers while jumping ahead by
+/// varying amounts, covering shifts across word boundaries.
+#[test]
+fn test_window_matches_model() {
+    let (_, mut receiver) = pair::<3>();
+
+    let mut received = Vec::new();
+    let mut seq = 0u64;
+    for step in [1, 5, 63, 64, 65, 1, 127, 128, 2, 191, 192, 193, 3] {
+        seq += step;
+        receiver.mark_received(seq);
+        received.push(seq);
+
+        for old in 0..=seq {
+            let expected = received.contains(&old) || seq - old >= 192;
+            assert_eq!(receiver.is_replay(old), expected, "seq {seq} old {old}");
+            if !expected && old.is_multiple_of(17) {
+                receiver.mark_received(old);
+                received.push(old);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_first_packet_may_have_any_sequence() {
+    let (mut sender, mut receiver) = pair::<1>();
+    sender.next_seq = 1 << 40;
+    let packet = send(&mut sender, b"Hello");
+    assert_eq!(receive(&mut receiver, &packet).unwrap(), b"Hello");
+    assert_eq!(receiver.highest_seq(), Some(1 << 40));
+}
+
+#[test]
+fn test_sequence_exhausted() {
+    let (mut sender, mut receiver) = pair::<1>();
+    sender.next_seq = u64::MAX - 1;
+    let packet = send(&mut sender, b"Hello");
+    receive(&mut receiver, &packet).unwrap();
+
+    let mut out = [0u8; 5 + OVERHEAD];
+    assert_eq!(
+        sender.encrypt(b"ad", b"Hello", &mut out),
+        Err(Error::SequenceExhausted)
+    );
+    assert_eq!(sender.next_seq(), u64::MAX);
+}
+
+#[test]
+fn test_buffer_errors() {
+    let (mut sender, mut receiver) = pair::<1>();
+
+    let mut out = [0u8; 5 + OVERHEAD - 1];
+    assert_eq!(
+        sender.encrypt(b"ad", b"Hello", &mut out),
+        Err(Error::BufferTooSmall)
+    );
+    assert_eq!(sender.next_seq(), 0);
+
+    let packet = send(&mut sender, b"Hello");
+    let mut payload = [0u8; 4];
+    assert_eq!(
+        receiver.decrypt(b"ad", &packet, &mut payload),
+        Err(Error::BufferTooSmall)
+    );
+    assert_eq!(
+        receiver.decrypt(b"ad", &packet[..OVERHEAD - 1], &mut payload),
+        Err(Error::InvalidPacket)
+    );
+    assert_eq!(receive(&mut receiver, &packet).unwrap(), b"Hello");
+}
+
+#[test]
+fn test_known_packet() {
+    let mut sender = Sender::new(&[0x42; 32], &[0x24; 32], Role::Initiator);
+    let _ = send(&mut sender, b"");
+    let packet = send(&mut sender, b"Hello");
+    assert_eq!(
+        packet,
+        hex_to_bytes("010000000000000005e9aed71c01b355a87885e864fe763a40d24d2dfd")
+    );
+}
@@ -0,0 +1,335 @@
+//! # Strobe protocol framework
+//!
+//! [Strobe](https://strobe.sourceforge.io/specs/) v1.0.2 with the Gimli permutation as `F`
+//! instead of Keccak-f\[1600\]. The framing, operation flags and duplex modes follow the
+//! specification, only the sponge is smaller: with a 384-bit state and 128-bit security the rate
+//! is `R = 48 - 128/4 - 2 = 14` bytes. The security parameter is fixed to 128 bits, the state
+//! is too small for 256.
+//!
+//! Every operation takes a `more` flag for streaming: `ad(b"hello ", false)` followed by
+//! `ad(b"world", true)` is the same as `ad(b"hello world", false)`.
+//!
+//! # Usage
+//!
+//! ```
+//! use gimli_crypto::Strobe;
+//!
+//! let key = [7u8; 32];
+//! let mut data = *b"Secret message";
+//!
+//! let mut sender = Strobe::new(b"example protocol v1");
+//! sender.key(&key, false);
+//! sender.send_enc(&mut data, false);
+//! let mut mac = [0u8; 16];
+//! sender.send_mac(&mut mac, false);
+//!
+//! let mut receiver = Strobe::new(b"example protocol v1");
+//! receiver.key(&key, false);
+//! receiver.recv_enc(&mut data, false);
+//! receiver.recv_mac(&mac).expect("authentication failed");
+//!
+//! assert_eq!(&data, b"Secret message");
+//! ```
+
+use crate::gimli::{State, gimli};
+use crate::{AuthenticationFailed, TAG_SIZE};
+use subtle::{Choice, ConstantTimeEq};
+
+/// Version of the Strobe specification implemented.
+pub const STROBE_VERSION: &[u8] = b"1.0.2";
+
+/// Strobe rate `R` in bytes for Gimli at 128-bit security.
+const R: usize = 14;
+
+/// Flag: data moves inbound.
+const FLAG_I: u8 = 1 << 0;
+/// Flag: data is sent to or received from the application.
+const FLAG_A: u8 = 1 << 1;
+/// Flag: the operation uses cipher output.
+const FLAG_C: u8 = 1 << 2;
+/// Flag: data is sent to or received from the transport.
+const FLAG_T: u8 = 1 << 3;
+/// Flag: meta-operation, for framing and other metadata.
+const FLAG_M: u8 = 1 << 4;
+
+const OP_AD: u8 = FLAG_A;
+const OP_KEY: u8 = FLAG_A | FLAG_C;
+const OP_PRF: u8 = FLAG_I | FLAG_A | FLAG_C;
+const OP_SEND_CLR: u8 = FLAG_A | FLAG_T;
+const OP_RECV_CLR: u8 = FLAG_I | FLAG_A | FLAG_T;
+const OP_SEND_ENC: u8 = FLAG_A | FLAG_C | FLAG_T;
+const OP_RECV_ENC: u8 = FLAG_I | FLAG_A | FLAG_C | FLAG_T;
+const OP_SEND_MAC: u8 = FLAG_C | FLAG_T;
+const OP_RECV_MAC: u8 = FLAG_I | FLAG_C | FLAG_T;
+const OP_RATCHET: u8 = FLAG_C;
+
+/// Strobe object over the Gimli permutation.
+///
+/// Both parties of a protocol perform the same sequence of operations, with `send_*` on one side
+/// matching `recv_*` on the other. The role is fixed by the first transport operation. The state
+/// is zeroed on drop.
+///
+/// # Panics
+///
+/// Operations panic if `more` is set and the previous operation was a different one.
+#[derive(Clone)]
+pub struct Strobe {
+    state: State,
+    /// Position in the rate.
+    pos: usize,
+    /// Position after the header of the current operation, `pos_begin` in the specification.
+    pos_begin: usize,
+    /// Role taken on with the first transport operation, `None` before that.
+    is_receiver: Option<bool>,
+    /// Flags of the previous operation, for checking `more`.
+    prev_flags: Option<u8>,
+}
+
+impl Strobe {
+    /// Version string of this instantiation, the [`STROBE_VERSION`] of the specification with the
+    /// permutation and security level.
+    pub const VERSION: &'static [u8] = b"Strobe-Gimli-128/384-v1.0.2";
+
+    /// Create a Strobe object for the protocol identified by `protocol`.
+    pub fn new(protocol: &[u8]) -> Self {
+        // F([0x01, R + 2, 0x01, 0x00, 0x01, 0x60] || "STROBEv1.0.2")
+        let mut state = State::new();
+        let state_bytes = state.as_bytes_mut();
+        state_bytes[..6].copy_from_slice(&[0x01, R as u8 + 2, 0x01, 0x00, 0x01, 0x60]);
+        state_bytes[6..13].copy_from_slice(b"STROBEv");
+        state_bytes[13..18].copy_from_slice(STROBE_VERSION);
+        gimli(&mut state);
+
+        let mut strobe = Self {
+            state,
+            pos: 0,
+            pos_begin: 0,
+            is_receiver: None,
+            prev_flags: None,
+        };
+        strobe.meta_ad(protocol, false);
+        strobe
+    }
+
+    /// Mix associated data into the state, `AD`.
+    pub fn ad(&mut self, data: &[u8], more: bool) {
+        self.operate_no_mutate(OP_AD, data, more);
+    }
+
+    /// Mix associated data into the state as metadata, `meta_AD`.
+    pub fn meta_ad(&mut self, data: &[u8], more: bool) {
+        self.operate_no_mutate(OP_AD | FLAG_M, data, more);
+    }
+
+    /// Set a symmetric key, `KEY`.
+    pub fn key(&mut self, key: &[u8], more: bool) {
+        self.operate_no_mutate(OP_KEY, key, more);
+    }
+
+    /// Set a symmetric key as metadata, `meta_KEY`.
+    pub fn meta_key(&mut self, key: &[u8], more: bool) {
+        self.operate_no_mutate(OP_KEY | FLAG_M, key, more);
+    }
+
+    /// Fill `out` with pseudorandom data depending on the state, `PRF`.
+    pub fn prf(&mut self, out: &mut [u8], more: bool) {
+        self.operate(OP_PRF, out, more);
+    }
+
+    /// Fill `out` with pseudorandom data as metadata, `meta_PRF`.
+    pub fn meta_prf(&mut self, out: &mut [u8], more: bool) {
+        self.operate(OP_PRF | FLAG_M, out, more);
+    }
+
+    /// Send a plaintext message, `send_CLR`.
+    pub fn send_clr(&mut self, data: &[u8], more: bool) {
+        self.operate_no_mutate(OP_SEND_CLR, data, more);
+    }
+
+    /// Send plaintext metadata such as a length or a message type, `meta_send_CLR`.
+    pub fn meta_send_clr(&mut self, data: &[u8], more: bool) {
+        self.operate_no_mutate(OP_SEND_CLR | FLAG_M, data, more);
+    }
+
+    /// Receive a plaintext message, `recv_CLR`.
+    pub fn recv_clr(&mut self, data: &[u8], more: bool) {
+        self.operate_no_mutate(OP_RECV_CLR, data, more);
+    }
+
+    /// Receive plaintext metadata, `meta_recv_CLR`.
+    pub fn meta_recv_clr(&mut self, data: &[u8], more: bool) {
+        self.operate_no_mutate(OP_RECV_CLR | FLAG_M, data, more);
+    }
+
+    /// Encrypt `buffer` in-place for sending, `send_ENC`.
+    pub fn send_enc(&mut self, buffer: &mut [u8], more: bool) {
+        self.operate(OP_SEND_ENC, buffer, more);
+    }
+
+    /// Encrypt metadata in-place for sending, `meta_send_ENC`.
+    pub fn meta_send_enc(&mut self, buffer: &mut [u8], more: bool) {
+        self.operate(OP_SEND_ENC | FLAG_M, buffer, more);
+    }
+
+    /// Decrypt a received `buffer` in-place, `recv_ENC`.
+    ///
+    /// The plaintext is not authenticated until a following [`recv_mac`](Self::recv_mac)
+    /// succeeds.
+    pub fn recv_enc(&mut self, buffer: &mut [u8], more: bool) {
+        self.operate(OP_RECV_ENC, buffer, more);
+    }
+
+    /// Decrypt received metadata in-place, `meta_recv_ENC`.
+    pub fn meta_recv_enc(&mut self, buffer: &mut [u8], more: bool) {
+        self.operate(OP_RECV_ENC | FLAG_M, buffer, more);
+    }
+
+    /// Fill `mac` with a MAC of the transcript so far, `send_MAC`.
+    pub fn send_mac(&mut self, mac: &mut [u8], more: bool) {
+        self.operate(OP_SEND_MAC, mac, more);
+    }
+
+    /// Fill `mac` with a MAC of the transcript as metadata, `meta_send_MAC`.
+    pub fn meta_send_mac(&mut self, mac: &mut [u8], more: bool) {
+        self.operate(OP_SEND_MAC | FLAG_M, mac, more);
+    }
+
+    /// Verify a received MAC of the transcript in constant time, `recv_MAC`.
+    ///
+    /// MACs shorter than [`TAG_SIZE`] bytes are rejected without changing the transcript, so a
+    /// truncated MAC of attacker-controlled length cannot pass. The MAC cannot be streamed. On
+    /// failure the object should be discarded.
+    pub fn recv_mac(&mut self, mac: &[u8]) -> Result<(), AuthenticationFailed> {
+        self.generalized_recv_mac(OP_RECV_MAC, mac)
+    }
+
+    /// Verify a received MAC of the transcript as metadata, `meta_recv_MAC`.
+    ///
+    /// MACs shorter than [`TAG_SIZE`] bytes are rejected without changing the transcript.
+    pub fn meta_recv_mac(&mut self, mac: &[u8]) -> Result<(), AuthenticationFailed> {
+        self.generalized_recv_mac(OP_RECV_MAC | FLAG_M, mac)
+    }
+
+    /// Zero `len` bytes of the state to prevent rollback, `RATCHET`.
+    pub fn ratchet(&mut self, len: usize, more: bool) {
+        self.begin(OP_RATCHET, more);
+        self.for_each_byte(len, |state_byte, _| *state_byte = 0);
+    }
+
+    /// Zero `len` bytes of the state as a meta-operation, `meta_RATCHET`.
+    pub fn meta_ratchet(&mut self, len: usize, more: bool) {
+        self.begin(OP_RATCHET | FLAG_M, more);
+        self.for_each_byte(len, |state_byte, _| *state_byte = 0);
+    }
+
+    fn generalized_recv_mac(&mut self, flags: u8, mac: &[u8]) -> Result<(), AuthenticationFailed> {
+        if mac.len() < TAG_SIZE {
+            return Err(AuthenticationFailed);
+        }
+        self.begin(flags, false);
+
+        let mut equal = Choice::from(1);
+        self.for_each_byte(mac.len(), |state_byte, i| {
+            equal &= state_byte.ct_eq(&mac[i]);
+            *state_byte = mac[i];
+        });
+
+        if equal.into() {
+            Ok(())
+        } else {
+            Err(AuthenticationFailed)
+        }
+    }
+
+    /// Run an operation whose data is read and written.
+    fn operate(&mut self, flags: u8, data: &mut [u8], more: bool) {
+        self.begin(flags, more);
+
+        match flags & !FLAG_M {
+            OP_SEND_ENC => self.for_each_byte(data.len(), |state_byte, i| {
+                *state_byte ^= data[i];
+                data[i] = *state_byte;
+            }),
+            OP_SEND_MAC => self.for_each_byte(data.len(), |state_byte, i| data[i] = *state_byte),
+            OP_PRF => self.for_each_byte(data.len(), |state_byte, i| {
+                data[i] = *state_byte;
+                *state_byte = 0;
+            }),
+            OP_RECV_ENC => self.for_each_byte(data.len(), |state_byte, i| {
+                data[i] ^= *state_byte;
+                *state_byte ^= data[i];
+            }),
+            _ => unreachable!("operation does not write its data"),
+        }
+    }
+
+    /// Run an operation whose data is only read.
+    fn operate_no_mutate(&mut self, flags: u8, data: &[u8], more: bool) {
+        self.begin(flags, more);
+
+        if flags & FLAG_C != 0 {
+            self.for_each_byte(data.len(), |state_byte, i| *state_byte = data[i]);
+        } else {
+            self.for_each_byte(data.len(), |state_byte, i| *state_byte ^= data[i]);
+        }
+    }
+
+    /// Check the use of `more` and start a new operation unless it continues the previous one.
+    fn begin(&mut self, flags: u8, more: bool) {
+        if more {
+            assert_eq!(
+                self.prev_flags,
+                Some(flags),
+                "`more` can only continue the same operation"
+            );
+            return;
+        }
+        self.prev_flags = Some(flags);
+
+        let mut flags = flags;
+        if flags & FLAG_T != 0 {
+            // Flip the I flag for the receiver so that both sides agree, `flags ^= is_receiver`.
+            let is_inbound = flags & FLAG_I != 0;
+            let is_receiver = *self.is_receiver.get_or_insert(is_inbound);
+            if is_receiver {
+                flags ^= FLAG_I;
+            }
+        }
+
+        let old_pos_begin = self.pos_begin;
+        self.pos_begin = self.pos + 1;
+        let header = [old_pos_begin as u8, flags];
+        self.for_each_byte(header.len(), |state_byte, i| *state_byte ^= header[i]);
+
+        if flags & FLAG_C != 0 && self.pos != 0 {
+            self.run_f();
+        }
+    }
+
+    /// Call `f` with `len` consecutive state bytes and their index, running `F` whenever the
+    /// rate is full.
+    #[inline(always)]
+    fn for_each_byte(&mut self, len: usize, mut f: impl FnMut(&mut u8, usize)) {
+        for i in 0..len {
+            f(&mut self.state.as_bytes_mut()[self.pos], i);
+            self.pos += 1;
+            if self.pos == R {
+                self.run_f();
+            }
+        }
+    }
+
+    /// Pad with `pos_begin` and the cSHAKE padding and apply the permutation.
+    fn run_f(&mut self) {
+        let state_bytes = self.state.as_bytes_mut();
+        state_bytes[self.pos] ^= self.pos_begin as u8;
+        state_bytes[self.pos + 1] ^= 0x04;
+        state_bytes[R + 1] ^= 0x80;
+        gimli(&mut self.state);
+        self.pos = 0;
+        self.pos_begin = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,308 @@
+extern crate std;
+use super::*;
+use std::vec::Vec;
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+const PROTOCOL: &[u8] = b"gimli-crypto strobe tests";
+
+fn test_message(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 7) as u8).collect()
+}
+
+fn prf16(strobe: &mut Strobe) -> [u8; 16] {
+    let mut out = [0u8; 16];
+    strobe.prf(&mut out, false);
+    out
+}
+
+/// One message of a transcript, as sent on the wire.
+#[derive(Clone)]
+struct Message {
+    meta_len: [u8; 2],
+    clear: Vec<u8>,
+    meta_encrypted: [u8; 4],
+    ciphertext: Vec<u8>,
+    meta_mac: [u8; 16],
+    mac: [u8; 16],
+}
+
+fn send(strobe: &mut Strobe, clear: &[u8], plaintext: &[u8]) -> Message {
+    let meta_len = (plaintext.len() as u16).to_le_bytes();
+    strobe.meta_send_clr(&meta_len, false);
+    strobe.send_clr(clear, false);
+
+    let mut meta_encrypted = *b"type";
+    strobe.meta_send_enc(&mut meta_encrypted, false);
+    let mut ciphertext = plaintext.to_vec();
+    strobe.send_enc(&mut ciphertext, false);
+
+    let mut meta_mac = [0u8; 16];
+    strobe.meta_send_mac(&mut meta_mac, false);
+    let mut mac = [0u8; 16];
+    strobe.send_mac(&mut mac, false);
+
+    Message {
+        meta_len,
+        clear: clear.to_vec(),
+        meta_encrypted,
+        ciphertext,
+        meta_mac,
+        mac,
+    }
+}
+
+/// Receive `message`, returning the decrypted metadata and plaintext.
+fn receive(
+    strobe: &mut Strobe,
+    message: &Message,
+) -> Result<([u8; 4], Vec<u8>), AuthenticationFailed> {
+    strobe.meta_recv_clr(&message.meta_len, false);
+    strobe.recv_clr(&message.clear, false);
+
+    let mut meta = message.meta_encrypted;
+    strobe.meta_recv_enc(&mut meta, false);
+    let mut plaintext = message.ciphertext.clone();
+    strobe.recv_enc(&mut plaintext, false);
+
+    strobe.meta_recv_mac(&message.meta_mac)?;
+    strobe.recv_mac(&message.mac)?;
+    Ok((meta, plaintext))
+}
+
+/// Run the same setup on both sides: protocol, key, associated data, meta operations.
+fn handshake() -> (Strobe, Strobe) {
+    let mut a = Strobe::new(PROTOCOL);
+    let mut b = Strobe::new(PROTOCOL);
+    for strobe in [&mut a, &mut b] {
+        strobe.meta_ad(b"setup", false);
+        strobe.key(&[0x42; 32], false);
+        strobe.meta_key(&[0x24; 16], false);
+        strobe.ad(b"associated data", false);
+        strobe.meta_ratchet(8, false);
+    }
+    (a, b)
+}
+
+#[test]
+fn test_version() {
+    assert_eq!(Strobe::VERSION, b"Strobe-Gimli-128/384-v1.0.2");
+}
+
+#[test]
+fn test_transcript_roundtrip() {
+    let (mut alice, mut bob) = handshake();
+
+    for len in [0, 1, 13, 14, 15, 28, 100] {
+        let message = test_message(len);
+
+        let sent = send(&mut alice, b"clear header", &message);
+        assert_eq!(
+            receive(&mut bob, &sent),
+            Ok((*b"type", message.clone())),
+            "length {len}"
+        );
+
+        // Answer in the other direction.
+        let sent = send(&mut bob, b"reply", &message);
+        assert_eq!(
+            receive(&mut alice, &sent),
+            Ok((*b"type", message)),
+            "length {len}"
+        );
+    }
+
+    alice.ratchet(32, false);
+    bob.ratchet(32, false);
+    let mut meta = [0u8; 16];
+    let mut meta_bob = [0u8; 16];
+    alice.meta_prf(&mut meta, false);
+    bob.meta_prf(&mut meta_bob, false);
+    assert_eq!(meta, meta_bob);
+    assert_eq!(prf16(&mut alice), prf16(&mut bob));
+}
+
+#[test]
+fn test_recv_mac_failure() {
+    let message = test_message(30);
+    let (mut alice, _) = handshake();
+    let sent = send(&mut alice, b"clear header", &message);
+
+    let tampered: [fn(&mut Message); 6] = [
+        |m| m.meta_len[0] ^= 1,
+        |m| m.clear[0] ^= 1,
+        |m| m.ciphertext[29] ^= 1,
+        |m| m.meta_mac[7] ^= 1,
+        |m| m.mac[0] ^= 1,
+        |m| m.mac[15] ^= 0x80,
+    ];
+    for (i, tamper) in tampered.iter().enumerate() {
+        let mut message = sent.clone();
+        tamper(&mut message);
+        let (_, mut bob) = handshake();
+        assert_eq!(
+            receive(&mut bob, &message),
+            Err(AuthenticationFailed),
+            "case {i}"
+        );
+    }
+
+    // Different key.
+    let mut bob = Strobe::new(PROTOCOL);
+    bob.meta_ad(b"setup", false);
+    bob.key(&[0x43; 32], false);
+    assert_eq!(receive(&mut bob, &sent), Err(AuthenticationFailed));
+
+    // All-zero MAC.
+    let (_, mut bob) = handshake();
+    let mut message = sent.clone();
+    message.meta_mac = [0; 16];
+    assert_eq!(receive(&mut bob, &message), Err(AuthenticationFailed));
+}
+
+#[test]
+fn test_recv_mac_too_short() {
+    let (mut alice, _) = handshake();
+    let sent = send(&mut alice, b"clear header", b"Secret message");
+
+    for len in [0, TAG_SIZE - 1] {
+        let (_, mut reference) = handshake();
+        receive(&mut reference, &sent).unwrap();
+
+        // A short MAC is rejected and leaves the transcript unchanged.
+        let (_, mut bob) = handshake();
+        let mut message = sent.clone();
+        let mut plaintext = message.ciphertext.clone();
+        bob.meta_recv_clr(&message.meta_len, false);
+        bob.recv_clr(&message.clear, false);
+        bob.meta_recv_enc(&mut message.meta_encrypted, false);
+        bob.recv_enc(&mut plaintext, false);
+        assert_eq!(
+            bob.meta_recv_mac(&message.meta_mac[..len]),
+            Err(AuthenticationFailed),
+            "meta length {len}"
+        );
+        bob.meta_recv_mac(&message.meta_mac).unwrap();
+        assert_eq!(
+            bob.recv_mac(&message.mac[..len]),
+            Err(AuthenticationFailed),
+            "length {len}"
+        );
+        bob.recv_mac(&message.mac).unwrap();
+        assert_eq!(prf16(&mut bob), prf16(&mut reference));
+    }
+}
+
+#[test]
+fn test_streaming_matches_single_call() {
+    let data = test_message(50);
+
+    for split in [0, 1, 14, 20, 50] {
+        let (mut whole, _) = handshake();
+        let (mut streamed, _) = handshake();
+
+        whole.ad(&data, false);
+        streamed.ad(&data[..split], false);
+        streamed.ad(&data[split..], true);
+
+        whole.key(&data, false);
+        streamed.key(&data[..split], false);
+        streamed.key(&data[split..], true);
+
+        let mut ciphertext = data.clone();
+        whole.send_enc(&mut ciphertext, false);
+        let mut streamed_ciphertext = data.clone();
+        let (head, tail) = streamed_ciphertext.split_at_mut(split);
+        streamed.send_enc(head, false);
+        streamed.send_enc(tail, true);
+        assert_eq!(ciphertext, streamed_ciphertext, "split {split}");
+
+        whole.ratchet(50, false);
+        streamed.ratchet(split, false);
+        streamed.ratchet(50 - split, true);
+
+        let mut out = [0u8; 50];
+        let mut streamed_out = [0u8; 50];
+        whole.prf(&mut out, false);
+        streamed.prf(&mut streamed_out[..split], false);
+        streamed.prf(&mut streamed_out[split..], true);
+        assert_eq!(out, streamed_out, "split {split}");
+    }
+}
+
+#[test]
+fn test_operations_are_domain_separated() {
+    let data = test_message(20);
+    let mut outputs: Vec<[u8; 16]> = Vec::new();
+
+    let mut push = |f: &dyn Fn(&mut Strobe)| {
+        let mut strobe = Strobe::new(PROTOCOL);
+        f(&mut strobe);
+        let out = prf16(&mut strobe);
+        assert!(!outputs.contains(&out));
+        outputs.push(out);
+    };
+
+    push(&|_| {});
+    push(&|s| s.ad(&data, false));
+    push(&|s| s.meta_ad(&data, false));
+    push(&|s| s.key(&data, false));
+    push(&|s| s.meta_key(&data, false));
+    push(&|s| s.send_clr(&data, false));
+    push(&|s| s.meta_send_clr(&data, false));
+    push(&|s| {
+        s.ad(&data[..10], false);
+        s.ad(&data[10..], false);
+    });
+    push(&|s| s.ratchet(14, false));
+    push(&|s| s.meta_ratchet(14, false));
+    push(&|s| s.prf(&mut [0u8; 16], false));
+
+    let mut other = Strobe::new(b"another protocol");
+    assert!(!outputs.contains(&prf16(&mut other)));
+}
+
+#[test]
+fn test_prf_erases_output() {
+    let (mut strobe, _) = handshake();
+    let first = prf16(&mut strobe);
+    let second = prf16(&mut strobe);
+    assert_ne!(first, second);
+}
+
+#[test]
+#[should_panic(expected = "`more` can only continue the same operation")]
+fn test_more_after_different_operation() {
+    let mut strobe = Strobe::new(PROTOCOL);
+    strobe.ad(b"data", false);
+    strobe.key(b"key", true);
+}
+
+#[test]
+#[should_panic(expected = "`more` can only continue the same operation")]
+fn test_more_after_meta_operation() {
+    let mut strobe = Strobe::new(PROTOCOL);
+    strobe.meta_ad(b"data", false);
+    strobe.ad(b"data", true);
+}
+
+#[test]
+fn test_transcript_output_is_stable() {
+    let (mut alice, _) = handshake();
+    let sent = send(&mut alice, b"clear header", b"Secret message");
+
+    assert_eq!(sent.meta_encrypted, [0x03, 0x30, 0x0a, 0xd0]);
+    assert_eq!(
+        sent.ciphertext,
+        hex_to_bytes("14a133d36f10b00fa824dd2200c6")
+    );
+    assert_eq!(
+        &sent.mac[..],
+        &hex_to_bytes("b064f6b933dd5a915dd59f6d9bbc5360")[..]
+    );
+}
@@ -0,0 +1,136 @@
+//! # Tree hashing
+//!
+//! A tree hash mode over `hash/gimli24v1` in the spirit of KangarooTwelve and ParallelHash for
+//! large inputs. The input is split into chunks of [`CHUNK_SIZE`] bytes, each chunk is hashed
+//! independently into a chaining value `H(0x00 || chunk)` and the digest is
+//! `H(0x01 || cv_0 || ... || cv_n-1 || len)` with the input length as a little-endian 64-bit
+//! integer. The chunks can therefore be hashed in any order or in parallel.
+//!
+//! [`hash`] and [`Hasher`] compute the digest sequentially in `no_std`, with the `std` feature
+//! `hash_parallel` hashes the chunks on the `rayon` thread pool. All produce the same digest,
+//! which differs from the plain [`crate::hash`] of the same input.
+//!
+//! # Usage
+//!
+//! ```
+//! use gimli_crypto::tree_hash::{self, Hasher};
+//!
+//! let data = [7u8; 20_000];
+//! let digest = tree_hash::hash(&data);
+//!
+//! let mut hasher = Hasher::new();
+//! for part in data.chunks(1000) {
+//!     hasher.update(part);
+//! }
+//! assert_eq!(hasher.finalize(), digest);
+//! ```
+
+use crate::HASH_SIZE;
+
+/// Size of the chunks hashed independently in bytes.
+pub const CHUNK_SIZE: usize = 8192;
+
+const CHUNK_PREFIX: u8 = 0x00;
+const ROOT_PREFIX: u8 = 0x01;
+
+fn chunk_hasher() -> crate::Hasher {
+    let mut hasher = crate::Hasher::new();
+    hasher.update(&[CHUNK_PREFIX]);
+    hasher
+}
+
+fn root_hasher() -> crate::Hasher {
+    let mut hasher = crate::Hasher::new();
+    hasher.update(&[ROOT_PREFIX]);
+    hasher
+}
+
+/// Chaining value of one chunk.
+fn chaining_value(chunk: &[u8]) -> [u8; HASH_SIZE] {
+    let mut hasher = chunk_hasher();
+    hasher.update(chunk);
+    hasher.finalize()
+}
+
+/// Tree hash of `input`.
+pub fn hash(input: &[u8]) -> [u8; HASH_SIZE] {
+    let mut root = root_hasher();
+    for chunk in input.chunks(CHUNK_SIZE) {
+        root.update(&chaining_value(chunk));
+    }
+    root.update(&(input.len() as u64).to_le_bytes());
+    root.finalize()
+}
+
+/// Tree hash of `input`, hashing the chunks in parallel.
+#[cfg(feature = "std")]
+pub fn hash_parallel(input: &[u8]) -> [u8; HASH_SIZE] {
+    use rayon::prelude::*;
+    use std::vec::Vec;
+
+    let chaining_values: Vec<[u8; HASH_SIZE]> =
+        input.par_chunks(CHUNK_SIZE).map(chaining_value).collect();
+
+    let mut root = root_hasher();
+    root.update(chaining_values.as_flattened());
+    root.update(&(input.len() as u64).to_le_bytes());
+    root.finalize()
+}
+
+/// Hasher for incremental tree hashing, e.g. of inputs larger than RAM.
+#[derive(Clone)]
+pub struct Hasher {
+    root: crate::Hasher,
+    chunk: crate::Hasher,
+    /// Bytes in the current chunk.
+    chunk_len: usize,
+    len: u64,
+}
+
+impl Hasher {
+    /// Create a new hasher.
+    pub fn new() -> Self {
+        Self {
+            root: root_hasher(),
+            chunk: chunk_hasher(),
+            chunk_len: 0,
+            len: 0,
+        }
+    }
+
+    /// Update the hasher with more data.
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.len += data.len() as u64;
+
+        while !data.is_empty() {
+            let (head, rest) = data.split_at(data.len().min(CHUNK_SIZE - self.chunk_len));
+            self.chunk.update(head);
+            self.chunk_len += head.len();
+            data = rest;
+
+            if self.chunk_len == CHUNK_SIZE {
+                let chunk = core::mem::replace(&mut self.chunk, chunk_hasher());
+                self.root.update(&chunk.finalize());
+                self.chunk_len = 0;
+            }
+        }
+    }
+
+    /// Finalize the hash and return the digest.
+    pub fn finalize(mut self) -> [u8; HASH_SIZE] {
+        if self.chunk_len > 0 {
+            self.root.update(&self.chunk.finalize());
+        }
+        self.root.update(&self.len.to_le_bytes());
+        self.root.finalize()
+    }
+}
+
+impl Default for Hasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests;
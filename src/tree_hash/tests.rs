@@ -0,0 +1,110 @@
+extern crate std;
+use super::*;
+use std::vec::Vec;
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn input(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8).collect()
+}
+
+// Under miri, only test a few lengths to keep test time reasonable.
+#[cfg(miri)]
+const LENGTHS: &[usize] = &[0, 1, CHUNK_SIZE + 1];
+#[cfg(not(miri))]
+const LENGTHS: &[usize] = &[
+    0,
+    1,
+    CHUNK_SIZE - 1,
+    CHUNK_SIZE,
+    CHUNK_SIZE + 1,
+    2 * CHUNK_SIZE,
+    3 * CHUNK_SIZE + 17,
+];
+
+#[test]
+fn test_known_answers() {
+    let cases = [
+        (
+            0,
+            "faa1e60f767dccefa535c78148318df75da1d237da681787d966feba76e1f5ec",
+        ),
+        (
+            1,
+            "ab1d6d46d851fe04cad7b094450f33a8e7358fdc181b9af3495c6ac8e9156af4",
+        ),
+        (
+            CHUNK_SIZE + 1,
+            "8db5f8f20940b85e03ad194854e57a87d05fd865f0b6e4ec7e2a144c7137aa93",
+        ),
+    ];
+    for (len, expected) in cases {
+        assert_eq!(hash(&input(len)).to_vec(), hex_to_bytes(expected), "{len}");
+    }
+}
+
+#[test]
+fn test_chunk_structure() {
+    for &len in LENGTHS {
+        let input = input(len);
+        let mut root = root_hasher();
+        for chunk in input.chunks(CHUNK_SIZE) {
+            let mut hasher = crate::Hasher::new();
+            hasher.update(&[0x00]);
+            hasher.update(chunk);
+            root.update(&hasher.finalize());
+        }
+        root.update(&(len as u64).to_le_bytes());
+        assert_eq!(hash(&input), root.finalize(), "{len}");
+    }
+}
+
+#[test]
+fn test_incremental() {
+    for &len in LENGTHS {
+        let input = input(len);
+        let expected = hash(&input);
+
+        for part_size in [1, 100, CHUNK_SIZE - 1, CHUNK_SIZE, CHUNK_SIZE + 3] {
+            // Under miri, only test one split within and one across chunks.
+            if cfg!(miri) && ![100, CHUNK_SIZE + 3].contains(&part_size) {
+                continue;
+            }
+            let mut hasher = Hasher::new();
+            for part in input.chunks(part_size) {
+                hasher.update(part);
+            }
+            assert_eq!(hasher.finalize(), expected, "{len} in parts of {part_size}");
+        }
+
+        let mut hasher = Hasher::default();
+        hasher.update(&[]);
+        hasher.update(&input);
+        hasher.update(&[]);
+        assert_eq!(hasher.finalize(), expected);
+    }
+}
+
+#[test]
+fn test_domain_separation() {
+    // Differs from the plain hash, and the length is bound for inputs ending in zeros.
+    for &len in LENGTHS {
+        assert_ne!(hash(&input(len)), crate::hash(&input(len)));
+    }
+    assert_ne!(hash(&[0; 10]), hash(&[0; 11]));
+    assert_ne!(hash(&input(CHUNK_SIZE)), hash(&input(CHUNK_SIZE + 1)));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_parallel() {
+    for &len in LENGTHS {
+        let input = input(len);
+        assert_eq!(hash_parallel(&input), hash(&input), "{len}");
+    }
+}
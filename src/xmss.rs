@@ -0,0 +1,404 @@
+//! # XMSS hash-based signatures
+//!
+//! Stateful post-quantum signatures from `hash/gimli24v1`, e.g. for firmware signing. A
+//! [`SigningKey`] of height `H` is a Merkle tree over `2^H` [`wots`] one-time keys and signs at
+//! most `2^H` messages, each with the next unused leaf. Security rests only on the hash function.
+//!
+//! The construction follows XMSS (RFC 8391) in the "simple" style of SPHINCS+: every hash call
+//! is a tweakable hash keyed with the public seed and the position of the call, chain starts come
+//! from a PRF of the secret seed, the signature randomness from a PRF of the message key, and the
+//! WOTS+ public key is compressed with a single tweakable hash instead of an L-tree. It is not
+//! interoperable with RFC 8391 implementations.
+//!
+//! **The signing key is state.** [`SigningKey::sign`] advances the leaf index before returning the
+//! signature. Persist the key with [`SigningKey::to_bytes`] before the signature leaves the
+//! device, and never restore an older copy or clone the key, signing twice with one leaf breaks
+//! security.
+//!
+//! Key generation and every signature recompute the tree, `2^H` one-time public keys of about
+//! 1000 hash calls each, so the height trades the number of signatures against signing time. A
+//! release build on an x86-64 desktop core takes about 1 ms per leaf, about a second per
+//! signature at [`MAX_HEIGHT`] and half of that for every height below. Verification costs about
+//! 1000 hash calls regardless of the height and needs no allocation.
+//!
+//! # Usage
+//!
+//! ```
+//! # #[cfg(not(miri))] {
+//! use gimli_crypto::xmss::{Signature, SigningKey, VerifyingKey, signature_size};
+//!
+//! // 2^4 = 16 signatures, use random seeds, e.g. `SigningKey::generate`.
+//! let mut signing_key = SigningKey::<4>::new(&[1u8; 32], &[2u8; 32], &[3u8; 32]);
+//! let verifying_key = signing_key.verifying_key();
+//!
+//! let signature = signing_key.sign(b"firmware v1.2.3").unwrap();
+//! let persisted = signing_key.to_bytes(); // Store before publishing the signature
+//! let signing_key = SigningKey::<4>::from_bytes(&persisted).unwrap();
+//! assert_eq!(signing_key.remaining(), 15);
+//!
+//! let mut encoded = [0u8; signature_size(4)];
+//! signature.encode(&mut encoded).unwrap();
+//!
+//! // On the device.
+//! let verifying_key = VerifyingKey::<4>::from_bytes(&verifying_key.to_bytes());
+//! let signature = Signature::<4>::decode(&encoded).unwrap();
+//! assert!(verifying_key.verify(b"firmware v1.2.3", &signature).is_ok());
+//! # }
+//! ```
+
+use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+mod hash;
+pub mod wots;
+
+use hash::{Address, h_msg, prf_msg, thash};
+
+pub use signature; // For `SigningKey` and `VerifyingKey` users
+
+/// Hash output size in bytes, the size of seeds and tree nodes.
+pub const N: usize = 32;
+
+/// Largest supported tree height, 1024 signatures.
+///
+/// Signing recomputes the whole tree, so the cost doubles with every height, see the module docs.
+pub const MAX_HEIGHT: usize = 10;
+
+/// Size of an encoded verifying key in bytes, `root || pub_seed`.
+pub const PUBLIC_KEY_SIZE: usize = 2 * N;
+
+/// Size of an encoded signing key in bytes, `sk_seed || sk_prf || pub_seed || root || index`.
+pub const SIGNING_KEY_SIZE: usize = 4 * N + 4;
+
+/// Size of an encoded signature of a tree of `height` in bytes.
+pub const fn signature_size(height: usize) -> usize {
+    4 + N + wots::SIGNATURE_SIZE + height * N
+}
+
+/// Errors of the signature types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// Every leaf of the signing key has been used.
+    KeyExhausted,
+    /// An encoded key or signature has the wrong length or an out of range index.
+    InvalidEncoding,
+    /// The output buffer is too small for the result.
+    BufferTooSmall,
+    /// The signature is not valid for the message and key.
+    VerificationFailed,
+}
+
+#[cfg(feature = "getrandom")]
+fn random_seeds<const COUNT: usize>() -> [[u8; N]; COUNT] {
+    use aead::rand_core::RngCore;
+
+    let mut seeds = [[0u8; N]; COUNT];
+    aead::OsRng.fill_bytes(seeds.as_flattened_mut());
+    seeds
+}
+
+/// Compute the root of the tree and the authentication path of `leaf`.
+///
+/// Keeps one node per height on the stack, so memory is bounded by [`MAX_HEIGHT`].
+fn treehash<const H: usize>(
+    sk_seed: &[u8; N],
+    pub_seed: &[u8; N],
+    leaf: u32,
+    auth_path: &mut [[u8; N]; H],
+) -> [u8; N] {
+    let mut stack = [([0u8; N], 0u32); MAX_HEIGHT + 1];
+    let mut len = 0;
+
+    for index in 0..1u32 << H {
+        let mut node = wots::public_key(sk_seed, pub_seed, index);
+        let mut height = 0;
+        if index == leaf ^ 1 {
+            auth_path[0] = node;
+        }
+
+        while len > 0 && stack[len - 1].1 == height {
+            len -= 1;
+            let parent = index >> (height + 1);
+            node = thash(
+                pub_seed,
+                Address::tree(height, parent),
+                &[&stack[len].0, &node],
+            );
+            height += 1;
+            if (height as usize) < H && parent == (leaf >> height) ^ 1 {
+                auth_path[height as usize] = node;
+            }
+        }
+
+        stack[len] = (node, height);
+        len += 1;
+    }
+    stack[0].0
+}
+
+/// An XMSS signing key of height `H`, signing up to `2^H` messages.
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct SigningKey<const H: usize> {
+    sk_seed: [u8; N],
+    sk_prf: [u8; N],
+    pub_seed: [u8; N],
+    root: [u8; N],
+    /// Leaf of the next signature.
+    next_index: u32,
+}
+
+impl<const H: usize> SigningKey<H> {
+    /// Number of signatures of a key.
+    pub const MAX_SIGNATURES: u32 = 1 << H;
+
+    /// Create a key from the secret seed, the message key and the public seed.
+    ///
+    /// Computes the whole tree, see the module docs for the cost.
+    pub fn new(sk_seed: &[u8; N], sk_prf: &[u8; N], pub_seed: &[u8; N]) -> Self {
+        const {
+            assert!(
+                H > 0 && H <= MAX_HEIGHT,
+                "the height must be 1 to MAX_HEIGHT"
+            )
+        };
+
+        Self {
+            sk_seed: *sk_seed,
+            sk_prf: *sk_prf,
+            pub_seed: *pub_seed,
+            root: treehash(sk_seed, pub_seed, 0, &mut [[0u8; N]; H]),
+            next_index: 0,
+        }
+    }
+
+    /// Create a key from random seeds.
+    #[cfg(feature = "getrandom")]
+    pub fn generate() -> Self {
+        let mut seeds = random_seeds::<3>();
+        let key = Self::new(&seeds[0], &seeds[1], &seeds[2]);
+        seeds.zeroize();
+        key
+    }
+
+    /// Restore a key with its state from [`to_bytes`](Self::to_bytes).
+    ///
+    /// The stored root is trusted, it is not recomputed.
+    pub fn from_bytes(bytes: &[u8; SIGNING_KEY_SIZE]) -> Result<Self, Error> {
+        const {
+            assert!(
+                H > 0 && H <= MAX_HEIGHT,
+                "the height must be 1 to MAX_HEIGHT"
+            )
+        };
+
+        let field = |i: usize| -> [u8; N] { bytes[i * N..(i + 1) * N].try_into().unwrap() };
+        let next_index = u32::from_le_bytes(bytes[4 * N..].try_into().unwrap());
+        if next_index > Self::MAX_SIGNATURES {
+            return Err(Error::InvalidEncoding);
+        }
+
+        Ok(Self {
+            sk_seed: field(0),
+            sk_prf: field(1),
+            pub_seed: field(2),
+            root: field(3),
+            next_index,
+        })
+    }
+
+    /// Encode the key and its state.
+    pub fn to_bytes(&self) -> [u8; SIGNING_KEY_SIZE] {
+        let mut bytes = [0u8; SIGNING_KEY_SIZE];
+        for (chunk, field) in
+            bytes
+                .chunks_exact_mut(N)
+                .zip([&self.sk_seed, &self.sk_prf, &self.pub_seed, &self.root])
+        {
+            chunk.copy_from_slice(field);
+        }
+        bytes[4 * N..].copy_from_slice(&self.next_index.to_le_bytes());
+        bytes
+    }
+
+    /// Leaf index of the next signature.
+    pub fn next_index(&self) -> u32 {
+        self.next_index
+    }
+
+    /// Number of signatures left.
+    pub fn remaining(&self) -> u32 {
+        Self::MAX_SIGNATURES - self.next_index
+    }
+
+    /// The public key verifying signatures of this key.
+    pub fn verifying_key(&self) -> VerifyingKey<H> {
+        VerifyingKey {
+            root: self.root,
+            pub_seed: self.pub_seed,
+        }
+    }
+
+    /// Sign `message` with the next leaf, advancing the state.
+    ///
+    /// Persist the key before publishing the signature, see the module docs.
+    pub fn sign(&mut self, message: &[u8]) -> Result<Signature<H>, Error> {
+        let index = self.next_index;
+        if index >= Self::MAX_SIGNATURES {
+            return Err(Error::KeyExhausted);
+        }
+        self.next_index = index + 1;
+
+        let randomness = prf_msg(&self.sk_prf, index);
+        let digest = h_msg(&randomness, &self.root, index, message);
+        let mut auth_path = [[0u8; N]; H];
+        treehash(&self.sk_seed, &self.pub_seed, index, &mut auth_path);
+
+        Ok(Signature {
+            index,
+            randomness,
+            wots: wots::sign_digest(&self.sk_seed, &self.pub_seed, index, &digest),
+            auth_path,
+        })
+    }
+}
+
+impl<const H: usize> signature::SignerMut<Signature<H>> for SigningKey<H> {
+    fn try_sign(&mut self, message: &[u8]) -> Result<Signature<H>, signature::Error> {
+        self.sign(message).map_err(|_| signature::Error::new())
+    }
+}
+
+impl<const H: usize> signature::Keypair for SigningKey<H> {
+    type VerifyingKey = VerifyingKey<H>;
+
+    fn verifying_key(&self) -> VerifyingKey<H> {
+        self.verifying_key()
+    }
+}
+
+/// An XMSS verifying key of height `H`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifyingKey<const H: usize> {
+    root: [u8; N],
+    pub_seed: [u8; N],
+}
+
+impl<const H: usize> VerifyingKey<H> {
+    /// Encode as `root || pub_seed`.
+    pub fn to_bytes(&self) -> [u8; PUBLIC_KEY_SIZE] {
+        let mut bytes = [0u8; PUBLIC_KEY_SIZE];
+        bytes[..N].copy_from_slice(&self.root);
+        bytes[N..].copy_from_slice(&self.pub_seed);
+        bytes
+    }
+
+    /// Decode from `root || pub_seed`.
+    pub fn from_bytes(bytes: &[u8; PUBLIC_KEY_SIZE]) -> Self {
+        let (root, pub_seed) = bytes.split_at(N);
+        Self {
+            root: root.try_into().unwrap(),
+            pub_seed: pub_seed.try_into().unwrap(),
+        }
+    }
+
+    /// Check `signature` of `message`.
+    pub fn verify(&self, message: &[u8], signature: &Signature<H>) -> Result<(), Error> {
+        let index = signature.index;
+        let digest = h_msg(&signature.randomness, &self.root, index, message);
+        let mut node =
+            wots::public_key_from_signature(&self.pub_seed, index, &digest, &signature.wots);
+
+        for (height, sibling) in signature.auth_path.iter().enumerate() {
+            let address = Address::tree(height as u32, index >> (height + 1));
+            node = if (index >> height) & 1 == 0 {
+                thash(&self.pub_seed, address, &[&node, sibling])
+            } else {
+                thash(&self.pub_seed, address, &[sibling, &node])
+            };
+        }
+
+        if bool::from(node.ct_eq(&self.root)) {
+            Ok(())
+        } else {
+            Err(Error::VerificationFailed)
+        }
+    }
+}
+
+impl<const H: usize> signature::Verifier<Signature<H>> for VerifyingKey<H> {
+    fn verify(&self, message: &[u8], signature: &Signature<H>) -> Result<(), signature::Error> {
+        VerifyingKey::verify(self, message, signature).map_err(|_| signature::Error::new())
+    }
+}
+
+/// An XMSS signature of a tree of height `H`.
+///
+/// Encoded as `index || randomness || wots_signature || auth_path` with the index in little-endian.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature<const H: usize> {
+    index: u32,
+    randomness: [u8; N],
+    wots: wots::Signature,
+    auth_path: [[u8; N]; H],
+}
+
+impl<const H: usize> Signature<H> {
+    /// Size of the encoding in bytes.
+    pub const SIZE: usize = signature_size(H);
+
+    /// Leaf index used for the signature.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Encode into `out`, returning the encoded length [`SIZE`](Self::SIZE).
+    pub fn encode(&self, out: &mut [u8]) -> Result<usize, Error> {
+        let out = out.get_mut(..Self::SIZE).ok_or(Error::BufferTooSmall)?;
+        let (index, rest) = out.split_at_mut(4);
+        let (randomness, rest) = rest.split_at_mut(N);
+        let (wots, auth_path) = rest.split_at_mut(wots::SIGNATURE_SIZE);
+
+        index.copy_from_slice(&self.index.to_le_bytes());
+        randomness.copy_from_slice(&self.randomness);
+        wots.copy_from_slice(&self.wots.to_bytes());
+        auth_path.copy_from_slice(self.auth_path.as_flattened());
+        Ok(Self::SIZE)
+    }
+
+    /// Decode from exactly [`SIZE`](Self::SIZE) bytes.
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != Self::SIZE {
+            return Err(Error::InvalidEncoding);
+        }
+        let (index, rest) = bytes.split_at(4);
+        let (randomness, rest) = rest.split_at(N);
+        let (wots, auth_path_bytes) = rest.split_at(wots::SIGNATURE_SIZE);
+
+        let index = u32::from_le_bytes(index.try_into().unwrap());
+        if u64::from(index) >= 1u64 << H {
+            return Err(Error::InvalidEncoding);
+        }
+        let mut auth_path = [[0u8; N]; H];
+        auth_path
+            .as_flattened_mut()
+            .copy_from_slice(auth_path_bytes);
+
+        Ok(Self {
+            index,
+            randomness: randomness.try_into().unwrap(),
+            wots: wots::Signature::from_bytes(wots.try_into().unwrap()),
+            auth_path,
+        })
+    }
+}
+
+impl<const H: usize> TryFrom<&[u8]> for Signature<H> {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        Self::decode(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,91 @@
+use super::N;
+use crate::Hasher;
+
+const DOMAIN_THASH: u8 = 0;
+const DOMAIN_PRF: u8 = 1;
+const DOMAIN_PRF_MSG: u8 = 2;
+const DOMAIN_H_MSG: u8 = 3;
+
+const TYPE_OTS: u32 = 0;
+const TYPE_OTS_PK: u32 = 1;
+const TYPE_TREE: u32 = 2;
+
+/// Position of a hash call in the scheme, the tweak that keeps every call distinct.
+///
+/// The words are the type, the leaf index, the chain index or tree height and the hash index or
+/// tree node index, encoded in big-endian.
+#[derive(Clone, Copy)]
+pub(super) struct Address([u32; 4]);
+
+impl Address {
+    /// Chain `chain` of the WOTS+ key of `leaf`.
+    pub(super) fn chain(leaf: u32, chain: u32) -> Self {
+        Self([TYPE_OTS, leaf, chain, 0])
+    }
+
+    /// Step `hash` of the chain.
+    pub(super) fn with_hash(mut self, hash: u32) -> Self {
+        self.0[3] = hash;
+        self
+    }
+
+    /// Compression of the WOTS+ public key of `leaf`.
+    pub(super) fn ots_public_key(leaf: u32) -> Self {
+        Self([TYPE_OTS_PK, leaf, 0, 0])
+    }
+
+    /// Tree node `index` over two children of height `height`.
+    pub(super) fn tree(height: u32, index: u32) -> Self {
+        Self([TYPE_TREE, 0, height, index])
+    }
+
+    fn to_bytes(self) -> [u8; 16] {
+        let mut bytes = [0u8; 16];
+        for (chunk, word) in bytes.chunks_exact_mut(4).zip(self.0) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        bytes
+    }
+}
+
+/// Tweakable hash of `inputs` under the public seed and `address`.
+pub(super) fn thash(pub_seed: &[u8; N], address: Address, inputs: &[&[u8]]) -> [u8; N] {
+    let mut hasher = Hasher::new();
+    hasher.update(&[DOMAIN_THASH]);
+    hasher.update(pub_seed);
+    hasher.update(&address.to_bytes());
+    for input in inputs {
+        hasher.update(input);
+    }
+    hasher.finalize()
+}
+
+/// Secret chain start for `address`.
+pub(super) fn prf(sk_seed: &[u8; N], pub_seed: &[u8; N], address: Address) -> [u8; N] {
+    let mut hasher = Hasher::new();
+    hasher.update(&[DOMAIN_PRF]);
+    hasher.update(sk_seed);
+    hasher.update(pub_seed);
+    hasher.update(&address.to_bytes());
+    hasher.finalize()
+}
+
+/// Signature randomness for the signature with `index`.
+pub(super) fn prf_msg(sk_prf: &[u8; N], index: u32) -> [u8; N] {
+    let mut hasher = Hasher::new();
+    hasher.update(&[DOMAIN_PRF_MSG]);
+    hasher.update(sk_prf);
+    hasher.update(&index.to_be_bytes());
+    hasher.finalize()
+}
+
+/// Digest of `message` that is signed with the one-time key.
+pub(super) fn h_msg(randomness: &[u8; N], root: &[u8; N], index: u32, message: &[u8]) -> [u8; N] {
+    let mut hasher = Hasher::new();
+    hasher.update(&[DOMAIN_H_MSG]);
+    hasher.update(randomness);
+    hasher.update(root);
+    hasher.update(&index.to_be_bytes());
+    hasher.update(message);
+    hasher.finalize()
+}
@@ -0,0 +1,260 @@
+extern crate std;
+use super::*;
+use std::vec;
+use std::vec::Vec;
+
+// Key generation and signing hash thousands of times, the tests doing so are too slow under miri.
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn signing_key<const H: usize>() -> SigningKey<H> {
+    SigningKey::new(&[1; N], &[2; N], &[3; N])
+}
+
+fn encode<const H: usize>(signature: &Signature<H>) -> Vec<u8> {
+    let mut out = vec![0u8; Signature::<H>::SIZE];
+    assert_eq!(signature.encode(&mut out), Ok(Signature::<H>::SIZE));
+    out
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_known_answer() {
+    let mut signing_key = signing_key::<2>();
+    assert_eq!(
+        signing_key.verifying_key().to_bytes().to_vec(),
+        hex_to_bytes(
+            "8c52fc6c57dc617532f9fb5753dceae82ecbfe51cebe7d8cf1d2ce6c85db1735\
+             0303030303030303030303030303030303030303030303030303030303030303"
+        )
+    );
+
+    let signature = signing_key.sign(b"firmware v1.2.3").unwrap();
+    assert_eq!(
+        crate::hash(&encode(&signature)).to_vec(),
+        hex_to_bytes("a2046d50caab46d6f82987a7fd1fa712b5b43abd6934b5f01b28fb50ef24cdca")
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_sign_every_leaf() {
+    let mut signing_key = signing_key::<3>();
+    let verifying_key = signing_key.verifying_key();
+
+    for index in 0..SigningKey::<3>::MAX_SIGNATURES {
+        assert_eq!(signing_key.next_index(), index);
+        assert_eq!(signing_key.remaining(), 8 - index);
+
+        let message = [index as u8; 10];
+        let signature = signing_key.sign(&message).unwrap();
+        assert_eq!(signature.index(), index);
+        verifying_key.verify(&message, &signature).unwrap();
+        assert_eq!(
+            verifying_key.verify(b"other message", &signature),
+            Err(Error::VerificationFailed)
+        );
+    }
+
+    assert_eq!(signing_key.remaining(), 0);
+    assert_eq!(signing_key.sign(b"message"), Err(Error::KeyExhausted));
+    assert_eq!(signing_key.next_index(), 8);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_persisted_state() {
+    let mut signing_key = signing_key::<2>();
+    let first = signing_key.sign(b"message").unwrap();
+
+    let mut restored = SigningKey::<2>::from_bytes(&signing_key.to_bytes()).unwrap();
+    assert_eq!(restored.next_index(), 1);
+    assert_eq!(restored.verifying_key(), signing_key.verifying_key());
+
+    // The restored key continues with the next leaf.
+    let second = restored.sign(b"message").unwrap();
+    assert_eq!(second.index(), 1);
+    assert_ne!(first, second);
+    signing_key
+        .verifying_key()
+        .verify(b"message", &second)
+        .unwrap();
+}
+
+#[test]
+fn test_signing_key_encoding() {
+    let mut bytes = [7u8; SIGNING_KEY_SIZE];
+    bytes[4 * N..].copy_from_slice(&4u32.to_le_bytes());
+    let signing_key = SigningKey::<2>::from_bytes(&bytes).unwrap();
+    assert_eq!(signing_key.remaining(), 0);
+    assert_eq!(signing_key.to_bytes(), bytes);
+
+    bytes[4 * N..].copy_from_slice(&5u32.to_le_bytes());
+    assert!(SigningKey::<2>::from_bytes(&bytes).is_err());
+}
+
+#[test]
+fn test_signature_encoding() {
+    let size = Signature::<2>::SIZE;
+    assert_eq!(size, signature_size(2));
+    assert_eq!(size, 4 + 32 + 67 * 32 + 2 * 32);
+
+    let mut bytes: Vec<u8> = (0..size).map(|i| i as u8).collect();
+    bytes[..4].copy_from_slice(&3u32.to_le_bytes());
+    let signature = Signature::<2>::decode(&bytes).unwrap();
+    assert_eq!(signature.index(), 3);
+    assert_eq!(encode(&signature), bytes);
+    assert_eq!(Signature::<2>::try_from(&bytes[..]), Ok(signature.clone()));
+
+    let mut out = vec![0u8; size - 1];
+    assert_eq!(signature.encode(&mut out), Err(Error::BufferTooSmall));
+
+    assert_eq!(
+        Signature::<2>::decode(&bytes[..size - 1]),
+        Err(Error::InvalidEncoding)
+    );
+    assert_eq!(
+        Signature::<2>::decode(&[&bytes[..], &[0]].concat()),
+        Err(Error::InvalidEncoding)
+    );
+
+    // The index must address a leaf of the tree.
+    bytes[..4].copy_from_slice(&4u32.to_le_bytes());
+    assert_eq!(Signature::<2>::decode(&bytes), Err(Error::InvalidEncoding));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_tampered_signature() {
+    let mut signing_key = signing_key::<2>();
+    let verifying_key = signing_key.verifying_key();
+    signing_key.sign(b"message").unwrap();
+    let signature = signing_key.sign(b"message").unwrap();
+    let encoded = encode(&signature);
+
+    for position in [
+        0,
+        4,
+        4 + N,
+        4 + N + wots::SIGNATURE_SIZE - 1,
+        encoded.len() - 1,
+    ] {
+        let mut tampered = encoded.clone();
+        tampered[position] ^= 1;
+        let tampered = Signature::<2>::decode(&tampered).unwrap();
+        assert_eq!(
+            verifying_key.verify(b"message", &tampered),
+            Err(Error::VerificationFailed),
+            "position {position}"
+        );
+    }
+
+    let other_key = SigningKey::<2>::new(&[1; N], &[2; N], &[4; N]).verifying_key();
+    assert_eq!(
+        other_key.verify(b"message", &signature),
+        Err(Error::VerificationFailed)
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_signature_traits() {
+    use signature::{Keypair, SignerMut, Verifier};
+
+    let mut signing_key = signing_key::<2>();
+    let verifying_key = Keypair::verifying_key(&signing_key);
+
+    let signature: Signature<2> = signing_key.try_sign(b"message").unwrap();
+    assert!(Verifier::verify(&verifying_key, b"message", &signature).is_ok());
+    assert!(Verifier::verify(&verifying_key, b"massage", &signature).is_err());
+
+    for _ in 1..4 {
+        signing_key.try_sign(b"message").unwrap();
+    }
+    assert!(signing_key.try_sign(b"message").is_err());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_wots() {
+    let signing_key = wots::SigningKey::new(&[1; N], &[2; N]);
+    let verifying_key = signing_key.verifying_key();
+    assert_eq!(
+        wots::VerifyingKey::from_bytes(&verifying_key.to_bytes()),
+        verifying_key
+    );
+
+    let signature = signing_key.sign(b"message");
+    verifying_key.verify(b"message", &signature).unwrap();
+    assert_eq!(
+        verifying_key.verify(b"massage", &signature),
+        Err(Error::VerificationFailed)
+    );
+    assert!(signature::Verifier::verify(&verifying_key, b"message", &signature).is_ok());
+
+    let mut tampered = signature.to_bytes();
+    tampered[wots::SIGNATURE_SIZE - 1] ^= 1;
+    assert_eq!(
+        verifying_key.verify(b"message", &wots::Signature::from_bytes(&tampered)),
+        Err(Error::VerificationFailed)
+    );
+
+    let other_key = wots::SigningKey::new(&[1; N], &[3; N]).verifying_key();
+    assert_eq!(
+        other_key.verify(b"message", &signature),
+        Err(Error::VerificationFailed)
+    );
+}
+
+#[test]
+fn test_wots_signature_encoding() {
+    let bytes: Vec<u8> = (0..wots::SIGNATURE_SIZE).map(|i| i as u8).collect();
+    let signature = wots::Signature::try_from(&bytes[..]).unwrap();
+    assert_eq!(signature.to_bytes().to_vec(), bytes);
+
+    assert_eq!(
+        wots::Signature::try_from(&bytes[1..]),
+        Err(Error::InvalidEncoding)
+    );
+}
+
+#[test]
+fn test_wots_digits() {
+    // The checksum is largest for the smallest digits, so raising a digit to forge a signature
+    // must lower a checksum digit.
+    let digits = wots::digits(&[0; N]);
+    assert!(digits[..wots::LEN1].iter().all(|&d| d == 0));
+    assert_eq!(digits[wots::LEN1..], [0x3, 0xc, 0x0]);
+
+    let digits = wots::digits(&[0xff; N]);
+    assert!(digits[..wots::LEN1].iter().all(|&d| d == 15));
+    assert_eq!(digits[wots::LEN1..], [0, 0, 0]);
+
+    let mut digest = [0; N];
+    digest[0] = 0xa5;
+    let digits = wots::digits(&digest);
+    assert_eq!(digits[..3], [0xa, 0x5, 0x0]);
+    assert_eq!(digits[wots::LEN1..], [0x3, 0xb, 0x1]);
+}
+
+#[test]
+#[cfg(feature = "getrandom")]
+#[cfg_attr(miri, ignore)]
+fn test_generate() {
+    let mut signing_key = SigningKey::<1>::generate();
+    let verifying_key = signing_key.verifying_key();
+    assert_ne!(SigningKey::<1>::generate().verifying_key(), verifying_key);
+
+    let signature = signing_key.sign(b"message").unwrap();
+    verifying_key.verify(b"message", &signature).unwrap();
+
+    let signing_key = wots::SigningKey::generate();
+    let verifying_key = signing_key.verifying_key();
+    let signature = signing_key.sign(b"message");
+    verifying_key.verify(b"message", &signature).unwrap();
+}
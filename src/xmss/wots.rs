@@ -0,0 +1,267 @@
+//! WOTS+ one-time signatures with Winternitz parameter 16 over `hash/gimli24v1`.
+//!
+//! A key must sign a single message, a second signature with the same key reveals enough chain
+//! values to forge signatures. [`SigningKey::sign`] therefore consumes the key. Use the XMSS
+//! keys of the parent module to sign many messages with one public key.
+//!
+//! ```
+//! # #[cfg(not(miri))] {
+//! use gimli_crypto::xmss::wots::SigningKey;
+//!
+//! let signing_key = SigningKey::new(&[1u8; 32], &[2u8; 32]);
+//! let verifying_key = signing_key.verifying_key();
+//!
+//! let signature = signing_key.sign(b"firmware v1.2.3");
+//! assert!(verifying_key.verify(b"firmware v1.2.3", &signature).is_ok());
+//! assert!(verifying_key.verify(b"firmware v1.2.4", &signature).is_err());
+//! # }
+//! ```
+
+use super::hash::{Address, h_msg, prf, thash};
+use super::{Error, N};
+use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Winternitz parameter, the length of each hash chain.
+pub const W: usize = 16;
+
+/// Number of chains signing the message digest.
+pub const LEN1: usize = 2 * N;
+
+/// Number of chains signing the checksum.
+pub const LEN2: usize = 3;
+
+/// Total number of chains.
+pub const LEN: usize = LEN1 + LEN2;
+
+/// Size of a signature in bytes.
+pub const SIGNATURE_SIZE: usize = LEN * N;
+
+/// Size of an encoded verifying key in bytes, `pub_seed || public_key`.
+pub const PUBLIC_KEY_SIZE: usize = 2 * N;
+
+/// Leaf index of standalone keys.
+const STANDALONE_LEAF: u32 = 0;
+
+/// Chain positions signing `digest`, its base-16 digits followed by those of the checksum.
+pub(super) fn digits(digest: &[u8; N]) -> [u8; LEN] {
+    let mut digits = [0u8; LEN];
+    for (pair, byte) in digits[..LEN1].chunks_exact_mut(2).zip(digest) {
+        pair[0] = byte >> 4;
+        pair[1] = byte & 0x0f;
+    }
+
+    let checksum: u32 = digits[..LEN1]
+        .iter()
+        .map(|&digit| (W - 1) as u32 - u32::from(digit))
+        .sum();
+    for (i, digit) in digits[LEN1..].iter_mut().enumerate() {
+        *digit = (checksum >> (4 * (LEN2 - 1 - i)) & 0x0f) as u8;
+    }
+    digits
+}
+
+/// Apply `steps` chain steps to `value` starting at position `start`.
+fn chain(pub_seed: &[u8; N], address: Address, value: &[u8; N], start: u8, steps: u8) -> [u8; N] {
+    let mut value = *value;
+    for hash in start..start + steps {
+        value = thash(pub_seed, address.with_hash(u32::from(hash)), &[&value]);
+    }
+    value
+}
+
+/// Secret start of chain `chain` of the key of `leaf`.
+fn chain_secret(sk_seed: &[u8; N], pub_seed: &[u8; N], leaf: u32, chain: usize) -> [u8; N] {
+    prf(sk_seed, pub_seed, Address::chain(leaf, chain as u32))
+}
+
+/// Compress the chain ends into the public key.
+fn compress(pub_seed: &[u8; N], leaf: u32, ends: &[[u8; N]; LEN]) -> [u8; N] {
+    thash(
+        pub_seed,
+        Address::ots_public_key(leaf),
+        &[ends.as_flattened()],
+    )
+}
+
+/// Public key of the key of `leaf`.
+pub(super) fn public_key(sk_seed: &[u8; N], pub_seed: &[u8; N], leaf: u32) -> [u8; N] {
+    let ends = core::array::from_fn(|i| {
+        let mut secret = chain_secret(sk_seed, pub_seed, leaf, i);
+        let end = chain(
+            pub_seed,
+            Address::chain(leaf, i as u32),
+            &secret,
+            0,
+            (W - 1) as u8,
+        );
+        secret.zeroize();
+        end
+    });
+    compress(pub_seed, leaf, &ends)
+}
+
+/// Sign `digest` with the key of `leaf`.
+pub(super) fn sign_digest(
+    sk_seed: &[u8; N],
+    pub_seed: &[u8; N],
+    leaf: u32,
+    digest: &[u8; N],
+) -> Signature {
+    let digits = digits(digest);
+    Signature(core::array::from_fn(|i| {
+        let mut secret = chain_secret(sk_seed, pub_seed, leaf, i);
+        let value = chain(
+            pub_seed,
+            Address::chain(leaf, i as u32),
+            &secret,
+            0,
+            digits[i],
+        );
+        secret.zeroize();
+        value
+    }))
+}
+
+/// Public key of the key of `leaf` implied by a signature of `digest`.
+pub(super) fn public_key_from_signature(
+    pub_seed: &[u8; N],
+    leaf: u32,
+    digest: &[u8; N],
+    signature: &Signature,
+) -> [u8; N] {
+    let digits = digits(digest);
+    let ends = core::array::from_fn(|i| {
+        chain(
+            pub_seed,
+            Address::chain(leaf, i as u32),
+            &signature.0[i],
+            digits[i],
+            (W - 1) as u8 - digits[i],
+        )
+    });
+    compress(pub_seed, leaf, &ends)
+}
+
+/// A WOTS+ signature, one chain value per chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature([[u8; N]; LEN]);
+
+impl Signature {
+    /// Encode as the concatenated chain values.
+    pub fn to_bytes(&self) -> [u8; SIGNATURE_SIZE] {
+        let mut bytes = [0u8; SIGNATURE_SIZE];
+        bytes.copy_from_slice(self.0.as_flattened());
+        bytes
+    }
+
+    /// Decode from the concatenated chain values.
+    pub fn from_bytes(bytes: &[u8; SIGNATURE_SIZE]) -> Self {
+        let mut signature = [[0u8; N]; LEN];
+        signature.as_flattened_mut().copy_from_slice(bytes);
+        Self(signature)
+    }
+}
+
+impl TryFrom<&[u8]> for Signature {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes = bytes.try_into().map_err(|_| Error::InvalidEncoding)?;
+        Ok(Self::from_bytes(bytes))
+    }
+}
+
+/// A WOTS+ signing key, usable for one signature.
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct SigningKey {
+    sk_seed: [u8; N],
+    pub_seed: [u8; N],
+    public_key: [u8; N],
+}
+
+impl SigningKey {
+    /// Create a key from a secret seed and a public seed, computing the public key.
+    pub fn new(sk_seed: &[u8; N], pub_seed: &[u8; N]) -> Self {
+        Self {
+            sk_seed: *sk_seed,
+            pub_seed: *pub_seed,
+            public_key: public_key(sk_seed, pub_seed, STANDALONE_LEAF),
+        }
+    }
+
+    /// Create a key from random seeds.
+    #[cfg(feature = "getrandom")]
+    pub fn generate() -> Self {
+        let mut seeds = super::random_seeds::<2>();
+        let key = Self::new(&seeds[0], &seeds[1]);
+        seeds.zeroize();
+        key
+    }
+
+    /// The public key verifying signatures of this key.
+    pub fn verifying_key(&self) -> VerifyingKey {
+        VerifyingKey {
+            pub_seed: self.pub_seed,
+            public_key: self.public_key,
+        }
+    }
+
+    /// Sign `message`, consuming the key.
+    pub fn sign(self, message: &[u8]) -> Signature {
+        let digest = h_msg(&self.pub_seed, &self.public_key, STANDALONE_LEAF, message);
+        sign_digest(&self.sk_seed, &self.pub_seed, STANDALONE_LEAF, &digest)
+    }
+}
+
+impl signature::Keypair for SigningKey {
+    type VerifyingKey = VerifyingKey;
+
+    fn verifying_key(&self) -> VerifyingKey {
+        self.verifying_key()
+    }
+}
+
+/// A WOTS+ verifying key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifyingKey {
+    pub_seed: [u8; N],
+    public_key: [u8; N],
+}
+
+impl VerifyingKey {
+    /// Encode as `pub_seed || public_key`.
+    pub fn to_bytes(&self) -> [u8; PUBLIC_KEY_SIZE] {
+        let mut bytes = [0u8; PUBLIC_KEY_SIZE];
+        bytes[..N].copy_from_slice(&self.pub_seed);
+        bytes[N..].copy_from_slice(&self.public_key);
+        bytes
+    }
+
+    /// Decode from `pub_seed || public_key`.
+    pub fn from_bytes(bytes: &[u8; PUBLIC_KEY_SIZE]) -> Self {
+        let (pub_seed, public_key) = bytes.split_at(N);
+        Self {
+            pub_seed: pub_seed.try_into().unwrap(),
+            public_key: public_key.try_into().unwrap(),
+        }
+    }
+
+    /// Check `signature` of `message`.
+    pub fn verify(&self, message: &[u8], signature: &Signature) -> Result<(), Error> {
+        let digest = h_msg(&self.pub_seed, &self.public_key, STANDALONE_LEAF, message);
+        let public_key =
+            public_key_from_signature(&self.pub_seed, STANDALONE_LEAF, &digest, signature);
+        if bool::from(public_key.ct_eq(&self.public_key)) {
+            Ok(())
+        } else {
+            Err(Error::VerificationFailed)
+        }
+    }
+}
+
+impl signature::Verifier<Signature> for VerifyingKey {
+    fn verify(&self, message: &[u8], signature: &Signature) -> Result<(), signature::Error> {
+        VerifyingKey::verify(self, message, signature).map_err(|_| signature::Error::new())
+    }
+}
@@ -0,0 +1,188 @@
+//! Differential tests against the reference C implementation vendored in `reference/`.
+
+// The reference implementation is called over FFI, which Miri cannot execute.
+#![cfg(not(miri))]
+
+use gimli_crypto::aead::generic_array::GenericArray;
+use gimli_crypto::{
+    AeadInPlace, AuthenticationFailed, Digest, GimliAead, GimliHash, Hasher, KEY_SIZE, KeyInit,
+    NONCE_SIZE, TAG_SIZE, decrypt_in_place, encrypt_in_place, hash,
+};
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+/// Longest AAD/message/hash input used by the property tests, several blocks long.
+const MAX_LEN: usize = 128;
+
+/// Fill `buffer` with a deterministic byte pattern that depends on `seed`.
+fn fill(buffer: &mut [u8], seed: u32) {
+    let mut x = seed.wrapping_mul(0x9e37_79b9) | 1;
+    for byte in buffer {
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        *byte = x as u8;
+    }
+}
+
+/// Feed `data` to `update` in chunks whose lengths are taken from `splits`.
+fn split_update(data: &[u8], splits: &[usize], mut update: impl FnMut(&[u8])) {
+    let mut rest = data;
+    for &split in splits {
+        let (chunk, tail) = rest.split_at(split.min(rest.len()));
+        update(chunk);
+        rest = tail;
+    }
+    update(rest);
+}
+
+fn encrypt(key: &[u8; KEY_SIZE], nonce: &[u8; NONCE_SIZE], aad: &[u8], pt: &[u8]) -> Vec<u8> {
+    let mut buffer = pt.to_vec();
+    let tag = encrypt_in_place(key, nonce, aad, &mut buffer);
+    buffer.extend_from_slice(&tag);
+    buffer
+}
+
+#[test]
+fn aead_all_lengths() {
+    let key = {
+        let mut key = [0u8; KEY_SIZE];
+        fill(&mut key, 1);
+        key
+    };
+    let nonce = {
+        let mut nonce = [0u8; NONCE_SIZE];
+        fill(&mut nonce, 2);
+        nonce
+    };
+
+    for aad_len in 0..=64 {
+        for pt_len in 0..=64 {
+            let mut aad = vec![0u8; aad_len];
+            let mut pt = vec![0u8; pt_len];
+            fill(&mut aad, (aad_len * 65 + pt_len) as u32);
+            fill(&mut pt, (pt_len * 65 + aad_len) as u32);
+
+            let expected = gimli_reference::aead_encrypt(&key, &nonce, &aad, &pt);
+            assert_eq!(
+                encrypt(&key, &nonce, &aad, &pt),
+                expected,
+                "AAD length {aad_len}, plaintext length {pt_len}"
+            );
+        }
+    }
+}
+
+#[test]
+fn hash_all_lengths() {
+    for len in 0..=4 * MAX_LEN {
+        let mut data = vec![0u8; len];
+        fill(&mut data, len as u32);
+
+        assert_eq!(
+            hash(&data),
+            gimli_reference::hash(&data),
+            "input length {len}"
+        );
+    }
+}
+
+#[test]
+fn hash_kat_matches_reference() {
+    let digests = include_str!("../LWC_HASH_KAT_256.txt")
+        .lines()
+        .filter_map(|line| line.strip_prefix("MD = "));
+
+    let message: Vec<u8> = (0..=u8::MAX).cycle().take(1024).collect();
+    let mut count = 0;
+    for (len, digest) in digests.enumerate() {
+        let expected: String = gimli_reference::hash(&message[..len])
+            .iter()
+            .map(|byte| format!("{byte:02X}"))
+            .collect();
+        assert_eq!(digest, expected, "message length {len}");
+        count += 1;
+    }
+    assert_eq!(count, 1025);
+}
+
+proptest! {
+    #[test]
+    fn aead_encrypt_matches_reference(
+        key in any::<[u8; KEY_SIZE]>(),
+        nonce in any::<[u8; NONCE_SIZE]>(),
+        aad in vec(any::<u8>(), 0..=MAX_LEN),
+        pt in vec(any::<u8>(), 0..=MAX_LEN),
+    ) {
+        let expected = gimli_reference::aead_encrypt(&key, &nonce, &aad, &pt);
+        prop_assert_eq!(&encrypt(&key, &nonce, &aad, &pt), &expected);
+
+        let cipher = GimliAead::new(&GenericArray::from(key));
+        let mut buffer = pt.clone();
+        let tag = cipher
+            .encrypt_in_place_detached(&GenericArray::from(nonce), &aad, &mut buffer)
+            .unwrap();
+        prop_assert_eq!(&buffer[..], &expected[..pt.len()]);
+        prop_assert_eq!(tag.as_slice(), &expected[pt.len()..]);
+    }
+
+    #[test]
+    fn aead_decrypt_matches_reference(
+        key in any::<[u8; KEY_SIZE]>(),
+        nonce in any::<[u8; NONCE_SIZE]>(),
+        aad in vec(any::<u8>(), 0..=MAX_LEN),
+        pt in vec(any::<u8>(), 0..=MAX_LEN),
+        flip in prop::option::of((any::<usize>(), 1..=u8::MAX)),
+    ) {
+        let mut sealed = gimli_reference::aead_encrypt(&key, &nonce, &aad, &pt);
+        if let Some((position, mask)) = flip {
+            let len = sealed.len();
+            sealed[position % len] ^= mask;
+        }
+        let expected = gimli_reference::aead_decrypt(&key, &nonce, &aad, &sealed);
+        prop_assert_eq!(expected.is_some(), flip.is_none());
+
+        let (ciphertext, tag) = sealed.split_at(sealed.len() - TAG_SIZE);
+        let tag: [u8; TAG_SIZE] = tag.try_into().unwrap();
+
+        let mut buffer = ciphertext.to_vec();
+        let result = decrypt_in_place(&key, &nonce, &aad, &mut buffer, &tag);
+        match &expected {
+            Some(plaintext) => {
+                prop_assert_eq!(result, Ok(()));
+                prop_assert_eq!(&buffer, plaintext);
+            }
+            None => prop_assert_eq!(result, Err(AuthenticationFailed)),
+        }
+
+        let cipher = GimliAead::new(&GenericArray::from(key));
+        let mut buffer = ciphertext.to_vec();
+        let result = cipher.decrypt_in_place_detached(
+            &GenericArray::from(nonce),
+            &aad,
+            &mut buffer,
+            &GenericArray::from(tag),
+        );
+        prop_assert_eq!(result.is_ok(), expected.is_some());
+        if let Some(plaintext) = &expected {
+            prop_assert_eq!(&buffer, plaintext);
+        }
+    }
+
+    #[test]
+    fn hash_matches_reference(
+        data in vec(any::<u8>(), 0..=4 * MAX_LEN),
+        splits in vec(0..=2 * MAX_LEN, 0..8),
+    ) {
+        let expected = gimli_reference::hash(&data);
+        prop_assert_eq!(hash(&data), expected);
+
+        let mut hasher = Hasher::new();
+        split_update(&data, &splits, |chunk| hasher.update(chunk));
+        prop_assert_eq!(hasher.finalize(), expected);
+
+        let mut digest = GimliHash::new();
+        split_update(&data, &splits, |chunk| Digest::update(&mut digest, chunk));
+        prop_assert_eq!(&digest.finalize()[..], &expected[..]);
+    }
+}